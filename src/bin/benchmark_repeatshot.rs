@@ -20,7 +20,9 @@ use url::Url;
 
 use shanan::{
   FromUrl,
-  model::{CocoLabel, DetectionNhwc},
+  input::Batcher,
+  model::{BatchPostprocess, Batched, CocoLabel, DetectionNhwc, LabelSet, RuntimeLabel},
+  output::BatchRender,
   task::BenchmarkTask,
 };
 use shanan_trait::Task;
@@ -39,9 +41,16 @@ pub struct Args {
   /// 输出路径
   #[arg(long, value_name = "OUTPUT")]
   pub output: Url,
+  /// 类别文件（TOML、JSON 或每行一个名称的文本），默认读取模型 URL 的 `labels` 参数，
+  /// 均未设置时使用 COCO 类别
+  #[arg(long, value_name = "LABELS")]
+  pub labels: Option<String>,
   /// 基准测试重复次数
   #[arg(long, value_name = "TIMES", default_value_t = 1000)]
   pub times: u32,
+  /// 每次推理的帧数，大于 1 时需要导出为对应批次的模型
+  #[arg(long, value_name = "BATCH", default_value_t = 1)]
+  pub batch: usize,
   /// benchmark 结果输出格式
   #[arg(long, value_name = "RESULT")]
   pub save: Option<PathBuf>,
@@ -61,21 +70,42 @@ fn main() -> Result<()> {
   info!("输入来源: {}", args.input);
   info!("输出路径: {}", args.output);
 
+  let labels = match &args.labels {
+    Some(path) => LabelSet::load(path)?,
+    None => LabelSet::from_url(&args.model)?,
+  };
+  RuntimeLabel::install_or::<CocoLabel>(labels);
+
   let input_image = shanan::input::InputWrapper::from_url(&args.input)?;
+  let quality = shanan::input::QualityGate::from_url(&args.input)?;
   let model: DetectionNhwc<640, 640> = shanan::model::Detection::from_url(&args.model)?;
-  let postprocess: shanan::model::DetectionPostprocess<640, 640, CocoLabel, Runtime> =
+  let postprocess: shanan::model::DetectionPostprocess<640, 640, RuntimeLabel, Runtime> =
     shanan::model::DetectionPostprocess::from_url(&args.model)?;
+  let postprocess =
+    shanan::model::LabelMap::from_url(&args.model)?.apply::<RuntimeLabel, _>(postprocess)?;
+  let postprocess = shanan::model::TensorRecorder::from_url(&args.model)?.apply(postprocess)?;
   let output = shanan::output::OutputWrapper::from_url(&args.output)?;
 
+  // 循环输入，使每一批都是满的
+  let batcher = Batcher::default().size(args.batch);
+  let input = input_image.into_nhwc();
+  // 输入因错误提前结束时，以输入的错误作为退出错误，而不是任务看到的输入结束
+  let status = input.status();
+  let input = batcher.apply(quality.apply(input).cycle());
+
+  // 各项耗时均为每批的耗时
   let record = BenchmarkTask::default().with_times(args.times).run_task(
-    input_image.into_nhwc(),
-    model,
-    postprocess,
-    output,
-  )?;
+    input,
+    Batched::new(model),
+    BatchPostprocess::new(postprocess),
+    BatchRender::new(output),
+  );
+  status.check()?;
+  let record = record?;
 
   if let Some(save_path) = args.save {
     let data = json!({
+      "batch": batcher.batch_size(),
       "data_load": record.data_load.as_secs_f64() * 1000.0,
       "inference": record.inference.as_secs_f64() * 1000.0,
       "postprocess": record.postprocess.as_secs_f64() * 1000.0,
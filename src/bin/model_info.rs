@@ -0,0 +1,65 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/bin/model_info.rs - 打印模型的输入输出信息
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+use anyhow::Result;
+use clap::Parser;
+use rknpu::InitFlags;
+use url::Url;
+
+use shanan::model::{LabelSet, inspect_rknn};
+
+/// 打印 RKNN 模型的输入输出张量信息，并可检查输入大小与类别数量
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct Args {
+  /// 模型 URL，例如 `yolo26:///path/to/model.rknn`
+  #[arg(long, value_name = "MODEL")]
+  pub model: Url,
+  /// 期望的输入大小，形如 `640x640`
+  #[arg(long, value_name = "WxH")]
+  pub size: Option<String>,
+  /// 类别文件，检查模型输出的类别数量（支持 `yolo26://` 与 `classify://`）
+  #[arg(long, value_name = "LABELS")]
+  pub labels: Option<String>,
+}
+
+fn main() -> Result<()> {
+  tracing_subscriber::fmt::init();
+
+  let args = Args::parse();
+  let info = inspect_rknn(args.model.path(), InitFlags::default())?;
+  print!("{}", info);
+
+  if let Some(size) = &args.size {
+    let (w, h) = size
+      .split_once(['x', 'X', '*'])
+      .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+      .ok_or_else(|| anyhow::anyhow!("无效的输入大小: {}", size))?;
+    info.check_input_size(w, h)?;
+    println!("输入大小检查通过: {}×{}", w, h);
+  }
+
+  if let Some(path) = &args.labels {
+    let num_labels = LabelSet::load(path)?.len();
+    // 非类别通道数，取决于检测头
+    let extra = match args.model.scheme() {
+      "yolo26" => 4,
+      "classify" => 0,
+      scheme => anyhow::bail!("不支持检查 {}:// 模型的类别数量", scheme),
+    };
+    info.check_labels(extra, num_labels)?;
+    println!("类别数量检查通过: {}", num_labels);
+  }
+
+  Ok(())
+}
@@ -11,14 +11,18 @@
 //
 // Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use clap::Parser;
 use url::Url;
 
 use shanan::{
   FromUrl,
-  model::{CocoLabel, DetectionNhwc},
-  task::ContinuousTask,
+  frame::RgbNhwcFrame,
+  model::{
+    CocoLabel, DetectionNhwc, DetectionPostprocess, HotReloadBuilder, LabelMap, LabelSet,
+    ModelPoolBuilder, RuntimeLabel, Stage, TensorRecorder,
+  },
+  task::PooledTask,
 };
 use shanan_trait::Task;
 use tracing::info;
@@ -36,6 +40,10 @@ pub struct Args {
   /// 输出路径
   #[arg(long, value_name = "OUTPUT")]
   pub output: Url,
+  /// 类别文件（TOML、JSON 或每行一个名称的文本），默认读取模型 URL 的 `labels` 参数，
+  /// 均未设置时使用 COCO 类别
+  #[arg(long, value_name = "LABELS")]
+  pub labels: Option<String>,
 
   #[arg(long, value_name = "FRAME_NUMBER")]
   pub frame_number: Option<usize>,
@@ -55,15 +63,52 @@ fn main() -> Result<()> {
   info!("输入来源: {}", args.input);
   info!("输出路径: {}", args.output);
 
+  let labels = match &args.labels {
+    Some(path) => LabelSet::load(path)?,
+    None => LabelSet::from_url(&args.model)?,
+  };
+  RuntimeLabel::install_or::<CocoLabel>(labels);
+
   let input_image = shanan::input::InputWrapper::from_url(&args.input)?;
-  let model: DetectionNhwc<640, 640> = shanan::model::Detection::from_url(&args.model)?;
-  let postprocess: shanan::model::DetectionPostprocess<640, 640, CocoLabel, Runtime> =
-    shanan::model::DetectionPostprocess::from_url(&args.model)?;
+  let quality = shanan::input::QualityGate::from_url(&args.input)?;
+  // 模型 URL 的 `pool` 参数指定实例数量，多个实例并发推理；
+  // `reload` 参数启用热重载，模型文件或参数变化时在不中断流的情况下切换模型
+  let hot_reload = HotReloadBuilder::from_url(&args.model)?;
+  let url = hot_reload.effective_url(&args.model)?;
+  // 工作线程数量在启动时确定，重载时不能修改实例数量
+  let workers = ModelPoolBuilder::from_url(&url)?.instances();
+  let reload = hot_reload
+    .build(&args.model, move |url| -> Result<_> {
+      let pool = ModelPoolBuilder::from_url(url)?;
+      if pool.instances() != workers {
+        bail!(
+          "重载不能修改模型池实例数量: {} -> {}",
+          workers,
+          pool.instances()
+        );
+      }
+      let pool = pool.build(|_| DetectionNhwc::<640, 640>::from_url(url))?;
+      let post: DetectionPostprocess<640, 640, RuntimeLabel, Runtime> =
+        DetectionPostprocess::from_url(url)?;
+      let post = LabelMap::from_url(url)?.apply::<RuntimeLabel, _>(post)?;
+      Ok(Stage { model: pool, post })
+    })?
+    .probe(RgbNhwcFrame::from(vec![0; 640 * 640 * 3]));
+  let model = reload.model();
+  // 录制在按帧顺序调用的后处理中进行，多个实例并发推理或重载模型时文件编号仍与帧顺序一致
+  let postprocess = TensorRecorder::from_url(&url)?.apply(reload.postprocess())?;
+  let _watcher = reload.watch()?;
   let output = shanan::output::OutputWrapper::from_url(&args.output)?;
 
-  ContinuousTask::default()
+  let input = input_image.into_nhwc();
+  // 输入因错误提前结束时，以输入的错误作为退出错误，而不是任务看到的输入结束
+  let status = input.status();
+  let result = PooledTask::default()
     .with_frame_number(args.frame_number)
-    .run_task(input_image.into_nhwc(), model, postprocess, output)?;
+    .with_workers(workers)
+    .run_task(quality.apply(input), model, postprocess, output);
+  status.check()?;
+  result?;
 
   Ok(())
 }
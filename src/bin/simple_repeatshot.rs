@@ -17,7 +17,7 @@ use url::Url;
 
 use shanan::{
   FromUrl,
-  model::{CocoLabel, DetectionNhwc},
+  model::{CocoLabel, DetectionNhwc, LabelSet, RuntimeLabel},
   task::RepeatShotTask,
 };
 use shanan_trait::Task;
@@ -36,6 +36,10 @@ pub struct Args {
   /// 输出路径
   #[arg(long, value_name = "OUTPUT")]
   pub output: Url,
+  /// 类别文件（TOML、JSON 或每行一个名称的文本），默认读取模型 URL 的 `labels` 参数，
+  /// 均未设置时使用 COCO 类别
+  #[arg(long, value_name = "LABELS")]
+  pub labels: Option<String>,
 }
 
 #[cfg(not(feature = "cubecl-wgpu"))]
@@ -52,13 +56,28 @@ fn main() -> Result<()> {
   info!("输入来源: {}", args.input);
   info!("输出路径: {}", args.output);
 
+  let labels = match &args.labels {
+    Some(path) => LabelSet::load(path)?,
+    None => LabelSet::from_url(&args.model)?,
+  };
+  RuntimeLabel::install_or::<CocoLabel>(labels);
+
   let input_image = shanan::input::InputWrapper::from_url(&args.input)?;
+  let quality = shanan::input::QualityGate::from_url(&args.input)?;
   let model: DetectionNhwc<640, 640> = shanan::model::Detection::from_url(&args.model)?;
-  let postprocess: shanan::model::DetectionPostprocess<640, 640, CocoLabel, Runtime> =
+  let postprocess: shanan::model::DetectionPostprocess<640, 640, RuntimeLabel, Runtime> =
     shanan::model::DetectionPostprocess::from_url(&args.model)?;
+  let postprocess =
+    shanan::model::LabelMap::from_url(&args.model)?.apply::<RuntimeLabel, _>(postprocess)?;
+  let postprocess = shanan::model::TensorRecorder::from_url(&args.model)?.apply(postprocess)?;
   let output = shanan::output::OutputWrapper::from_url(&args.output)?;
 
-  RepeatShotTask.run_task(input_image.into_nhwc(), model, postprocess, output)?;
+  let input = input_image.into_nhwc();
+  // 输入因错误提前结束时，以输入的错误作为退出错误，而不是任务看到的输入结束
+  let status = input.status();
+  let result = RepeatShotTask.run_task(quality.apply(input), model, postprocess, output);
+  status.check()?;
+  result?;
 
   Ok(())
 }
@@ -11,10 +11,58 @@
 //
 // Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
 
-use crate::input::{AsNchwFrame, AsNhwcFrame};
+use std::time::{Duration, Instant};
+
+use crate::{
+  input::{AsNchwFrame, AsNhwcFrame},
+  model::BBox,
+};
 
 const RGB_CHANNELS: usize = 3;
 
+/// 帧时间戳
+///
+/// 记录帧在输入管道中的时间信息。对于没有时间概念的输入（例如图像文件），各字段均为 `None`。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameTimestamp {
+  /// 显示时间戳（PTS）
+  pub pts: Option<Duration>,
+  /// 解码时间戳（DTS）
+  pub dts: Option<Duration>,
+  /// 帧持续时间
+  pub duration: Option<Duration>,
+  /// 相对于管道启动的运行时间
+  pub running_time: Option<Duration>,
+  /// 估算的采集时刻，用于计算采集到渲染的延迟
+  pub captured_at: Option<Instant>,
+}
+
+impl FrameTimestamp {
+  /// 自采集以来经过的时间
+  pub fn latency(&self) -> Option<Duration> {
+    self.captured_at.map(|t| t.elapsed())
+  }
+}
+
+/// 帧图像质量指标
+///
+/// 由推理前的质量检测阶段计算，参见 [`crate::input::QualityGate`]。
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameQuality {
+  /// 清晰度（亮度通道 Laplacian 方差）
+  pub sharpness: f32,
+  /// 平均亮度，范围 0-255
+  pub luminance: f32,
+  /// 欠曝像素（所有通道接近 0）占比
+  pub clipped_dark: f32,
+  /// 过曝像素（任一通道接近 255）占比
+  pub clipped_bright: f32,
+  /// 与上一帧像素的平均绝对差（0-255），未检测画面冻结或没有上一帧时为 `None`
+  pub frame_diff: Option<f32>,
+  /// 是否满足质量阈值
+  pub passed: bool,
+}
+
 pub trait FrameFormat {
   fn tensor_format(&self) -> rknpu::TensorFormat;
   fn tensor_type(&self) -> rknpu::TensorType;
@@ -23,6 +71,8 @@ pub trait FrameFormat {
 #[derive(Debug, Clone)]
 pub struct RgbNchwFrame<const W: u32, const H: u32> {
   data: Box<[u8]>,
+  timestamp: FrameTimestamp,
+  quality: Option<FrameQuality>,
 }
 
 impl<const W: u32, const H: u32> From<Vec<u8>> for RgbNchwFrame<W, H> {
@@ -37,6 +87,8 @@ impl<const W: u32, const H: u32> From<Vec<u8>> for RgbNchwFrame<W, H> {
 
     Self {
       data: data.into_boxed_slice(),
+      timestamp: FrameTimestamp::default(),
+      quality: None,
     }
   }
 }
@@ -55,11 +107,32 @@ impl<const W: u32, const H: u32> Default for RgbNchwFrame<W, H> {
   fn default() -> Self {
     let size = RGB_CHANNELS * (W as usize) * (H as usize);
     let data = vec![0u8; size].into_boxed_slice();
-    Self { data }
+    Self {
+      data,
+      timestamp: FrameTimestamp::default(),
+      quality: None,
+    }
   }
 }
 
 impl<const W: u32, const H: u32> RgbNchwFrame<W, H> {
+  pub fn with_timestamp(mut self, timestamp: FrameTimestamp) -> Self {
+    self.timestamp = timestamp;
+    self
+  }
+
+  pub fn timestamp(&self) -> &FrameTimestamp {
+    &self.timestamp
+  }
+
+  pub fn set_quality(&mut self, quality: FrameQuality) {
+    self.quality = Some(quality);
+  }
+
+  pub fn quality(&self) -> Option<&FrameQuality> {
+    self.quality.as_ref()
+  }
+
   pub fn height(&self) -> usize {
     H as usize
   }
@@ -88,6 +161,8 @@ impl<const W: u32, const H: u32> AsNchwFrame<W, H> for RgbNchwFrame<W, H> {
 #[derive(Debug, Clone)]
 pub struct RgbNhwcFrame<const W: u32, const H: u32> {
   data: Box<[u8]>,
+  timestamp: FrameTimestamp,
+  quality: Option<FrameQuality>,
 }
 
 impl<const W: u32, const H: u32> From<Vec<u8>> for RgbNhwcFrame<W, H> {
@@ -102,6 +177,8 @@ impl<const W: u32, const H: u32> From<Vec<u8>> for RgbNhwcFrame<W, H> {
 
     Self {
       data: data.into_boxed_slice(),
+      timestamp: FrameTimestamp::default(),
+      quality: None,
     }
   }
 }
@@ -120,11 +197,32 @@ impl<const W: u32, const H: u32> Default for RgbNhwcFrame<W, H> {
   fn default() -> Self {
     let size = RGB_CHANNELS * (W as usize) * (H as usize);
     let data = vec![0u8; size].into_boxed_slice();
-    Self { data }
+    Self {
+      data,
+      timestamp: FrameTimestamp::default(),
+      quality: None,
+    }
   }
 }
 
 impl<const W: u32, const H: u32> RgbNhwcFrame<W, H> {
+  pub fn with_timestamp(mut self, timestamp: FrameTimestamp) -> Self {
+    self.timestamp = timestamp;
+    self
+  }
+
+  pub fn timestamp(&self) -> &FrameTimestamp {
+    &self.timestamp
+  }
+
+  pub fn set_quality(&mut self, quality: FrameQuality) {
+    self.quality = Some(quality);
+  }
+
+  pub fn quality(&self) -> Option<&FrameQuality> {
+    self.quality.as_ref()
+  }
+
   pub fn height(&self) -> usize {
     H as usize
   }
@@ -149,3 +247,65 @@ impl<const W: u32, const H: u32> AsNhwcFrame<W, H> for RgbNhwcFrame<W, H> {
     &self.data
   }
 }
+
+/// 一次推理的多帧，例如多路摄像头各取一帧，或目录中连续的若干张图像
+#[derive(Debug, Clone)]
+pub struct FrameBatch<F> {
+  frames: Vec<F>,
+}
+
+impl<F> From<Vec<F>> for FrameBatch<F> {
+  fn from(frames: Vec<F>) -> Self {
+    Self { frames }
+  }
+}
+
+impl<F> FrameBatch<F> {
+  pub fn frames(&self) -> &[F] {
+    &self.frames
+  }
+
+  pub fn into_frames(self) -> Vec<F> {
+    self.frames
+  }
+
+  pub fn len(&self) -> usize {
+    self.frames.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.frames.is_empty()
+  }
+}
+
+/// 从 NHWC 图像中裁剪区域并双线性缩放，区域超出图像的部分按边缘像素填充
+pub fn crop_resize(
+  src: &[u8],
+  (src_w, src_h): (usize, usize),
+  region: &BBox,
+  (dst_w, dst_h): (usize, usize),
+) -> Vec<u8> {
+  let mut data = vec![0u8; dst_w * dst_h * 3];
+  let scale_x = (region.x_max - region.x_min) / dst_w as f32;
+  let scale_y = (region.y_max - region.y_min) / dst_h as f32;
+  let max_x = (src_w - 1) as f32;
+  let max_y = (src_h - 1) as f32;
+
+  for dy in 0..dst_h {
+    let sy = (region.y_min + (dy as f32 + 0.5) * scale_y - 0.5).clamp(0.0, max_y);
+    let (y0, fy) = (sy.floor() as usize, sy.fract());
+    let y1 = (y0 + 1).min(src_h - 1);
+    for dx in 0..dst_w {
+      let sx = (region.x_min + (dx as f32 + 0.5) * scale_x - 0.5).clamp(0.0, max_x);
+      let (x0, fx) = (sx.floor() as usize, sx.fract());
+      let x1 = (x0 + 1).min(src_w - 1);
+      for c in 0..3 {
+        let at = |x: usize, y: usize| src[(y * src_w + x) * 3 + c] as f32;
+        let top = at(x0, y0) * (1.0 - fx) + at(x1, y0) * fx;
+        let bottom = at(x0, y1) * (1.0 - fx) + at(x1, y1) * fx;
+        data[(dy * dst_w + dx) * 3 + c] = (top * (1.0 - fy) + bottom * fy).round() as u8;
+      }
+    }
+  }
+  data
+}
@@ -26,6 +26,14 @@ use crate::{
   frame::{RgbNchwFrame, RgbNhwcFrame},
 };
 
+mod batch;
+pub use self::batch::{BatchIter, Batcher, MultiInput};
+
+mod quality;
+pub use self::quality::{
+  QualityGate, QualityGateError, QualityGateIter, QualityGateMode, QualityMeasure,
+};
+
 #[cfg(feature = "read_image_file")]
 mod read_image_file;
 #[cfg(feature = "read_image_file")]
@@ -38,6 +46,16 @@ pub use self::gstreamer_input::{
   GStreamerInput, GStreamerInputBuilderItem, GStreamerInputError, GStreamerInputPipelineBuilder,
 };
 
+#[cfg(feature = "watch_folder")]
+mod watch_folder;
+#[cfg(feature = "watch_folder")]
+pub use self::watch_folder::{WatchFolderInput, WatchFolderInputError};
+
+#[cfg(feature = "mjpeg_input")]
+mod mjpeg_input;
+#[cfg(feature = "mjpeg_input")]
+pub use self::mjpeg_input::{MjpegInput, MjpegInputError};
+
 #[derive(Error, Debug)]
 pub enum InputError {
   #[cfg(feature = "read_image_file")]
@@ -46,10 +64,37 @@ pub enum InputError {
   #[cfg(feature = "gstreamer_input")]
   #[error("GStreamer input error: {0}")]
   GStreamerInputError(#[from] GStreamerInputError),
+  #[cfg(feature = "watch_folder")]
+  #[error("Watch folder input error: {0}")]
+  WatchFolderInputError(#[from] WatchFolderInputError),
+  #[cfg(feature = "mjpeg_input")]
+  #[error("MJPEG input error: {0}")]
+  MjpegInputError(#[from] MjpegInputError),
   #[error("URI scheme mismatch")]
   SchemeMismatch,
 }
 
+/// 输入的结束状态
+///
+/// 输入被移动到任务之后仍可查询。帧迭代器在流结束和输入出错（例如 GStreamer 缺少插件、
+/// RTSP 认证失败）时都会结束，任务正常返回后通过 [`InputStatus::check`] 区分两者。
+#[derive(Debug, Clone, Default)]
+pub struct InputStatus {
+  #[cfg(feature = "gstreamer_input")]
+  bus: Option<crate::utils::GStreamerBusMonitor>,
+}
+
+impl InputStatus {
+  /// 输入因错误结束时返回该错误
+  pub fn check(&self) -> Result<(), InputError> {
+    #[cfg(feature = "gstreamer_input")]
+    if let Some(event) = self.bus.as_ref().and_then(|bus| bus.last_error()) {
+      return Err(GStreamerInputError::BusError(event).into());
+    }
+    Ok(())
+  }
+}
+
 #[derive(Debug, Clone)]
 pub enum InputWrapper<const W: u32, const H: u32> {
   #[cfg(feature = "gstreamer_input")]
@@ -58,6 +103,10 @@ pub enum InputWrapper<const W: u32, const H: u32> {
   ReadImageFile(ImageFileInput<W, H>),
   #[cfg(feature = "read_image_file")]
   ReadImageFolder(ReadImageFolderInput<W, H>),
+  #[cfg(feature = "watch_folder")]
+  WatchFolder(WatchFolderInput<W, H>),
+  #[cfg(feature = "mjpeg_input")]
+  Mjpeg(MjpegInput<W, H>),
 }
 
 impl<const W: u32, const H: u32> FromUrl for InputWrapper<W, H> {
@@ -91,6 +140,24 @@ impl<const W: u32, const H: u32> FromUrl for InputWrapper<W, H> {
         return Ok(InputWrapper::ReadImageFolder(input));
       }
     }
+    #[cfg(feature = "watch_folder")]
+    {
+      use crate::FromUrlWithScheme;
+
+      if url.scheme() == WatchFolderInput::<W, H>::SCHEME {
+        let input = WatchFolderInput::from_url(url)?;
+        return Ok(InputWrapper::WatchFolder(input));
+      }
+    }
+    #[cfg(feature = "mjpeg_input")]
+    {
+      use crate::FromUrlWithScheme;
+
+      if url.scheme() == MjpegInput::<W, H>::SCHEME {
+        let input = MjpegInput::from_url(url)?;
+        return Ok(InputWrapper::Mjpeg(input));
+      }
+    }
     Err(InputError::SchemeMismatch)
   }
 }
@@ -106,6 +173,10 @@ impl<const W: u32, const H: u32> InputWrapper<W, H> {
       InputWrapper::ReadImageFile(input) => InputWrapperNhwcIter::ReadImageFile(input.into_nhwc()),
       #[cfg(feature = "read_image_file")]
       InputWrapper::ReadImageFolder(input) => InputWrapperNhwcIter::ReadImageFolder(input),
+      #[cfg(feature = "watch_folder")]
+      InputWrapper::WatchFolder(input) => InputWrapperNhwcIter::WatchFolder(input.into_nhwc()),
+      #[cfg(feature = "mjpeg_input")]
+      InputWrapper::Mjpeg(input) => InputWrapperNhwcIter::Mjpeg(input.into_nhwc()),
     }
   }
 
@@ -121,6 +192,10 @@ impl<const W: u32, const H: u32> InputWrapper<W, H> {
       InputWrapper::ReadImageFolder(_) => {
         panic!("当前 ReadImageFolderInput 只支持 NHWC 格式。后续可能会变化！")
       }
+      #[cfg(feature = "watch_folder")]
+      InputWrapper::WatchFolder(input) => InputWrapperNchwIter::WatchFolder(input.into_nchw()),
+      #[cfg(feature = "mjpeg_input")]
+      InputWrapper::Mjpeg(input) => InputWrapperNchwIter::Mjpeg(input.into_nchw()),
     }
   }
 }
@@ -133,6 +208,30 @@ pub enum InputWrapperNhwcIter<const W: u32, const H: u32> {
   ReadImageFile(self::read_image_file::ImageFileInputNhwc<W, H>),
   #[cfg(feature = "read_image_file")]
   ReadImageFolder(self::read_image_file::ReadImageFolderInput<W, H>),
+  #[cfg(feature = "watch_folder")]
+  WatchFolder(self::watch_folder::WatchFolderInputNhwc<W, H>),
+  #[cfg(feature = "mjpeg_input")]
+  Mjpeg(self::mjpeg_input::MjpegInputNhwc<W, H>),
+}
+
+impl<const W: u32, const H: u32> InputWrapperNhwcIter<W, H> {
+  /// 输入的结束状态，在输入移动到任务之前获取
+  pub fn status(&self) -> InputStatus {
+    InputStatus {
+      #[cfg(feature = "gstreamer_input")]
+      bus: self.bus_monitor(),
+    }
+  }
+
+  /// GStreamer 管道总线监控器，非 GStreamer 输入返回 `None`
+  #[cfg(feature = "gstreamer_input")]
+  pub fn bus_monitor(&self) -> Option<crate::utils::GStreamerBusMonitor> {
+    match self {
+      InputWrapperNhwcIter::GStreamerInput(input) => Some(input.bus_monitor().clone()),
+      #[allow(unreachable_patterns)]
+      _ => None,
+    }
+  }
 }
 
 impl<const W: u32, const H: u32> Iterator for InputWrapperNhwcIter<W, H> {
@@ -146,6 +245,10 @@ impl<const W: u32, const H: u32> Iterator for InputWrapperNhwcIter<W, H> {
       InputWrapperNhwcIter::ReadImageFile(input) => input.next(),
       #[cfg(feature = "read_image_file")]
       InputWrapperNhwcIter::ReadImageFolder(input) => input.next(),
+      #[cfg(feature = "watch_folder")]
+      InputWrapperNhwcIter::WatchFolder(input) => input.next(),
+      #[cfg(feature = "mjpeg_input")]
+      InputWrapperNhwcIter::Mjpeg(input) => input.next(),
     }
   }
 }
@@ -156,6 +259,30 @@ pub enum InputWrapperNchwIter<const W: u32, const H: u32> {
   GStreamerInput(self::gstreamer_input::GStreamerInputNchw<W, H>),
   #[cfg(feature = "read_image_file")]
   ReadImageFile(self::read_image_file::ImageFileInputNchw<W, H>),
+  #[cfg(feature = "watch_folder")]
+  WatchFolder(self::watch_folder::WatchFolderInputNchw<W, H>),
+  #[cfg(feature = "mjpeg_input")]
+  Mjpeg(self::mjpeg_input::MjpegInputNchw<W, H>),
+}
+
+impl<const W: u32, const H: u32> InputWrapperNchwIter<W, H> {
+  /// 输入的结束状态，在输入移动到任务之前获取
+  pub fn status(&self) -> InputStatus {
+    InputStatus {
+      #[cfg(feature = "gstreamer_input")]
+      bus: self.bus_monitor(),
+    }
+  }
+
+  /// GStreamer 管道总线监控器，非 GStreamer 输入返回 `None`
+  #[cfg(feature = "gstreamer_input")]
+  pub fn bus_monitor(&self) -> Option<crate::utils::GStreamerBusMonitor> {
+    match self {
+      InputWrapperNchwIter::GStreamerInput(input) => Some(input.bus_monitor().clone()),
+      #[allow(unreachable_patterns)]
+      _ => None,
+    }
+  }
 }
 
 impl<const W: u32, const H: u32> Iterator for InputWrapperNchwIter<W, H> {
@@ -167,6 +294,10 @@ impl<const W: u32, const H: u32> Iterator for InputWrapperNchwIter<W, H> {
       InputWrapperNchwIter::GStreamerInput(input) => input.next(),
       #[cfg(feature = "read_image_file")]
       InputWrapperNchwIter::ReadImageFile(input) => input.next(),
+      #[cfg(feature = "watch_folder")]
+      InputWrapperNchwIter::WatchFolder(input) => input.next(),
+      #[cfg(feature = "mjpeg_input")]
+      InputWrapperNchwIter::Mjpeg(input) => input.next(),
     }
   }
 }
@@ -0,0 +1,119 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/input/batch.rs - 批量输入
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 批量输入
+//!
+//! 将帧组合为 [`FrameBatch`]，供批量推理（[`Batched`](crate::model::Batched)）使用：
+//!
+//! - [`Batcher`]: 把一个输入中连续的帧按批大小分组，最后一批可能不满
+//! - [`MultiInput`]: 多个输入各取一帧组成一批，例如多路摄像头，任一输入结束时结束
+//!
+//! ## URL 参数
+//!
+//! - `batch`: 批大小，在输入 URL 上指定，默认 1
+
+use url::Url;
+
+use crate::{FromUrl, frame::FrameBatch};
+
+const BATCH_SIZE: usize = 1;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Batcher {
+  size: usize,
+}
+
+impl Default for Batcher {
+  fn default() -> Self {
+    Self { size: BATCH_SIZE }
+  }
+}
+
+impl FromUrl for Batcher {
+  type Error = std::convert::Infallible;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    let mut batcher = Batcher::default();
+    for (k, v) in url.query_pairs() {
+      if k == "batch" {
+        batcher.size = v.parse().unwrap_or(BATCH_SIZE).max(1);
+      }
+    }
+    Ok(batcher)
+  }
+}
+
+impl Batcher {
+  pub fn size(mut self, size: usize) -> Self {
+    self.size = size.max(1);
+    self
+  }
+
+  pub fn batch_size(&self) -> usize {
+    self.size
+  }
+
+  pub fn apply<I: Iterator>(self, input: I) -> BatchIter<I> {
+    BatchIter {
+      input,
+      size: self.size,
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchIter<I> {
+  input: I,
+  size: usize,
+}
+
+impl<I: Iterator> Iterator for BatchIter<I> {
+  type Item = FrameBatch<I::Item>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let frames: Vec<_> = self.input.by_ref().take(self.size).collect();
+    if frames.is_empty() {
+      None
+    } else {
+      Some(FrameBatch::from(frames))
+    }
+  }
+}
+
+/// 多个输入各取一帧组成一批，批中帧的顺序与输入顺序一致
+#[derive(Debug, Clone)]
+pub struct MultiInput<I> {
+  inputs: Vec<I>,
+}
+
+impl<I> From<Vec<I>> for MultiInput<I> {
+  fn from(inputs: Vec<I>) -> Self {
+    Self { inputs }
+  }
+}
+
+impl<I: Iterator> Iterator for MultiInput<I> {
+  type Item = FrameBatch<I::Item>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.inputs.is_empty() {
+      return None;
+    }
+    let frames = self
+      .inputs
+      .iter_mut()
+      .map(Iterator::next)
+      .collect::<Option<Vec<_>>>()?;
+    Some(FrameBatch::from(frames))
+  }
+}
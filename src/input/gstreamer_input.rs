@@ -132,10 +132,12 @@
 //! 应验证或限制管道描述以防止资源滥用。
 
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 use crate::{
   FromUrl, FromUrlWithScheme,
-  frame::{RgbNchwFrame, RgbNhwcFrame},
+  frame::{FrameTimestamp, RgbNchwFrame, RgbNhwcFrame},
+  utils::{GStreamerBusEvent, GStreamerBusMonitor},
 };
 
 use gstreamer::{self as gst, prelude::*};
@@ -145,6 +147,11 @@ use thiserror::Error;
 use tracing::{error, info};
 use url::Url;
 
+/// 拉取样本时检查总线的间隔
+const BUS_POLL_INTERVAL_MS: u64 = 100;
+/// 启动管道时等待状态切换完成的超时时间
+const PIPELINE_STARTUP_TIMEOUT_S: u64 = 5;
+
 /// GStreamer 输入错误类型
 ///
 /// 包含所有可能的 GStreamer 输入相关错误。
@@ -180,6 +187,9 @@ pub enum GStreamerInputError {
   /// 状态改变错误
   #[error("State change error: {0}")]
   StateChangeError(#[from] gst::StateChangeError),
+  /// 管道总线上报的错误
+  #[error("GStreamer bus error: {0}")]
+  BusError(GStreamerBusEvent),
 }
 
 pub enum GStreamerInputBuilderItem {
@@ -370,10 +380,24 @@ impl<const W: u32, const H: u32> GStreamerInputPipelineBuilder<W, H> {
       .downcast::<gst_app::AppSink>()
       .map_err(|_| GStreamerInputError::AppSinkConversionFailed)?;
 
+    let bus = GStreamerBusMonitor::new(&pipeline)
+      .ok_or_else(|| GStreamerInputError::PipelineError("Pipeline has no bus".to_string()))?;
+
     // Start the pipeline
     pipeline.set_state(gst::State::Playing)?;
 
-    Ok(GStreamerInput { pipeline, appsink })
+    // 等待管道启动，尽早暴露协商失败、认证失败等错误
+    let _ = pipeline.state(gst::ClockTime::from_seconds(PIPELINE_STARTUP_TIMEOUT_S));
+    if let Some(event) = bus.poll() {
+      let _ = pipeline.set_state(gst::State::Null);
+      return Err(GStreamerInputError::BusError(event));
+    }
+
+    Ok(GStreamerInput {
+      pipeline,
+      appsink,
+      bus,
+    })
   }
 }
 
@@ -435,6 +459,7 @@ impl<const W: u32, const H: u32> FromUrl for GStreamerInputPipelineBuilder<W, H>
 pub struct GStreamerInput<const W: u32, const H: u32> {
   pipeline: gst::Pipeline,
   appsink: gst_app::AppSink,
+  bus: GStreamerBusMonitor,
 }
 
 impl<const W: u32, const H: u32> Drop for GStreamerInput<W, H> {
@@ -454,22 +479,81 @@ impl<const W: u32, const H: u32> GStreamerInput<W, H> {
     GStreamerInputNhwc { inner: self }
   }
 
+  /// 管道总线监控器
+  pub fn bus_monitor(&self) -> &GStreamerBusMonitor {
+    &self.bus
+  }
+
+  /// 检查管道总线，若有错误则返回
+  pub fn check_bus(&self) -> Result<(), GStreamerInputError> {
+    match self.bus.poll() {
+      Some(event) => Err(GStreamerInputError::BusError(event)),
+      None => Ok(()),
+    }
+  }
+
+  /// 管道最近一次上报的错误
+  ///
+  /// 帧迭代器遇到管道错误时结束，与流结束无法区分，任务结束后据此判断输入是否因错误停止。
+  pub fn last_error(&self) -> Option<GStreamerInputError> {
+    self.bus.last_error().map(GStreamerInputError::BusError)
+  }
+
+  /// 拉取下一个样本，流结束或管道出错时返回 `None`，错误可由 [`Self::last_error`] 查询
   fn pull_sample(&self) -> Option<gst::Sample> {
-    if self.appsink.is_eos() {
-      return None;
+    loop {
+      if let Err(e) = self.check_bus() {
+        error!("管道错误，输入结束: {}", e);
+        return None;
+      }
+
+      if self.appsink.is_eos() {
+        return None;
+      }
+
+      if let Some(sample) = self
+        .appsink
+        .try_pull_sample(gst::ClockTime::from_mseconds(BUS_POLL_INTERVAL_MS))
+      {
+        return Some(sample);
+      }
     }
+  }
 
-    self
-      .appsink
-      .pull_sample()
-      .map_err(|e| {
-        error!("Failed to pull sample: {}", e);
-        e
-      })
-      .ok()
+  /// 从样本中提取缓冲区时间戳
+  ///
+  /// 运行时间由样本所在的 segment 换算得到；采集时刻由管道当前运行时间与缓冲区运行时间之差反推。
+  fn sample_timestamp(&self, sample: &gst::Sample) -> FrameTimestamp {
+    let received_at = Instant::now();
+    let Some(buffer) = sample.buffer() else {
+      return FrameTimestamp::default();
+    };
+
+    let pts = buffer.pts();
+    let running_time = sample
+      .segment()
+      .and_then(|segment| segment.downcast_ref::<gst::ClockTime>())
+      .and_then(|segment| pts.and_then(|pts| segment.to_running_time(pts)));
+    let captured_at = running_time
+      .zip(self.pipeline.current_running_time())
+      .and_then(|(buffer_rt, now_rt)| {
+        received_at.checked_sub(clock_time_to_duration(now_rt.saturating_sub(buffer_rt)))
+      });
+
+    FrameTimestamp {
+      pts: pts.map(clock_time_to_duration),
+      dts: buffer.dts().map(clock_time_to_duration),
+      duration: buffer.duration().map(clock_time_to_duration),
+      running_time: running_time.map(clock_time_to_duration),
+      captured_at,
+    }
   }
 }
 
+fn clock_time_to_duration(time: gst::ClockTime) -> Duration {
+  Duration::from_nanos(time.nseconds())
+}
+
 /// GStreamer 输入的 NCHW 格式迭代器
 ///
 /// 将视频帧转换为 NCHW 格式（Channels × Height × Width）。
@@ -478,12 +562,26 @@ pub struct GStreamerInputNchw<const W: u32, const H: u32> {
   inner: GStreamerInput<W, H>,
 }
 
+impl<const W: u32, const H: u32> GStreamerInputNchw<W, H> {
+  /// 管道总线监控器
+  pub fn bus_monitor(&self) -> &GStreamerBusMonitor {
+    self.inner.bus_monitor()
+  }
+
+  /// 管道最近一次上报的错误，参见 [`GStreamerInput::last_error`]
+  pub fn last_error(&self) -> Option<GStreamerInputError> {
+    self.inner.last_error()
+  }
+}
+
 impl<const W: u32, const H: u32> Iterator for GStreamerInputNchw<W, H> {
   type Item = RgbNchwFrame<W, H>;
 
   fn next(&mut self) -> Option<Self::Item> {
     let sample = self.inner.pull_sample()?;
+    let timestamp = self.inner.sample_timestamp(&sample);
     convert_sample_to_nchw(sample)
+      .map(|frame| frame.with_timestamp(timestamp))
       .map_err(|e| {
         error!("Failed to fetch sample: {}", e);
         e
@@ -500,12 +598,26 @@ pub struct GStreamerInputNhwc<const W: u32, const H: u32> {
   inner: GStreamerInput<W, H>,
 }
 
+impl<const W: u32, const H: u32> GStreamerInputNhwc<W, H> {
+  /// 管道总线监控器
+  pub fn bus_monitor(&self) -> &GStreamerBusMonitor {
+    self.inner.bus_monitor()
+  }
+
+  /// 管道最近一次上报的错误，参见 [`GStreamerInput::last_error`]
+  pub fn last_error(&self) -> Option<GStreamerInputError> {
+    self.inner.last_error()
+  }
+}
+
 impl<const W: u32, const H: u32> Iterator for GStreamerInputNhwc<W, H> {
   type Item = RgbNhwcFrame<W, H>;
 
   fn next(&mut self) -> Option<Self::Item> {
     let sample = self.inner.pull_sample()?;
+    let timestamp = self.inner.sample_timestamp(&sample);
     convert_sample_to_nhwc(sample)
+      .map(|frame| frame.with_timestamp(timestamp))
       .map_err(|e| {
         error!("Failed to fetch sample x: {}", e);
         e
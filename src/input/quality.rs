@@ -0,0 +1,440 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/input/quality.rs - 推理前图像质量检测
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 推理前图像质量检测
+//!
+//! 在推理之前计算帧的清晰度（Laplacian 方差）、平均亮度、过曝/欠曝像素占比以及与上一帧的差异，
+//! 对镜头起雾、红外切换、全黑、画面冻结等低质量帧进行丢弃或标记。计算结果通过
+//! [`FrameQuality`] 附加在帧上，输出端可以读取并记录。
+//!
+//! ## 参数说明
+//!
+//! 参数从输入 URL 的查询参数中读取：
+//!
+//! - `quality`: `skip` 丢弃不合格的帧，`flag` 仅标记，默认 `flag`
+//! - `quality_min_sharpness`: 最小清晰度
+//! - `quality_min_luma`: 最小平均亮度（0-255）
+//! - `quality_max_luma`: 最大平均亮度（0-255）
+//! - `quality_max_clip`: 过曝或欠曝像素的最大占比（0-1）
+//! - `quality_min_diff`: 与上一帧像素的最小平均绝对差（0-255），低于该值视为画面冻结
+//!
+//! 未设置任何参数时不进行检测。
+
+use thiserror::Error;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::{
+  FromUrl,
+  frame::{FrameQuality, RgbNchwFrame, RgbNhwcFrame},
+  input::{AsNchwFrame, AsNhwcFrame},
+};
+
+/// 通道值不高于该值视为欠曝
+const QUALITY_DARK_LEVEL: u8 = 5;
+/// 通道值不低于该值视为过曝
+const QUALITY_BRIGHT_LEVEL: u8 = 250;
+
+#[derive(Error, Debug)]
+pub enum QualityGateError {
+  #[error("未知的质量检测模式: {0}")]
+  InvalidMode(String),
+  #[error("无效的质量阈值 {name}: {value}")]
+  InvalidThreshold { name: String, value: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QualityGateMode {
+  /// 丢弃不合格的帧
+  Skip,
+  /// 保留不合格的帧，仅标记
+  #[default]
+  Flag,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct QualityGate {
+  enabled: bool,
+  mode: QualityGateMode,
+  min_sharpness: Option<f32>,
+  min_luminance: Option<f32>,
+  max_luminance: Option<f32>,
+  max_clipped: Option<f32>,
+  min_frame_diff: Option<f32>,
+}
+
+impl FromUrl for QualityGate {
+  type Error = QualityGateError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    let mut gate = QualityGate::default();
+    for (k, v) in url.query_pairs() {
+      match k.as_ref() {
+        "quality" => {
+          gate.mode = match v.as_ref() {
+            "skip" => QualityGateMode::Skip,
+            "flag" => QualityGateMode::Flag,
+            other => return Err(QualityGateError::InvalidMode(other.to_string())),
+          };
+          gate.enabled = true;
+        }
+        "quality_min_sharpness" => gate.min_sharpness = Some(parse_threshold(&k, &v)?),
+        "quality_min_luma" => gate.min_luminance = Some(parse_threshold(&k, &v)?),
+        "quality_max_luma" => gate.max_luminance = Some(parse_threshold(&k, &v)?),
+        "quality_max_clip" => gate.max_clipped = Some(parse_threshold(&k, &v)?),
+        "quality_min_diff" => gate.min_frame_diff = Some(parse_threshold(&k, &v)?),
+        _ => continue,
+      }
+    }
+    gate.enabled |= gate.min_sharpness.is_some()
+      || gate.min_luminance.is_some()
+      || gate.max_luminance.is_some()
+      || gate.max_clipped.is_some()
+      || gate.min_frame_diff.is_some();
+    Ok(gate)
+  }
+}
+
+fn parse_threshold(name: &str, value: &str) -> Result<f32, QualityGateError> {
+  value
+    .parse()
+    .map_err(|_| QualityGateError::InvalidThreshold {
+      name: name.to_string(),
+      value: value.to_string(),
+    })
+}
+
+impl QualityGate {
+  pub fn mode(mut self, mode: QualityGateMode) -> Self {
+    self.mode = mode;
+    self.enabled = true;
+    self
+  }
+
+  pub fn min_sharpness(mut self, min_sharpness: f32) -> Self {
+    self.min_sharpness = Some(min_sharpness);
+    self.enabled = true;
+    self
+  }
+
+  pub fn luminance_range(mut self, min: f32, max: f32) -> Self {
+    self.min_luminance = Some(min);
+    self.max_luminance = Some(max);
+    self.enabled = true;
+    self
+  }
+
+  pub fn max_clipped(mut self, max_clipped: f32) -> Self {
+    self.max_clipped = Some(max_clipped);
+    self.enabled = true;
+    self
+  }
+
+  /// 与上一帧的最小差异，低于该值的帧视为画面冻结
+  pub fn min_frame_diff(mut self, min_frame_diff: f32) -> Self {
+    self.min_frame_diff = Some(min_frame_diff);
+    self.enabled = true;
+    self
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    self.enabled
+  }
+
+  /// 计算质量指标并判断是否满足阈值，不检测画面冻结
+  pub fn evaluate<F: QualityMeasure>(&self, frame: &F) -> FrameQuality {
+    self.evaluate_with(frame, None)
+  }
+
+  /// 计算质量指标并判断是否满足阈值，`previous` 为上一帧的像素数据
+  pub fn evaluate_with<F: QualityMeasure>(
+    &self,
+    frame: &F,
+    previous: Option<&[u8]>,
+  ) -> FrameQuality {
+    let mut quality = frame.measure_quality();
+    quality.frame_diff = previous.map(|previous| frame_diff(previous, frame.pixels()));
+    quality.passed = self.min_sharpness.is_none_or(|t| quality.sharpness >= t)
+      && self.min_luminance.is_none_or(|t| quality.luminance >= t)
+      && self.max_luminance.is_none_or(|t| quality.luminance <= t)
+      && self
+        .max_clipped
+        .is_none_or(|t| quality.clipped_dark <= t && quality.clipped_bright <= t)
+      && self
+        .min_frame_diff
+        .zip(quality.frame_diff)
+        .is_none_or(|(t, diff)| diff >= t);
+    quality
+  }
+
+  /// 在输入迭代器上应用质量检测
+  pub fn apply<I, F>(self, input: I) -> QualityGateIter<I>
+  where
+    I: Iterator<Item = F>,
+    F: QualityMeasure,
+  {
+    QualityGateIter {
+      gate: self,
+      input,
+      previous: None,
+    }
+  }
+}
+
+/// 可计算质量指标的帧
+pub trait QualityMeasure {
+  fn measure_quality(&self) -> FrameQuality;
+  /// 帧的像素数据，用于比较相邻帧
+  fn pixels(&self) -> &[u8];
+  fn set_quality(&mut self, quality: FrameQuality);
+}
+
+impl<const W: u32, const H: u32> QualityMeasure for RgbNhwcFrame<W, H> {
+  fn measure_quality(&self) -> FrameQuality {
+    let data = self.as_nhwc();
+    measure(self.width(), self.height(), |i| {
+      [data[3 * i], data[3 * i + 1], data[3 * i + 2]]
+    })
+  }
+
+  fn pixels(&self) -> &[u8] {
+    self.as_nhwc()
+  }
+
+  fn set_quality(&mut self, quality: FrameQuality) {
+    RgbNhwcFrame::set_quality(self, quality)
+  }
+}
+
+impl<const W: u32, const H: u32> QualityMeasure for RgbNchwFrame<W, H> {
+  fn measure_quality(&self) -> FrameQuality {
+    let data = self.as_nchw();
+    let plane = self.width() * self.height();
+    measure(self.width(), self.height(), |i| {
+      [data[i], data[plane + i], data[2 * plane + i]]
+    })
+  }
+
+  fn pixels(&self) -> &[u8] {
+    self.as_nchw()
+  }
+
+  fn set_quality(&mut self, quality: FrameQuality) {
+    RgbNchwFrame::set_quality(self, quality)
+  }
+}
+
+fn measure(width: usize, height: usize, pixel: impl Fn(usize) -> [u8; 3]) -> FrameQuality {
+  let total = width * height;
+  if total == 0 {
+    return FrameQuality::default();
+  }
+
+  let mut luma = Vec::with_capacity(total);
+  let mut dark = 0usize;
+  let mut bright = 0usize;
+  for i in 0..total {
+    let [r, g, b] = pixel(i);
+    luma.push(0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32);
+    if r <= QUALITY_DARK_LEVEL && g <= QUALITY_DARK_LEVEL && b <= QUALITY_DARK_LEVEL {
+      dark += 1;
+    }
+    if r >= QUALITY_BRIGHT_LEVEL || g >= QUALITY_BRIGHT_LEVEL || b >= QUALITY_BRIGHT_LEVEL {
+      bright += 1;
+    }
+  }
+  let luminance = luma.iter().sum::<f32>() / total as f32;
+
+  // 4 邻域 Laplacian，仅在内部像素上计算
+  let mut sum = 0f64;
+  let mut sum_sq = 0f64;
+  let mut count = 0usize;
+  for y in 1..height.saturating_sub(1) {
+    for x in 1..width.saturating_sub(1) {
+      let i = y * width + x;
+      let lap =
+        (luma[i - width] + luma[i + width] + luma[i - 1] + luma[i + 1] - 4.0 * luma[i]) as f64;
+      sum += lap;
+      sum_sq += lap * lap;
+      count += 1;
+    }
+  }
+  let sharpness = if count > 0 {
+    let mean = sum / count as f64;
+    (sum_sq / count as f64 - mean * mean) as f32
+  } else {
+    0.0
+  };
+
+  FrameQuality {
+    sharpness,
+    luminance,
+    clipped_dark: dark as f32 / total as f32,
+    clipped_bright: bright as f32 / total as f32,
+    frame_diff: None,
+    passed: true,
+  }
+}
+
+/// 两帧像素的平均绝对差
+fn frame_diff(previous: &[u8], current: &[u8]) -> f32 {
+  if previous.len() != current.len() || current.is_empty() {
+    return f32::MAX;
+  }
+  let sum: u64 = previous
+    .iter()
+    .zip(current)
+    .map(|(a, b)| a.abs_diff(*b) as u64)
+    .sum();
+  sum as f32 / current.len() as f32
+}
+
+/// 带质量检测的输入迭代器
+#[derive(Debug, Clone)]
+pub struct QualityGateIter<I> {
+  gate: QualityGate,
+  input: I,
+  /// 检测画面冻结时保留的上一帧像素
+  previous: Option<Vec<u8>>,
+}
+
+impl<I, F> Iterator for QualityGateIter<I>
+where
+  I: Iterator<Item = F>,
+  F: QualityMeasure,
+{
+  type Item = F;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      let mut frame = self.input.next()?;
+      if !self.gate.enabled {
+        return Some(frame);
+      }
+
+      let quality = self.gate.evaluate_with(&frame, self.previous.as_deref());
+      if self.gate.min_frame_diff.is_some() {
+        self.previous = Some(frame.pixels().to_vec());
+      }
+      debug!("帧质量: {:?}", quality);
+      frame.set_quality(quality);
+      if !quality.passed && self.gate.mode == QualityGateMode::Skip {
+        info!(
+          "丢弃低质量帧: 清晰度 {:.1}, 亮度 {:.1}, 欠曝 {:.3}, 过曝 {:.3}",
+          quality.sharpness, quality.luminance, quality.clipped_dark, quality.clipped_bright
+        );
+        continue;
+      }
+      return Some(frame);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use url::Url;
+
+  use super::{QualityGate, QualityGateMode, QualityMeasure};
+  use crate::{FromUrl, frame::RgbNhwcFrame};
+
+  type Frame = RgbNhwcFrame<8, 8>;
+
+  fn flat(value: u8) -> Frame {
+    Frame::from(vec![value; 8 * 8 * 3])
+  }
+
+  /// 每个像素为灰度随机值的噪声帧
+  fn noisy(seed: u32) -> Frame {
+    let mut state = seed;
+    let mut data = Vec::with_capacity(8 * 8 * 3);
+    for _ in 0..8 * 8 {
+      state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+      let value = 64 + (state >> 16) as u8 / 2;
+      data.extend([value; 3]);
+    }
+    Frame::from(data)
+  }
+
+  #[test]
+  fn flat_frame_has_no_sharpness() {
+    let quality = flat(128).measure_quality();
+    assert_eq!(quality.sharpness, 0.0);
+    assert!((quality.luminance - 128.0).abs() < 1e-3);
+    assert_eq!((quality.clipped_dark, quality.clipped_bright), (0.0, 0.0));
+  }
+
+  #[test]
+  fn blur_threshold_separates_flat_and_noisy() {
+    let gate = QualityGate::default().min_sharpness(10.0);
+    assert!(noisy(1).measure_quality().sharpness > 10.0);
+    assert!(gate.evaluate(&noisy(1)).passed);
+    assert!(!gate.evaluate(&flat(128)).passed);
+  }
+
+  #[test]
+  fn exposure_thresholds() {
+    let gate = QualityGate::default()
+      .luminance_range(20.0, 230.0)
+      .max_clipped(0.5);
+    let dark = gate.evaluate(&flat(0));
+    assert_eq!(dark.clipped_dark, 1.0);
+    assert!(!dark.passed);
+    let bright = gate.evaluate(&flat(255));
+    assert_eq!(bright.clipped_bright, 1.0);
+    assert!(!bright.passed);
+    assert!(gate.evaluate(&flat(128)).passed);
+  }
+
+  #[test]
+  fn skips_repeated_frames() {
+    let gate = QualityGate::default()
+      .mode(QualityGateMode::Skip)
+      .min_frame_diff(1.0);
+    let frames = vec![noisy(1), noisy(1), noisy(1), noisy(2)];
+    let kept: Vec<_> = gate
+      .apply(frames.into_iter())
+      .map(|frame| frame.quality().unwrap().frame_diff)
+      .collect();
+    // 第一帧没有上一帧，重复的两帧被丢弃
+    assert_eq!(kept.len(), 2);
+    assert_eq!(kept[0], None);
+    assert!(kept[1].unwrap() >= 1.0);
+  }
+
+  #[test]
+  fn flag_mode_keeps_failed_frames() {
+    let url = Url::parse("file:///input?quality_min_luma=50").unwrap();
+    let gate = QualityGate::from_url(&url).unwrap();
+    assert!(gate.is_enabled());
+    let passed: Vec<_> = gate
+      .apply(vec![flat(0), flat(128)].into_iter())
+      .map(|frame| frame.quality().unwrap().passed)
+      .collect();
+    assert_eq!(passed, [false, true]);
+  }
+
+  #[test]
+  fn parses_query() {
+    let url = Url::parse("file:///input").unwrap();
+    let gate = QualityGate::from_url(&url).unwrap();
+    assert!(!gate.is_enabled());
+    // 未启用时不计算质量指标
+    let frame = gate.apply(vec![flat(0)].into_iter()).next().unwrap();
+    assert!(frame.quality().is_none());
+
+    let url = Url::parse("file:///input?quality=drop").unwrap();
+    assert!(QualityGate::from_url(&url).is_err());
+    let url = Url::parse("file:///input?quality_max_clip=abc").unwrap();
+    assert!(QualityGate::from_url(&url).is_err());
+  }
+}
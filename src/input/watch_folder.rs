@@ -0,0 +1,357 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/input/watch_folder.rs - 监控目录输入
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 监控目录输入
+//!
+//! 监控指定目录（Linux 下基于 inotify），将新写入或移动进来的图像文件作为帧输出。
+//! 该输入不会自行结束。
+//!
+//! ## URL Scheme
+//!
+//! `watch://`
+//!
+//! ```text
+//! watch:///data/upload?done=/data/done&failed=/data/failed&settle_ms=500
+//! ```
+//!
+//! ## 参数说明
+//!
+//! - `done`: 处理成功的文件移动到该目录，默认不移动
+//! - `failed`: 解码失败的文件移动到该目录，默认不移动
+//! - `settle_ms`: 未收到写入关闭事件时，文件大小保持不变多久视为写入完成，默认 500
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, channel};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use image::{ImageFormat, ImageReader, RgbImage};
+use notify::event::{AccessKind, AccessMode, ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use thiserror::Error;
+use tracing::{debug, error, info, warn};
+use url::Url;
+
+use crate::{
+  FromUrl, FromUrlWithScheme,
+  frame::{RgbNchwFrame, RgbNhwcFrame},
+};
+
+const WATCH_FOLDER_SETTLE_MS: u64 = 500;
+
+#[derive(Error, Debug)]
+pub enum WatchFolderInputError {
+  #[error("URI scheme mismatch")]
+  SchemeMismatch,
+  #[error("Not a directory: {0}")]
+  NotADirectory(String),
+  #[error("I/O error: {0}")]
+  IoError(#[from] std::io::Error),
+  #[error("Image loading error: {0}")]
+  ImageLoadError(#[from] image::ImageError),
+  #[error("File watcher error: {0}")]
+  NotifyError(#[from] notify::Error),
+}
+
+struct PendingFile {
+  size: u64,
+  since: Instant,
+}
+
+struct WatchFolderState {
+  directory: PathBuf,
+  done_dir: Option<PathBuf>,
+  failed_dir: Option<PathBuf>,
+  settle: Duration,
+  _watcher: RecommendedWatcher,
+  events: Receiver<notify::Result<Event>>,
+  pending: HashMap<PathBuf, PendingFile>,
+  ready: VecDeque<PathBuf>,
+}
+
+#[derive(Clone)]
+pub struct WatchFolderInput<const W: u32, const H: u32> {
+  state: Arc<Mutex<WatchFolderState>>,
+}
+
+impl<const W: u32, const H: u32> std::fmt::Debug for WatchFolderInput<W, H> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let state = self.state.lock().unwrap();
+    f.debug_struct("WatchFolderInput")
+      .field("directory", &state.directory)
+      .field("done_dir", &state.done_dir)
+      .field("failed_dir", &state.failed_dir)
+      .field("settle", &state.settle)
+      .finish()
+  }
+}
+
+impl<const W: u32, const H: u32> FromUrlWithScheme for WatchFolderInput<W, H> {
+  const SCHEME: &'static str = "watch";
+}
+
+impl<const W: u32, const H: u32> FromUrl for WatchFolderInput<W, H> {
+  type Error = WatchFolderInputError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    if url.scheme() != Self::SCHEME {
+      error!(
+        "URI scheme mismatch: expected '{}', found '{}'",
+        Self::SCHEME,
+        url.scheme()
+      );
+      return Err(WatchFolderInputError::SchemeMismatch);
+    }
+
+    let query: HashMap<String, String> = url
+      .query_pairs()
+      .map(|(k, v)| (String::from(k), String::from(v)))
+      .collect();
+    let done_dir = query.get("done").map(PathBuf::from);
+    let failed_dir = query.get("failed").map(PathBuf::from);
+    let settle = query
+      .get("settle_ms")
+      .and_then(|v| v.parse::<u64>().ok())
+      .unwrap_or(WATCH_FOLDER_SETTLE_MS);
+
+    Self::new(
+      Path::new(url.path()),
+      done_dir,
+      failed_dir,
+      Duration::from_millis(settle),
+    )
+  }
+}
+
+impl<const W: u32, const H: u32> WatchFolderInput<W, H> {
+  pub fn new(
+    directory: &Path,
+    done_dir: Option<PathBuf>,
+    failed_dir: Option<PathBuf>,
+    settle: Duration,
+  ) -> Result<Self, WatchFolderInputError> {
+    if !directory.is_dir() {
+      return Err(WatchFolderInputError::NotADirectory(
+        directory.display().to_string(),
+      ));
+    }
+    let directory = directory.canonicalize()?;
+
+    let (tx, events) = channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(&directory, RecursiveMode::NonRecursive)?;
+    info!("开始监控目录: {}", directory.display());
+
+    Ok(Self {
+      state: Arc::new(Mutex::new(WatchFolderState {
+        directory,
+        done_dir,
+        failed_dir,
+        settle,
+        _watcher: watcher,
+        events,
+        pending: HashMap::new(),
+        ready: VecDeque::new(),
+      })),
+    })
+  }
+
+  pub fn into_nchw(self) -> WatchFolderInputNchw<W, H> {
+    WatchFolderInputNchw { inner: self }
+  }
+
+  pub fn into_nhwc(self) -> WatchFolderInputNhwc<W, H> {
+    WatchFolderInputNhwc { inner: self }
+  }
+
+  fn next_image(&self) -> Option<RgbImage> {
+    self.state.lock().unwrap().next_image()
+  }
+}
+
+impl WatchFolderState {
+  fn next_image(&mut self) -> Option<RgbImage> {
+    let interval = (self.settle / 2).max(Duration::from_millis(50));
+    loop {
+      while let Some(path) = self.ready.pop_front() {
+        if let Some(image) = self.load(&path) {
+          return Some(image);
+        }
+      }
+
+      match self.events.recv_timeout(interval) {
+        Ok(Ok(event)) => self.handle_event(event),
+        Ok(Err(e)) => warn!("监控目录事件错误: {}", e),
+        Err(RecvTimeoutError::Timeout) => {}
+        Err(RecvTimeoutError::Disconnected) => {
+          error!("目录监控已停止: {}", self.directory.display());
+          return None;
+        }
+      }
+
+      self.poll_pending();
+    }
+  }
+
+  fn is_candidate(&self, path: &Path) -> bool {
+    let hidden = path
+      .file_name()
+      .and_then(|name| name.to_str())
+      .map(|name| name.starts_with('.'))
+      .unwrap_or(true);
+    path.parent() == Some(self.directory.as_path())
+      && !hidden
+      && ImageFormat::from_path(path).is_ok()
+  }
+
+  fn handle_event(&mut self, event: Event) {
+    debug!("监控目录事件: {:?}", event);
+    match event.kind {
+      // 写入完成或移动进入目录，文件已完整
+      EventKind::Access(AccessKind::Close(AccessMode::Write))
+      | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+        for path in event.paths {
+          self.mark_ready(path);
+        }
+      }
+      EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+        if let Some(from) = event.paths.first() {
+          self.pending.remove(from);
+        }
+        if let Some(to) = event.paths.last() {
+          self.mark_ready(to.clone());
+        }
+      }
+      EventKind::Modify(ModifyKind::Name(RenameMode::From)) | EventKind::Remove(_) => {
+        for path in event.paths {
+          self.pending.remove(&path);
+        }
+      }
+      EventKind::Create(_) | EventKind::Modify(_) => {
+        for path in event.paths {
+          if self.is_candidate(&path) {
+            let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            self.pending.insert(
+              path,
+              PendingFile {
+                size,
+                since: Instant::now(),
+              },
+            );
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn mark_ready(&mut self, path: PathBuf) {
+    self.pending.remove(&path);
+    if self.is_candidate(&path) && !self.ready.contains(&path) {
+      self.ready.push_back(path);
+    }
+  }
+
+  /// 对没有收到写入关闭事件的文件，以大小稳定作为写入完成的依据
+  fn poll_pending(&mut self) {
+    let mut settled = Vec::new();
+    self.pending.retain(|path, pending| {
+      let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+      };
+      if metadata.len() != pending.size {
+        pending.size = metadata.len();
+        pending.since = Instant::now();
+        return true;
+      }
+      if pending.since.elapsed() >= self.settle {
+        settled.push(path.clone());
+        return false;
+      }
+      true
+    });
+    for path in settled {
+      self.mark_ready(path);
+    }
+  }
+
+  fn load(&self, path: &Path) -> Option<RgbImage> {
+    match decode_image(path) {
+      Ok(image) => {
+        info!("读取监控目录图像: {}", path.display());
+        if let Some(done_dir) = &self.done_dir {
+          move_file(path, done_dir);
+        }
+        Some(image)
+      }
+      Err(e) => {
+        error!("读取监控目录图像失败: {}, 错误: {}", path.display(), e);
+        if let Some(failed_dir) = &self.failed_dir {
+          move_file(path, failed_dir);
+        }
+        None
+      }
+    }
+  }
+}
+
+fn decode_image(path: &Path) -> Result<RgbImage, WatchFolderInputError> {
+  let image = ImageReader::open(path)?.with_guessed_format()?.decode()?;
+  Ok(image.to_rgb8())
+}
+
+fn move_file(path: &Path, directory: &Path) {
+  let Some(file_name) = path.file_name() else {
+    return;
+  };
+  let target = directory.join(file_name);
+  let result = std::fs::create_dir_all(directory)
+    .and_then(|_| std::fs::rename(path, &target))
+    // 跨文件系统时 rename 会失败，退化为复制后删除
+    .or_else(|_| std::fs::copy(path, &target).and_then(|_| std::fs::remove_file(path)));
+  if let Err(e) = result {
+    warn!(
+      "移动文件失败: {} -> {}, 错误: {}",
+      path.display(),
+      target.display(),
+      e
+    );
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchFolderInputNchw<const W: u32, const H: u32> {
+  inner: WatchFolderInput<W, H>,
+}
+
+impl<const W: u32, const H: u32> Iterator for WatchFolderInputNchw<W, H> {
+  type Item = RgbNchwFrame<W, H>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.inner.next_image().map(RgbNchwFrame::from)
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchFolderInputNhwc<const W: u32, const H: u32> {
+  inner: WatchFolderInput<W, H>,
+}
+
+impl<const W: u32, const H: u32> Iterator for WatchFolderInputNhwc<W, H> {
+  type Item = RgbNhwcFrame<W, H>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.inner.next_image().map(RgbNhwcFrame::from)
+  }
+}
@@ -28,11 +28,79 @@ pub struct BBox {
   pub y_max: f32,
 }
 
+impl BBox {
+  pub fn width(&self) -> f32 {
+    (self.x_max - self.x_min).max(0.0)
+  }
+
+  pub fn height(&self) -> f32 {
+    (self.y_max - self.y_min).max(0.0)
+  }
+
+  pub fn area(&self) -> f32 {
+    self.width() * self.height()
+  }
+
+  /// 交并比
+  pub fn iou(&self, other: &BBox) -> f32 {
+    let inter = BBox {
+      x_min: self.x_min.max(other.x_min),
+      y_min: self.y_min.max(other.y_min),
+      x_max: self.x_max.min(other.x_max),
+      y_max: self.y_max.min(other.y_max),
+    }
+    .area();
+    let union = self.area() + other.area() - inter;
+    if union <= 0.0 { 0.0 } else { inter / union }
+  }
+}
+
 #[derive(Debug, Clone)]
 pub struct DetectItem<T> {
   pub kind: T,
   pub score: f32,
   pub bbox: BBox,
+  /// 实例分割掩码，只有分割模型会输出
+  pub mask: Option<Mask>,
+  /// 姿态关键点，只有姿态模型会输出
+  pub keypoints: Option<Pose>,
+  /// 旋转边框，只有旋转检测模型会输出，此时 `bbox` 为其外接矩形
+  pub obb: Option<RotatedBox>,
+  /// 级联二级模型给出的标签，按分数从高到低排列
+  pub attributes: Vec<Attribute>,
+}
+
+impl<T> DetectItem<T> {
+  /// 只有类别、分数和边框的检测项，其余字段由具体模型按需填充
+  pub fn new(kind: T, score: f32, bbox: BBox) -> Self {
+    Self {
+      kind,
+      score,
+      bbox,
+      mask: None,
+      keypoints: None,
+      obb: None,
+      attributes: Vec::new(),
+    }
+  }
+}
+
+/// 二级模型的标签，类别与检测模型不同，因此以编号和名称保存
+#[derive(Debug, Clone)]
+pub struct Attribute {
+  pub id: u32,
+  pub label: String,
+  pub score: f32,
+}
+
+impl<T: WithLabel> From<&ClassifyItem<T>> for Attribute {
+  fn from(item: &ClassifyItem<T>) -> Self {
+    Self {
+      id: item.kind.to_label_id(),
+      label: item.kind.to_label_str(),
+      score: item.score,
+    }
+  }
 }
 
 #[derive(Debug, Clone)]
@@ -46,41 +114,267 @@ impl<T> DetectResult<T> {
   }
 }
 
+#[derive(Debug, Clone)]
+pub struct ClassifyItem<T> {
+  pub kind: T,
+  pub score: f32,
+}
+
+/// 分类结果，按分数从高到低排列
+#[derive(Debug, Clone)]
+pub struct ClassifyResult<T> {
+  pub items: Box<[ClassifyItem<T>]>,
+}
+
+impl<T> ClassifyResult<T> {
+  pub fn is_empty(&self) -> bool {
+    self.items.is_empty()
+  }
+
+  /// 分数最高的类别
+  pub fn top(&self) -> Option<&ClassifyItem<T>> {
+    self.items.first()
+  }
+}
+
 use crate::{FromUrl, FromUrlWithScheme, input::AsNhwcFrame};
 
+mod tensor;
+pub use self::tensor::{Quantization, Tensor, TensorDType, TensorData, TensorView, Tensors};
+
+mod mask;
+pub use self::mask::Mask;
+
+mod obb;
+pub use self::obb::RotatedBox;
+
+mod pose;
+pub use self::pose::{COCO_KEYPOINTS, COCO_SKELETON, Keypoint, Pose, parse_skeleton};
+
+mod runtime_label;
+pub use self::runtime_label::{LabelSet, RuntimeLabel, RuntimeLabelError, label_num};
+
+mod label_map;
+pub use self::label_map::{LabelMap, LabelMapError, MappedLabel, Remap};
+
+mod class_filter;
+pub use self::class_filter::{ClassFilter, ClassFilterError, ClassRules};
+
+mod nms;
+pub use self::nms::{Nms, NmsError, NmsMethod, NmsMode, nms};
+
+mod batch;
+pub use self::batch::{BatchModel, BatchPostprocess, Batched};
+
+mod info;
+pub use self::info::{ModelInfo, ModelInfoError, TensorInfo, TensorLayout};
+
+mod pool;
+pub use self::pool::{ModelPool, ModelPoolBuilder, PoolError, infer_ordered};
+
+mod rknn_model;
+pub use self::rknn_model::{RknnModel, RknnModelError, inspect_rknn};
+
 #[cfg(feature = "model_yolo26")]
 mod yolo26;
 #[cfg(feature = "model_yolo26")]
 pub use self::yolo26::{Yolo26, Yolo26Builder, Yolo26Error, Yolo26Nhwc, Yolo26Postprocess};
 
+#[cfg(feature = "model_yolov8")]
+mod yolov8;
+#[cfg(feature = "model_yolov8")]
+pub use self::yolov8::{YoloV8, YoloV8Builder, YoloV8Error, YoloV8Nhwc, YoloV8Postprocess};
+
+#[cfg(feature = "model_yolov8_seg")]
+mod yolov8_seg;
+#[cfg(feature = "model_yolov8_seg")]
+pub use self::yolov8_seg::{YoloV8SegBuilder, YoloV8SegError, YoloV8SegPostprocess};
+
+#[cfg(feature = "model_yolov8_pose")]
+mod yolov8_pose;
+#[cfg(feature = "model_yolov8_pose")]
+pub use self::yolov8_pose::{YoloV8PoseBuilder, YoloV8PoseError, YoloV8PosePostprocess};
+
+#[cfg(feature = "model_yolov8_obb")]
+mod yolov8_obb;
+#[cfg(feature = "model_yolov8_obb")]
+pub use self::yolov8_obb::{YoloV8ObbBuilder, YoloV8ObbError, YoloV8ObbPostprocess};
+
+#[cfg(feature = "model_yolov5")]
+mod yolov5;
+#[cfg(feature = "model_yolov5")]
+pub use self::yolov5::{YoloV5, YoloV5Builder, YoloV5Error, YoloV5Nhwc, YoloV5Postprocess};
+
+#[cfg(feature = "model_classify")]
+mod classify;
+#[cfg(feature = "model_classify")]
+pub use self::classify::{
+  Classify, ClassifyBuilder, ClassifyError, ClassifyNhwc, ClassifyPostprocess,
+};
+
+#[cfg(feature = "model_cascade")]
+mod cascade;
+#[cfg(feature = "model_cascade")]
+pub use self::cascade::{Cascade, CascadeBuilder, CascadeError, CascadeOutput, CascadePostprocess};
+
+#[cfg(feature = "model_tiled")]
+mod tiled;
+#[cfg(feature = "model_tiled")]
+pub use self::tiled::{TileMerge, TileMergeMethod, Tiled, TiledBuilder, TiledError};
+
+#[cfg(feature = "model_onnx")]
+mod yolo26_onnx;
+#[cfg(feature = "model_onnx")]
+pub use self::yolo26_onnx::{
+  OnnxInputLayout, Yolo26Onnx, Yolo26OnnxBuilder, Yolo26OnnxError, Yolo26OnnxNhwc,
+};
+
+#[cfg(feature = "model_replay")]
+mod replay;
+#[cfg(feature = "model_replay")]
+pub use self::replay::{
+  RecordingPostprocess, ReplayBuilder, ReplayError, ReplayModel, TensorRecorder, read_tensors,
+  write_tensors,
+};
+
+#[cfg(feature = "hot_reload")]
+mod reload;
+#[cfg(feature = "hot_reload")]
+pub use self::reload::{
+  HotReload, HotReloadBuilder, HotReloadError, ReloadModel, ReloadPostprocess, ReloadWatcher,
+  Reloaded, Stage, effective_url,
+};
+
 pub type DetectionNhwc<const W: u32, const H: u32> =
-  Detection<W, H, crate::frame::RgbNhwcFrame<H, W>>;
+  Detection<W, H, crate::frame::RgbNhwcFrame<W, H>>;
 
 #[derive(Error, Debug)]
 pub enum DetectionError {
+  #[error("RKNN 模型错误: {0}")]
+  RknnModelError(#[from] rknn_model::RknnModelError),
   #[cfg(feature = "model_yolo26")]
   #[error("Yolo26 错误: {0}")]
   Yolo26Error(#[from] yolo26::Yolo26Error),
+  #[cfg(feature = "model_yolov8")]
+  #[error("YOLOv8 错误: {0}")]
+  YoloV8Error(#[from] yolov8::YoloV8Error),
+  #[cfg(feature = "model_yolov8_seg")]
+  #[error("YOLOv8 分割错误: {0}")]
+  YoloV8SegError(#[from] yolov8_seg::YoloV8SegError),
+  #[cfg(feature = "model_yolov8_pose")]
+  #[error("YOLOv8 姿态错误: {0}")]
+  YoloV8PoseError(#[from] yolov8_pose::YoloV8PoseError),
+  #[cfg(feature = "model_yolov8_obb")]
+  #[error("YOLOv8 旋转检测错误: {0}")]
+  YoloV8ObbError(#[from] yolov8_obb::YoloV8ObbError),
+  #[cfg(feature = "model_yolov5")]
+  #[error("YOLOv5 错误: {0}")]
+  YoloV5Error(#[from] yolov5::YoloV5Error),
+  #[cfg(feature = "model_classify")]
+  #[error("分类模型错误: {0}")]
+  ClassifyError(#[from] classify::ClassifyError),
+  #[cfg(feature = "model_onnx")]
+  #[error("Yolo26 ONNX 错误: {0}")]
+  Yolo26OnnxError(#[from] yolo26_onnx::Yolo26OnnxError),
+  #[cfg(feature = "model_replay")]
+  #[error("回放错误: {0}")]
+  ReplayError(#[from] replay::ReplayError),
 }
 
 pub enum Detection<const W: u32, const H: u32, F> {
   #[cfg(feature = "model_yolo26")]
   Yolo26(Yolo26<W, H, F>),
+  #[cfg(feature = "model_yolov8")]
+  YoloV8(YoloV8<W, H, F>),
+  #[cfg(feature = "model_yolov5")]
+  YoloV5(YoloV5<W, H, F>),
+  #[cfg(feature = "model_classify")]
+  Classify(Classify<W, H, F>),
+  #[cfg(feature = "model_onnx")]
+  Yolo26Onnx(Yolo26Onnx<W, H, F>),
+  #[cfg(feature = "model_replay")]
+  Replay(ReplayModel<W, H, F>),
 }
-pub enum DetectionOutput {
-  RknnOutput(rknpu::Output),
-}
+
+/// 模型输出，所有后端都转换为与后端无关的张量
+pub type DetectionOutput = Tensors;
 
 impl<const W: u32, const H: u32, F> FromUrl for Detection<W, H, F> {
   type Error = DetectionError;
 
+  /// 按运行时类别表加载模型，见 [`Detection::from_url_with_label`]
   fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    Self::from_url_with_label::<RuntimeLabel>(url)
+  }
+}
+
+impl<const W: u32, const H: u32, F> Detection<W, H, F> {
+  /// 加载模型，支持的模型在加载时检查输出的类别数量是否与 `T` 一致
+  ///
+  /// `T` 为 [`RuntimeLabel`] 且未安装类别表时不检查。
+  pub fn from_url_with_label<T: WithLabel>(url: &Url) -> Result<Self, DetectionError> {
+    let num_labels = label_num::<T>();
     match url.scheme() {
       #[cfg(feature = "model_yolo26")]
       Yolo26Builder::SCHEME => {
-        let model = Yolo26Builder::from_url(url)?.build_model()?;
+        let model = Yolo26Builder::from_url(url)?
+          .labels(num_labels)
+          .build_model()?;
         Ok(Detection::Yolo26(model))
       }
+      #[cfg(feature = "model_yolov8")]
+      YoloV8Builder::SCHEME => {
+        let model = YoloV8Builder::from_url(url)?
+          .labels(num_labels)
+          .build_model()?;
+        Ok(Detection::YoloV8(model))
+      }
+      #[cfg(feature = "model_yolov8_seg")]
+      YoloV8SegBuilder::SCHEME => {
+        // 分割模型与检测模型使用相同的推理，只在后处理上有所区别
+        let model = YoloV8SegBuilder::from_url(url)?
+          .labels(num_labels)
+          .build_model()?;
+        Ok(Detection::YoloV8(model))
+      }
+      #[cfg(feature = "model_yolov8_pose")]
+      YoloV8PoseBuilder::SCHEME => {
+        let model = YoloV8PoseBuilder::from_url(url)?
+          .labels(num_labels)
+          .build_model()?;
+        Ok(Detection::YoloV8(model))
+      }
+      #[cfg(feature = "model_yolov8_obb")]
+      YoloV8ObbBuilder::SCHEME => {
+        let model = YoloV8ObbBuilder::from_url(url)?
+          .labels(num_labels)
+          .build_model()?;
+        Ok(Detection::YoloV8(model))
+      }
+      #[cfg(feature = "model_yolov5")]
+      YoloV5Builder::SCHEME => {
+        let model = YoloV5Builder::from_url(url)?
+          .labels(num_labels)
+          .build_model()?;
+        Ok(Detection::YoloV5(model))
+      }
+      #[cfg(feature = "model_classify")]
+      ClassifyBuilder::SCHEME => {
+        let model = ClassifyBuilder::from_url(url)?
+          .labels(num_labels)
+          .build_model()?;
+        Ok(Detection::Classify(model))
+      }
+      #[cfg(feature = "model_onnx")]
+      Yolo26OnnxBuilder::SCHEME => {
+        let model = Yolo26OnnxBuilder::from_url(url)?.build_model()?;
+        Ok(Detection::Yolo26Onnx(model))
+      }
+      #[cfg(feature = "model_replay")]
+      ReplayBuilder::SCHEME => {
+        let model = ReplayBuilder::from_url(url)?.build_model()?;
+        Ok(Detection::Replay(model))
+      }
       _ => Err(DetectionError::Yolo26Error(
         yolo26::Yolo26Error::ModelPathError(format!("Unsupported model scheme: {}", url.scheme())),
       )),
@@ -88,7 +382,7 @@ impl<const W: u32, const H: u32, F> FromUrl for Detection<W, H, F> {
   }
 }
 
-impl<const W: u32, const H: u32, Frame: AsNhwcFrame<H, W>> Model for Detection<W, H, Frame> {
+impl<const W: u32, const H: u32, Frame: AsNhwcFrame<W, H>> Model for Detection<W, H, Frame> {
   type Input = Frame;
   type Output = DetectionOutput;
   type Error = DetectionError;
@@ -96,9 +390,34 @@ impl<const W: u32, const H: u32, Frame: AsNhwcFrame<H, W>> Model for Detection<W
   fn infer(&self, input: &Self::Input) -> Result<Self::Output, Self::Error> {
     match self {
       #[cfg(feature = "model_yolo26")]
-      Detection::Yolo26(model) => Ok(DetectionOutput::RknnOutput(
-        model.infer(input).map_err(DetectionError::from)?,
-      )),
+      Detection::Yolo26(model) => model.infer(input).map_err(DetectionError::from),
+      #[cfg(feature = "model_yolov8")]
+      Detection::YoloV8(model) => model.infer(input).map_err(DetectionError::from),
+      #[cfg(feature = "model_yolov5")]
+      Detection::YoloV5(model) => model.infer(input).map_err(DetectionError::from),
+      #[cfg(feature = "model_classify")]
+      Detection::Classify(model) => model.infer(input).map_err(DetectionError::from),
+      #[cfg(feature = "model_onnx")]
+      Detection::Yolo26Onnx(model) => model.infer(input).map_err(DetectionError::from),
+      #[cfg(feature = "model_replay")]
+      Detection::Replay(model) => model.infer(input).map_err(DetectionError::from),
+    }
+  }
+}
+
+impl<const W: u32, const H: u32, Frame: AsNhwcFrame<W, H>> BatchModel for Detection<W, H, Frame> {
+  fn infer_batch(&self, inputs: &[Self::Input]) -> Result<Vec<Self::Output>, Self::Error> {
+    match self {
+      #[cfg(feature = "model_yolo26")]
+      Detection::Yolo26(model) => model.infer_batch(inputs).map_err(DetectionError::from),
+      #[cfg(feature = "model_yolov8")]
+      Detection::YoloV8(model) => model.infer_batch(inputs).map_err(DetectionError::from),
+      #[cfg(feature = "model_yolov5")]
+      Detection::YoloV5(model) => model.infer_batch(inputs).map_err(DetectionError::from),
+      #[cfg(feature = "model_classify")]
+      Detection::Classify(model) => model.infer_batch(inputs).map_err(DetectionError::from),
+      #[allow(unreachable_patterns)]
+      _ => inputs.iter().map(|input| self.infer(input)).collect(),
     }
   }
 }
@@ -106,6 +425,16 @@ impl<const W: u32, const H: u32, Frame: AsNhwcFrame<H, W>> Model for Detection<W
 pub enum DetectionPostprocess<const W: u32, const H: u32, T, R: Runtime> {
   #[cfg(feature = "model_yolo26")]
   Yolo26(Yolo26Postprocess<W, H, T, R>),
+  #[cfg(feature = "model_yolov8")]
+  YoloV8(YoloV8Postprocess<W, H, T>),
+  #[cfg(feature = "model_yolov8_seg")]
+  YoloV8Seg(YoloV8SegPostprocess<W, H, T>),
+  #[cfg(feature = "model_yolov8_pose")]
+  YoloV8Pose(YoloV8PosePostprocess<W, H, T>),
+  #[cfg(feature = "model_yolov8_obb")]
+  YoloV8Obb(YoloV8ObbPostprocess<W, H, T>),
+  #[cfg(feature = "model_yolov5")]
+  YoloV5(YoloV5Postprocess<W, H, T>),
 }
 
 impl<const W: u32, const H: u32, T: WithLabel, R: Runtime> Postprocess
@@ -118,11 +447,17 @@ impl<const W: u32, const H: u32, T: WithLabel, R: Runtime> Postprocess
   fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
     match self {
       #[cfg(feature = "model_yolo26")]
-      DetectionPostprocess::Yolo26(post) => match output {
-        DetectionOutput::RknnOutput(rknn_output) => {
-          post.process(rknn_output).map_err(DetectionError::from)
-        }
-      },
+      DetectionPostprocess::Yolo26(post) => post.process(output).map_err(DetectionError::from),
+      #[cfg(feature = "model_yolov8")]
+      DetectionPostprocess::YoloV8(post) => post.process(output).map_err(DetectionError::from),
+      #[cfg(feature = "model_yolov8_seg")]
+      DetectionPostprocess::YoloV8Seg(post) => post.process(output).map_err(DetectionError::from),
+      #[cfg(feature = "model_yolov8_pose")]
+      DetectionPostprocess::YoloV8Pose(post) => post.process(output).map_err(DetectionError::from),
+      #[cfg(feature = "model_yolov8_obb")]
+      DetectionPostprocess::YoloV8Obb(post) => post.process(output).map_err(DetectionError::from),
+      #[cfg(feature = "model_yolov5")]
+      DetectionPostprocess::YoloV5(post) => post.process(output).map_err(DetectionError::from),
     }
   }
 }
@@ -139,9 +474,90 @@ impl<const W: u32, const H: u32, T: WithLabel, R: Runtime> FromUrl
         let post = Yolo26Builder::from_url(url)?.build_postprocess()?;
         Ok(DetectionPostprocess::Yolo26(post))
       }
+      #[cfg(feature = "model_yolov8")]
+      YoloV8Builder::SCHEME => {
+        let post = YoloV8Builder::from_url(url)?.build_postprocess()?;
+        Ok(DetectionPostprocess::YoloV8(post))
+      }
+      #[cfg(feature = "model_yolov8_seg")]
+      YoloV8SegBuilder::SCHEME => {
+        let post = YoloV8SegBuilder::from_url(url)?.build_postprocess()?;
+        Ok(DetectionPostprocess::YoloV8Seg(post))
+      }
+      #[cfg(feature = "model_yolov8_pose")]
+      YoloV8PoseBuilder::SCHEME => {
+        let post = YoloV8PoseBuilder::from_url(url)?.build_postprocess()?;
+        Ok(DetectionPostprocess::YoloV8Pose(post))
+      }
+      #[cfg(feature = "model_yolov8_obb")]
+      YoloV8ObbBuilder::SCHEME => {
+        let post = YoloV8ObbBuilder::from_url(url)?.build_postprocess()?;
+        Ok(DetectionPostprocess::YoloV8Obb(post))
+      }
+      #[cfg(feature = "model_yolov5")]
+      YoloV5Builder::SCHEME => {
+        let post = YoloV5Builder::from_url(url)?.build_postprocess()?;
+        Ok(DetectionPostprocess::YoloV5(post))
+      }
+      #[cfg(feature = "model_onnx")]
+      Yolo26OnnxBuilder::SCHEME => {
+        let post = Yolo26OnnxBuilder::from_url(url)?.build_postprocess()?;
+        Ok(DetectionPostprocess::Yolo26(post))
+      }
+      #[cfg(feature = "model_replay")]
+      ReplayBuilder::SCHEME => {
+        let model_url = ReplayBuilder::from_url(url)?.model_url()?;
+        Self::from_url(&model_url)
+      }
       _ => Err(DetectionError::Yolo26Error(
         yolo26::Yolo26Error::ModelPathError(format!("Unsupported model scheme: {}", url.scheme())),
       )),
     }
   }
 }
+
+/// 分类后处理，模型推理使用 [`Detection`]
+#[cfg(feature = "model_classify")]
+pub enum ClassificationPostprocess<T> {
+  Classify(ClassifyPostprocess<T>),
+}
+
+#[cfg(feature = "model_classify")]
+impl<T: WithLabel> Postprocess for ClassificationPostprocess<T> {
+  type Input = DetectionOutput;
+  type Output = ClassifyResult<T>;
+  type Error = DetectionError;
+
+  fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+    match self {
+      ClassificationPostprocess::Classify(post) => {
+        post.process(output).map_err(DetectionError::from)
+      }
+    }
+  }
+}
+
+#[cfg(feature = "model_classify")]
+impl<T: WithLabel> FromUrl for ClassificationPostprocess<T> {
+  type Error = DetectionError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    match url.scheme() {
+      ClassifyBuilder::SCHEME => {
+        let post = ClassifyBuilder::from_url(url)?.build_postprocess()?;
+        Ok(ClassificationPostprocess::Classify(post))
+      }
+      #[cfg(feature = "model_replay")]
+      ReplayBuilder::SCHEME => {
+        let model_url = ReplayBuilder::from_url(url)?.model_url()?;
+        Self::from_url(&model_url)
+      }
+      _ => Err(DetectionError::ClassifyError(
+        classify::ClassifyError::ModelPathError(format!(
+          "Unsupported model scheme: {}",
+          url.scheme()
+        )),
+      )),
+    }
+  }
+}
@@ -0,0 +1,168 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/batch.rs - 批量推理
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 批量推理
+//!
+//! [`Batched`] 以 [`FrameBatch`] 为输入，一次调用推理多帧，输出每帧的模型输出；
+//! [`BatchPostprocess`] 对每帧输出分别后处理，结果与逐帧推理相同。
+//!
+//! 支持批量的后端实现 [`BatchModel::infer_batch`]：导出为多批次的 RKNN 模型一次推理一整批，
+//! 减少每次调用的开销；其他后端逐帧推理。
+//!
+//! ```rust,ignore
+//! let model = Batched::new(model);
+//! let post = BatchPostprocess::new(post);
+//! BenchmarkTask::default().run_task(Batcher::from_url(&input)?.apply(frames), model, post, BatchRender::new(output))?;
+//! ```
+
+use shanan_trait::Postprocess;
+
+use crate::{frame::FrameBatch, model::Model};
+
+/// 一次推理多帧的模型
+pub trait BatchModel: Model {
+  /// 按输入顺序返回每帧的输出，默认逐帧推理
+  fn infer_batch(&self, inputs: &[Self::Input]) -> Result<Vec<Self::Output>, Self::Error> {
+    inputs.iter().map(|input| self.infer(input)).collect()
+  }
+}
+
+/// 以 [`FrameBatch`] 为输入的模型
+pub struct Batched<M> {
+  model: M,
+}
+
+impl<M> Batched<M> {
+  pub fn new(model: M) -> Self {
+    Self { model }
+  }
+
+  pub fn inner(&self) -> &M {
+    &self.model
+  }
+}
+
+impl<M: BatchModel> Model for Batched<M> {
+  type Input = FrameBatch<M::Input>;
+  type Output = Vec<M::Output>;
+  type Error = M::Error;
+
+  fn infer(&self, input: &Self::Input) -> Result<Self::Output, Self::Error> {
+    self.model.infer_batch(input.frames())
+  }
+}
+
+/// 对一批输出逐帧后处理
+pub struct BatchPostprocess<P> {
+  post: P,
+}
+
+impl<P> BatchPostprocess<P> {
+  pub fn new(post: P) -> Self {
+    Self { post }
+  }
+}
+
+impl<P: Postprocess> Postprocess for BatchPostprocess<P> {
+  type Input = Vec<P::Input>;
+  type Output = Vec<P::Output>;
+  type Error = P::Error;
+
+  fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+    output
+      .into_iter()
+      .map(|output| self.post.process(output))
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use shanan_trait::Postprocess;
+
+  use super::{BatchModel, BatchPostprocess, Batched};
+  use crate::{frame::FrameBatch, model::Model};
+
+  /// 每帧输出为输入的前缀和，批量推理时在一个缓冲区中计算后按帧拆分
+  struct PrefixSum {
+    batch: usize,
+  }
+
+  impl Model for PrefixSum {
+    type Input = Vec<u32>;
+    type Output = Vec<u32>;
+    type Error = std::convert::Infallible;
+
+    fn infer(&self, input: &Self::Input) -> Result<Self::Output, Self::Error> {
+      Ok(
+        input
+          .iter()
+          .scan(0, |sum, x| {
+            *sum += x;
+            Some(*sum)
+          })
+          .collect(),
+      )
+    }
+  }
+
+  impl BatchModel for PrefixSum {
+    fn infer_batch(&self, inputs: &[Self::Input]) -> Result<Vec<Self::Output>, Self::Error> {
+      let mut outputs = Vec::with_capacity(inputs.len());
+      for chunk in inputs.chunks(self.batch) {
+        let len = chunk[0].len();
+        let data: Vec<u32> = chunk.iter().flatten().copied().collect();
+        let mut sums = Vec::with_capacity(data.len());
+        for frame in data.chunks(len) {
+          sums.extend(self.infer(&frame.to_vec())?);
+        }
+        outputs.extend(sums.chunks(len).map(<[u32]>::to_vec));
+      }
+      Ok(outputs)
+    }
+  }
+
+  /// 取最大值及其位置
+  struct ArgMax;
+
+  impl Postprocess for ArgMax {
+    type Input = Vec<u32>;
+    type Output = Option<(usize, u32)>;
+    type Error = std::convert::Infallible;
+
+    fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+      Ok(output.into_iter().enumerate().max_by_key(|(_, x)| *x))
+    }
+  }
+
+  #[test]
+  fn batched_matches_per_frame() {
+    let frames: Vec<Vec<u32>> = (0..7u32)
+      .map(|i| (0..5).map(|j| (i * 7 + j * 3) % 11).collect())
+      .collect();
+
+    let expected: Vec<_> = frames
+      .iter()
+      .map(|frame| ArgMax.process(PrefixSum { batch: 1 }.infer(frame).unwrap()))
+      .collect::<Result<_, _>>()
+      .unwrap();
+
+    let post = BatchPostprocess::new(ArgMax);
+    // 批大小不整除帧数时最后一批不足
+    for batch in [1, 3, 4, 7] {
+      let model = Batched::new(PrefixSum { batch });
+      let output = model.infer(&FrameBatch::from(frames.clone())).unwrap();
+      assert_eq!(post.process(output).unwrap(), expected, "批大小 {}", batch);
+    }
+  }
+}
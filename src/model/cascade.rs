@@ -0,0 +1,411 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/cascade.rs - 检测与分类级联
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 检测与分类级联
+//!
+//! [`Cascade`] 先运行检测模型及其后处理，从原始帧中裁剪选中的检测结果，缩放到二级模型的输入大小后
+//! 逐个推理；[`CascadePostprocess`] 对每个裁剪的输出做二级后处理，并把分类结果作为
+//! [`Attribute`](crate::model::Attribute) 附加到对应的检测结果上。常用于车型识别、是否佩戴安全帽等场景。
+//!
+//! 检测模型的后处理在 [`Cascade`] 的推理阶段完成，流水线的后处理阶段只包含二级后处理。
+//!
+//! ## 参数说明
+//!
+//! 参数从二级模型 URL 的查询参数中读取：
+//!
+//! ```text
+//! classify:///path/to/helmet.rknn?top_k=1&crop_classes=person&crop_min_size=32
+//! ```
+//!
+//! - `crop_classes`: 只裁剪这些检测类别，逗号分隔，类别名称或编号，默认全部
+//! - `crop_min_size`: 边框宽高的最小像素值，更小的检测不裁剪，默认 16
+//! - `crop_padding`: 裁剪区域按边框宽高向外扩展的比例，默认 0
+//!
+//! ## 使用
+//!
+//! ```rust,ignore
+//! let cascade = CascadeBuilder::from_url(&classifier_url)?;
+//! let model = cascade.apply(detector, detector_post, classifier)?;
+//! let post = cascade.build_postprocess(classifier_post);
+//! ```
+
+use shanan_trait::Postprocess;
+use thiserror::Error;
+use tracing::debug;
+use url::Url;
+
+use crate::{
+  FromUrl,
+  frame::{RgbNhwcFrame, crop_resize},
+  input::AsNhwcFrame,
+  model::{
+    Attribute, BBox, ClassFilter, ClassFilterError, ClassRules, ClassifyResult, DetectResult,
+    Model, WithLabel,
+  },
+};
+
+const CASCADE_MIN_SIZE: f32 = 16.0;
+const CASCADE_PADDING: f32 = 0.0;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Error, Debug)]
+pub enum CascadeError {
+  #[error("检测模型错误: {0}")]
+  DetectorError(BoxError),
+  #[error("二级模型错误: {0}")]
+  ClassifierError(BoxError),
+  #[error("类别过滤配置错误: {0}")]
+  ClassFilterError(#[from] ClassFilterError),
+}
+
+/// 检测结果与每个裁剪的二级模型输出，裁剪以检测结果的下标标识
+pub struct CascadeOutput<T, O> {
+  pub result: DetectResult<T>,
+  pub crops: Vec<(usize, O)>,
+}
+
+/// 级联模型，`W`、`H` 为检测模型输入大小，`CW`、`CH` 为二级模型输入大小
+pub struct Cascade<const W: u32, const H: u32, const CW: u32, const CH: u32, M, P, C> {
+  detector: M,
+  post: P,
+  classifier: C,
+  rules: ClassRules,
+  min_size: f32,
+  padding: f32,
+}
+
+pub struct CascadePostprocess<T, P> {
+  post: P,
+  _phantom: std::marker::PhantomData<T>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CascadeBuilder {
+  classes: ClassFilter,
+  min_size: f32,
+  padding: f32,
+}
+
+impl Default for CascadeBuilder {
+  fn default() -> Self {
+    Self {
+      classes: ClassFilter::default(),
+      min_size: CASCADE_MIN_SIZE,
+      padding: CASCADE_PADDING,
+    }
+  }
+}
+
+impl FromUrl for CascadeBuilder {
+  type Error = CascadeError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    let mut builder = CascadeBuilder::default();
+    for (k, v) in url.query_pairs() {
+      match k.as_ref() {
+        "crop_classes" => {
+          for name in v.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            builder.classes = builder.classes.include(name);
+          }
+        }
+        "crop_min_size" => builder.min_size = v.parse().unwrap_or(CASCADE_MIN_SIZE),
+        "crop_padding" => builder.padding = v.parse().unwrap_or(CASCADE_PADDING),
+        _ => {}
+      }
+    }
+    Ok(builder)
+  }
+}
+
+impl CascadeBuilder {
+  pub fn classes(mut self, classes: ClassFilter) -> Self {
+    self.classes = classes;
+    self
+  }
+
+  pub fn min_size(mut self, min_size: f32) -> Self {
+    self.min_size = min_size;
+    self
+  }
+
+  pub fn padding(mut self, padding: f32) -> Self {
+    self.padding = padding;
+    self
+  }
+
+  /// 组合检测模型、检测后处理与二级模型
+  pub fn apply<const W: u32, const H: u32, const CW: u32, const CH: u32, M, P, C, T>(
+    &self,
+    detector: M,
+    post: P,
+    classifier: C,
+  ) -> Result<Cascade<W, H, CW, CH, M, P, C>, CascadeError>
+  where
+    P: Postprocess<Output = DetectResult<T>>,
+    T: WithLabel,
+  {
+    Ok(Cascade {
+      detector,
+      post,
+      classifier,
+      rules: self.classes.resolve::<T>(0.0)?,
+      min_size: self.min_size,
+      padding: self.padding,
+    })
+  }
+
+  pub fn build_postprocess<T, P>(&self, post: P) -> CascadePostprocess<T, P> {
+    CascadePostprocess {
+      post,
+      _phantom: std::marker::PhantomData,
+    }
+  }
+}
+
+impl<const W: u32, const H: u32, const CW: u32, const CH: u32, M, P, C, T> Model
+  for Cascade<W, H, CW, CH, M, P, C>
+where
+  M: Model,
+  M::Input: AsNhwcFrame<W, H>,
+  M::Error: std::error::Error + Send + Sync + 'static,
+  P: Postprocess<Input = M::Output, Output = DetectResult<T>>,
+  P::Error: std::error::Error + Send + Sync + 'static,
+  C: Model<Input = RgbNhwcFrame<CW, CH>>,
+  C::Error: std::error::Error + Send + Sync + 'static,
+  T: WithLabel,
+{
+  type Input = M::Input;
+  type Output = CascadeOutput<T, C::Output>;
+  type Error = CascadeError;
+
+  fn infer(&self, input: &Self::Input) -> Result<Self::Output, Self::Error> {
+    let output = self
+      .detector
+      .infer(input)
+      .map_err(|e| CascadeError::DetectorError(Box::new(e)))?;
+    let result = self
+      .post
+      .process(output)
+      .map_err(|e| CascadeError::DetectorError(Box::new(e)))?;
+
+    let mut crops = Vec::new();
+    for (index, item) in result.items.iter().enumerate() {
+      let bbox = item.bbox;
+      if !self.rules.accept(item.kind.to_label_id(), item.score)
+        || bbox.width() < self.min_size
+        || bbox.height() < self.min_size
+      {
+        continue;
+      }
+
+      let region = BBox {
+        x_min: bbox.x_min - bbox.width() * self.padding,
+        y_min: bbox.y_min - bbox.height() * self.padding,
+        x_max: bbox.x_max + bbox.width() * self.padding,
+        y_max: bbox.y_max + bbox.height() * self.padding,
+      };
+      let data = crop_resize(
+        input.as_nhwc(),
+        (W as usize, H as usize),
+        &region,
+        (CW as usize, CH as usize),
+      );
+      let output = self
+        .classifier
+        .infer(&RgbNhwcFrame::from(data))
+        .map_err(|e| CascadeError::ClassifierError(Box::new(e)))?;
+      crops.push((index, output));
+    }
+    debug!("级联裁剪数量: {}", crops.len());
+
+    Ok(CascadeOutput { result, crops })
+  }
+}
+
+impl<T, T2, O, P> Postprocess for CascadePostprocess<T, P>
+where
+  P: Postprocess<Input = O, Output = ClassifyResult<T2>>,
+  P::Error: std::error::Error + Send + Sync + 'static,
+  T2: WithLabel,
+{
+  type Input = CascadeOutput<T, O>;
+  type Output = DetectResult<T>;
+  type Error = CascadeError;
+
+  fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+    let CascadeOutput { result, crops } = output;
+    let mut items = result.items.into_vec();
+    for (index, output) in crops {
+      let classes = self
+        .post
+        .process(output)
+        .map_err(|e| CascadeError::ClassifierError(Box::new(e)))?;
+      if let Some(item) = items.get_mut(index) {
+        item.attributes = classes.items.iter().map(Attribute::from).collect();
+      }
+    }
+
+    Ok(DetectResult {
+      items: items.into_boxed_slice(),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::convert::Infallible;
+
+  use shanan_trait::{Model, Postprocess, WithLabel};
+
+  use super::CascadeBuilder;
+  use crate::{
+    frame::RgbNhwcFrame,
+    input::AsNhwcFrame,
+    model::{BBox, ClassFilter, ClassifyItem, ClassifyResult, DetectItem, DetectResult},
+  };
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct TestLabel(u32);
+
+  impl WithLabel for TestLabel {
+    const LABEL_NUM: u32 = 3;
+
+    fn from_label_id(id: u32) -> Self {
+      Self(id)
+    }
+
+    fn to_label_id(&self) -> u32 {
+      self.0
+    }
+
+    fn to_label_str(&self) -> String {
+      format!("class{}", self.0)
+    }
+  }
+
+  /// 检测模型直接返回固定的检测结果
+  struct MockDetector;
+
+  impl Model for MockDetector {
+    type Input = RgbNhwcFrame<32, 16>;
+    type Output = ();
+    type Error = Infallible;
+
+    fn infer(&self, _: &Self::Input) -> Result<Self::Output, Self::Error> {
+      Ok(())
+    }
+  }
+
+  struct MockDetectorPost;
+
+  impl Postprocess for MockDetectorPost {
+    type Input = ();
+    type Output = DetectResult<TestLabel>;
+    type Error = Infallible;
+
+    fn process(&self, _: Self::Input) -> Result<Self::Output, Self::Error> {
+      let item = |class, [x_min, y_min, x_max, y_max]: [f32; 4]| {
+        DetectItem::new(
+          TestLabel(class),
+          0.9,
+          BBox {
+            x_min,
+            y_min,
+            x_max,
+            y_max,
+          },
+        )
+      };
+      Ok(DetectResult {
+        items: vec![
+          // 左半边
+          item(0, [0.0, 0.0, 16.0, 16.0]),
+          // 不在裁剪类别中
+          item(1, [16.0, 0.0, 32.0, 16.0]),
+          // 小于最小尺寸
+          item(0, [16.0, 0.0, 18.0, 2.0]),
+          // 右半边
+          item(0, [16.0, 0.0, 32.0, 16.0]),
+        ]
+        .into_boxed_slice(),
+      })
+    }
+  }
+
+  /// 二级模型输出裁剪的平均像素值
+  struct MockClassifier;
+
+  impl Model for MockClassifier {
+    type Input = RgbNhwcFrame<4, 4>;
+    type Output = u32;
+    type Error = Infallible;
+
+    fn infer(&self, input: &Self::Input) -> Result<Self::Output, Self::Error> {
+      let data = input.as_nhwc();
+      Ok(data.iter().map(|v| *v as u32).sum::<u32>() / data.len() as u32)
+    }
+  }
+
+  /// 平均像素值除以 100 作为类别
+  struct MockClassifierPost;
+
+  impl Postprocess for MockClassifierPost {
+    type Input = u32;
+    type Output = ClassifyResult<TestLabel>;
+    type Error = Infallible;
+
+    fn process(&self, value: Self::Input) -> Result<Self::Output, Self::Error> {
+      Ok(ClassifyResult {
+        items: vec![ClassifyItem {
+          kind: TestLabel(value / 100),
+          score: 1.0,
+        }]
+        .into_boxed_slice(),
+      })
+    }
+  }
+
+  #[test]
+  fn attaches_attributes_to_cropped_items() {
+    let builder = CascadeBuilder::default()
+      .classes(ClassFilter::default().include("class0"))
+      .min_size(4.0);
+    let cascade = builder
+      .apply::<32, 16, 4, 4, _, _, _, TestLabel>(MockDetector, MockDetectorPost, MockClassifier)
+      .unwrap();
+    let post = builder.build_postprocess(MockClassifierPost);
+
+    // 左半边像素为 10，右半边为 200
+    let frame: RgbNhwcFrame<32, 16> = (0..32 * 16)
+      .flat_map(|i| [if i % 32 < 16 { 10 } else { 200 }; 3])
+      .collect::<Vec<u8>>()
+      .into();
+    let output = cascade.infer(&frame).unwrap();
+    let crops: Vec<_> = output
+      .crops
+      .iter()
+      .map(|(index, value)| (*index, *value))
+      .collect();
+    assert_eq!(crops, [(0, 10), (3, 200)]);
+
+    let result = post.process(output).unwrap();
+    let attributes: Vec<Vec<u32>> = result
+      .items
+      .iter()
+      .map(|item| item.attributes.iter().map(|a| a.id).collect())
+      .collect();
+    assert_eq!(attributes, [vec![0], vec![], vec![], vec![2]]);
+    assert_eq!(result.items[3].attributes[0].label, "class2");
+  }
+}
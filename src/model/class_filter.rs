@@ -0,0 +1,383 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/class_filter.rs - 按类别过滤检测结果
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 按类别过滤
+//!
+//! 为每个类别设置单独的置信度阈值，并通过白名单/黑名单过滤类别。类别使用
+//! [`WithLabel::to_label_str`] 返回的名称或类别编号指定，参数从模型 URL 的查询参数中读取：
+//!
+//! - `class_thresh`: 类别阈值，形如 `person:0.3,cell phone:0.7`
+//! - `classes`: 只保留这些类别，逗号分隔
+//! - `exclude_classes`: 丢弃这些类别，逗号分隔
+//! - `class_file`: 从 TOML 文件读取以上配置
+//!
+//! TOML 文件格式：
+//!
+//! ```toml
+//! include = ["person", "car", "cell phone"]
+//! exclude = ["potted plant"]
+//!
+//! [thresholds]
+//! person = 0.3
+//! "cell phone" = 0.7
+//! ```
+//!
+//! 未设置阈值的类别使用模型的 `object_thresh`。
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+  FromUrl,
+  model::{DetectItem, WithLabel, label_num},
+};
+
+#[derive(Error, Debug)]
+pub enum ClassFilterError {
+  #[error("无效的类别阈值: {0}")]
+  InvalidThreshold(String),
+  #[error("未知的类别: {0}")]
+  UnknownClass(String),
+  #[error("类别配置文件读取错误: {0}")]
+  IoError(#[from] std::io::Error),
+  #[error("类别配置文件解析错误: {0}")]
+  TomlError(#[from] toml::de::Error),
+}
+
+/// 按名称指定的类别过滤配置
+#[derive(Debug, Clone, Default)]
+pub struct ClassFilter {
+  thresholds: HashMap<String, f32>,
+  include: Option<Vec<String>>,
+  exclude: Vec<String>,
+}
+
+fn split_names(value: &str) -> impl Iterator<Item = String> + '_ {
+  value
+    .split(',')
+    .map(str::trim)
+    .filter(|s| !s.is_empty())
+    .map(String::from)
+}
+
+impl FromUrl for ClassFilter {
+  type Error = ClassFilterError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    let mut filter = ClassFilter::default();
+    for (k, v) in url.query_pairs() {
+      match k.as_ref() {
+        "class_file" => filter = filter.merge_file(v.as_ref())?,
+        "class_thresh" => {
+          for pair in split_names(&v) {
+            let (name, thresh) = pair
+              .rsplit_once(':')
+              .and_then(|(name, t)| Some((name.trim(), t.trim().parse::<f32>().ok()?)))
+              .ok_or_else(|| ClassFilterError::InvalidThreshold(pair.clone()))?;
+            filter.thresholds.insert(name.to_string(), thresh);
+          }
+        }
+        "classes" => filter
+          .include
+          .get_or_insert_with(Vec::new)
+          .extend(split_names(&v)),
+        "exclude_classes" => filter.exclude.extend(split_names(&v)),
+        _ => continue,
+      }
+    }
+    Ok(filter)
+  }
+}
+
+impl ClassFilter {
+  pub fn threshold(mut self, class: &str, thresh: f32) -> Self {
+    self.thresholds.insert(class.to_string(), thresh);
+    self
+  }
+
+  pub fn include(mut self, class: &str) -> Self {
+    self
+      .include
+      .get_or_insert_with(Vec::new)
+      .push(class.to_string());
+    self
+  }
+
+  pub fn exclude(mut self, class: &str) -> Self {
+    self.exclude.push(class.to_string());
+    self
+  }
+
+  /// 合并 TOML 配置文件
+  pub fn merge_file(mut self, path: &str) -> Result<Self, ClassFilterError> {
+    let table = std::fs::read_to_string(path)?.parse::<toml::Table>()?;
+
+    let names = |key: &str| -> Result<Option<Vec<String>>, ClassFilterError> {
+      let Some(value) = table.get(key) else {
+        return Ok(None);
+      };
+      value
+        .as_array()
+        .and_then(|array| {
+          array
+            .iter()
+            .map(|v| v.as_str().map(String::from))
+            .collect::<Option<Vec<_>>>()
+        })
+        .map(Some)
+        .ok_or_else(|| ClassFilterError::UnknownClass(format!("{} 必须是字符串数组", key)))
+    };
+
+    if let Some(include) = names("include")? {
+      self.include.get_or_insert_with(Vec::new).extend(include);
+    }
+    if let Some(exclude) = names("exclude")? {
+      self.exclude.extend(exclude);
+    }
+    if let Some(thresholds) = table.get("thresholds").and_then(|v| v.as_table()) {
+      for (name, value) in thresholds {
+        let thresh = value
+          .as_float()
+          .or_else(|| value.as_integer().map(|v| v as f64))
+          .ok_or_else(|| ClassFilterError::InvalidThreshold(format!("{} = {}", name, value)))?;
+        self.thresholds.insert(name.clone(), thresh as f32);
+      }
+    }
+    Ok(self)
+  }
+
+  /// 将类别名称解析为类别编号
+  pub fn resolve<T: WithLabel>(&self, object_thresh: f32) -> Result<ClassRules, ClassFilterError> {
+    let num = label_num::<T>();
+    let names: HashMap<String, u32> = (0..num as u32)
+      .map(|id| (T::from_label_id(id).to_label_str(), id))
+      .collect();
+    let lookup = |name: &str| -> Result<usize, ClassFilterError> {
+      names
+        .get(name)
+        .copied()
+        .or_else(|| name.parse::<u32>().ok().filter(|id| (*id as usize) < num))
+        .map(|id| id as usize)
+        .ok_or_else(|| ClassFilterError::UnknownClass(name.to_string()))
+    };
+
+    let mut thresholds = vec![object_thresh; num];
+    for (name, thresh) in &self.thresholds {
+      thresholds[lookup(name)?] = *thresh;
+    }
+
+    let mut allowed = vec![self.include.is_none(); num];
+    for name in self.include.iter().flatten() {
+      allowed[lookup(name)?] = true;
+    }
+    for name in &self.exclude {
+      allowed[lookup(name)?] = false;
+    }
+
+    Ok(ClassRules {
+      thresholds,
+      allowed,
+      object_thresh,
+    })
+  }
+}
+
+/// 解析后的按类别规则
+#[derive(Debug, Clone)]
+pub struct ClassRules {
+  thresholds: Vec<f32>,
+  allowed: Vec<bool>,
+  object_thresh: f32,
+}
+
+impl ClassRules {
+  /// 所有类别都使用同一阈值
+  pub fn uniform(num: usize, object_thresh: f32) -> Self {
+    Self {
+      thresholds: vec![object_thresh; num],
+      allowed: vec![true; num],
+      object_thresh,
+    }
+  }
+
+  /// 解码时使用的阈值，即允许类别中的最低阈值
+  pub fn min_thresh(&self) -> f32 {
+    self
+      .thresholds
+      .iter()
+      .zip(&self.allowed)
+      .filter(|(_, allowed)| **allowed)
+      .map(|(t, _)| *t)
+      .reduce(f32::min)
+      .unwrap_or(self.object_thresh)
+  }
+
+  /// 每个类别是否允许，解码时跳过不允许的类别
+  pub fn allowed(&self) -> &[bool] {
+    &self.allowed
+  }
+
+  pub fn accept(&self, class_id: u32, score: f32) -> bool {
+    let id = class_id as usize;
+    self.allowed.get(id).copied().unwrap_or(false)
+      && score
+        > self
+          .thresholds
+          .get(id)
+          .copied()
+          .unwrap_or(self.object_thresh)
+  }
+
+  pub fn retain<T: WithLabel>(&self, items: &mut Vec<DetectItem<T>>) {
+    items.retain(|item| self.accept(item.kind.to_label_id(), item.score));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use url::Url;
+
+  use super::{ClassFilter, ClassFilterError, ClassRules};
+  use crate::{
+    FromUrl,
+    model::{BBox, DetectItem, WithLabel},
+  };
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct TestLabel(u32);
+
+  impl WithLabel for TestLabel {
+    const LABEL_NUM: u32 = 3;
+
+    fn from_label_id(id: u32) -> Self {
+      Self(id)
+    }
+
+    fn to_label_id(&self) -> u32 {
+      self.0
+    }
+
+    fn to_label_str(&self) -> String {
+      ["person", "car", "cell phone"][self.0 as usize].to_string()
+    }
+  }
+
+  fn resolve(filter: &ClassFilter) -> ClassRules {
+    filter.resolve::<TestLabel>(0.5).unwrap()
+  }
+
+  #[test]
+  fn parses_query() {
+    let url = Url::parse(
+      "yolov8:///m.rknn?class_thresh=person:0.3,cell%20phone:0.7&classes=person,cell%20phone",
+    )
+    .unwrap();
+    let rules = resolve(&ClassFilter::from_url(&url).unwrap());
+    assert_eq!(rules.thresholds, [0.3, 0.5, 0.7]);
+    assert_eq!(rules.allowed, [true, false, true]);
+    assert_eq!(rules.min_thresh(), 0.3);
+
+    let url = Url::parse("yolov8:///m.rknn?class_thresh=person").unwrap();
+    assert!(matches!(
+      ClassFilter::from_url(&url),
+      Err(ClassFilterError::InvalidThreshold(_))
+    ));
+  }
+
+  #[test]
+  fn parses_toml_file() {
+    let dir = std::env::temp_dir().join(format!("shanan-class-filter-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("classes.toml");
+    std::fs::write(
+      &path,
+      "include = [\"person\", \"car\"]\nexclude = [\"car\"]\n\n[thresholds]\nperson = 0.3\n\"cell phone\" = 1\n",
+    )
+    .unwrap();
+    let url = Url::parse(&format!(
+      "yolov8:///m.rknn?class_file={}&classes=cell%20phone",
+      path.display()
+    ))
+    .unwrap();
+    let rules = resolve(&ClassFilter::from_url(&url).unwrap());
+    assert_eq!(rules.thresholds, [0.3, 0.5, 1.0]);
+    assert_eq!(rules.allowed, [true, false, true]);
+
+    std::fs::write(&path, "include = \"person\"\n").unwrap();
+    assert!(matches!(
+      ClassFilter::default().merge_file(path.to_str().unwrap()),
+      Err(ClassFilterError::UnknownClass(_))
+    ));
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn rejects_unknown_classes() {
+    for filter in [
+      ClassFilter::default().exclude("dog"),
+      ClassFilter::default().include("3"),
+      ClassFilter::default().threshold("Person", 0.3),
+    ] {
+      assert!(matches!(
+        filter.resolve::<TestLabel>(0.5),
+        Err(ClassFilterError::UnknownClass(_))
+      ));
+    }
+    // 也可以使用类别编号
+    let rules = resolve(&ClassFilter::default().include("2"));
+    assert_eq!(rules.allowed, [false, false, true]);
+  }
+
+  #[test]
+  fn exclude_overrides_include() {
+    let rules = resolve(
+      &ClassFilter::default()
+        .include("person")
+        .include("car")
+        .exclude("car"),
+    );
+    assert_eq!(rules.allowed, [true, false, false]);
+
+    // 只设置黑名单时其余类别都允许
+    let rules = resolve(&ClassFilter::default().exclude("person"));
+    assert_eq!(rules.allowed, [false, true, true]);
+
+    // 没有允许的类别时最低阈值为 object_thresh
+    let rules = resolve(&ClassFilter::default().threshold("car", 0.1).exclude("car"));
+    assert_eq!(rules.min_thresh(), 0.5);
+  }
+
+  #[test]
+  fn retains_by_class_threshold() {
+    let rules = resolve(
+      &ClassFilter::default()
+        .threshold("person", 0.3)
+        .exclude("cell phone"),
+    );
+    let bbox = BBox {
+      x_min: 0.0,
+      y_min: 0.0,
+      x_max: 1.0,
+      y_max: 1.0,
+    };
+    let mut items: Vec<_> = [(0, 0.4), (0, 0.2), (1, 0.4), (1, 0.6), (2, 0.9), (7, 0.9)]
+      .into_iter()
+      .map(|(class, score)| DetectItem::new(TestLabel(class), score, bbox))
+      .collect();
+    rules.retain(&mut items);
+    let kept: Vec<_> = items.iter().map(|item| (item.kind.0, item.score)).collect();
+    assert_eq!(kept, [(0, 0.4), (1, 0.6)]);
+  }
+}
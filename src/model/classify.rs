@@ -0,0 +1,309 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/classify.rs - 图像分类
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 图像分类
+//!
+//! 分类模型输出一个长度为类别数量的向量，后处理经过 softmax 后取分数最高的 k 个类别，
+//! 结果为 [`ClassifyResult`]。
+//!
+//! ## URL Scheme
+//!
+//! ```text
+//! classify:///path/to/resnet50.rknn?top_k=5&min_score=0.1
+//! ```
+//!
+//! ## 参数说明
+//!
+//! - `top_k`: 保留的类别数量，默认 5
+//! - `min_score`: 最低分数，默认 0
+//! - `softmax`: 输出是否需要经过 softmax，默认 `true`；模型已输出概率时设为 `false`
+//! - `class_thresh`、`classes`、`exclude_classes`、`class_file`: 按类别的阈值与过滤，见 [`ClassFilter`]
+
+use rknpu::InitFlags;
+use shanan_trait::Postprocess;
+use thiserror::Error;
+use tracing::debug;
+use url::Url;
+
+use crate::{
+  FromUrl, FromUrlWithScheme,
+  model::{
+    ClassFilter, ClassFilterError, ClassRules, ClassifyItem, ClassifyResult, RknnModel,
+    RknnModelError, Tensors, WithLabel, label_num,
+  },
+};
+
+const CLASSIFY_TOP_K: usize = 5;
+const CLASSIFY_MIN_SCORE: f32 = 0.0;
+const CLASSIFY_NUM_OUTPUTS: u32 = 1;
+
+pub type Classify<const W: u32, const H: u32, Frame> = RknnModel<W, H, Frame>;
+pub type ClassifyNhwc<const W: u32, const H: u32> =
+  Classify<W, H, crate::frame::RgbNhwcFrame<W, H>>;
+
+#[derive(Error, Debug)]
+pub enum ClassifyError {
+  #[error("RKNN 模型错误: {0}")]
+  RknnModelError(#[from] RknnModelError),
+  #[error("类别过滤配置错误: {0}")]
+  ClassFilterError(#[from] ClassFilterError),
+  #[error("模型路径错误: {0}")]
+  ModelPathError(String),
+  #[error("模型输出不匹配: {0}")]
+  OutputMismatch(String),
+}
+
+pub struct ClassifyPostprocess<T> {
+  top_k: usize,
+  softmax: bool,
+  classes: ClassRules,
+  /// 类别数量，构建时确定
+  num_classes: usize,
+  _phantom: std::marker::PhantomData<T>,
+}
+
+pub struct ClassifyBuilder {
+  model_path: String,
+  flags: InitFlags,
+  top_k: usize,
+  min_score: f32,
+  softmax: bool,
+  classes: ClassFilter,
+  num_labels: Option<usize>,
+}
+
+impl FromUrlWithScheme for ClassifyBuilder {
+  const SCHEME: &'static str = "classify";
+}
+
+impl Default for ClassifyBuilder {
+  fn default() -> Self {
+    Self {
+      model_path: String::new(),
+      flags: InitFlags::default(),
+      top_k: CLASSIFY_TOP_K,
+      min_score: CLASSIFY_MIN_SCORE,
+      softmax: true,
+      classes: ClassFilter::default(),
+      num_labels: None,
+    }
+  }
+}
+
+impl FromUrl for ClassifyBuilder {
+  type Error = ClassifyError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    if url.scheme() != Self::SCHEME {
+      return Err(ClassifyError::ModelPathError(format!(
+        "模型路径必须使用 {} 方案",
+        Self::SCHEME
+      )));
+    }
+
+    let mut builder = ClassifyBuilder::default()
+      .model_path(url.path().to_string())
+      .classes(ClassFilter::from_url(url)?);
+    for (k, v) in url.query_pairs() {
+      match k.as_ref() {
+        "top_k" => builder.top_k = v.parse().unwrap_or(CLASSIFY_TOP_K),
+        "min_score" => builder.min_score = v.parse().unwrap_or(CLASSIFY_MIN_SCORE),
+        "softmax" => builder.softmax = v.parse().unwrap_or(true),
+        _ => {}
+      }
+    }
+
+    Ok(builder)
+  }
+}
+
+impl ClassifyBuilder {
+  pub fn flags(mut self, flags: InitFlags) -> Self {
+    self.flags = flags;
+    self
+  }
+
+  pub fn model_path(mut self, model_path: String) -> Self {
+    self.model_path = model_path;
+    self
+  }
+
+  pub fn top_k(mut self, top_k: usize) -> Self {
+    self.top_k = top_k;
+    self
+  }
+
+  pub fn min_score(mut self, min_score: f32) -> Self {
+    self.min_score = min_score;
+    self
+  }
+
+  pub fn softmax(mut self, softmax: bool) -> Self {
+    self.softmax = softmax;
+    self
+  }
+
+  pub fn classes(mut self, classes: ClassFilter) -> Self {
+    self.classes = classes;
+    self
+  }
+
+  /// 类别数量，构建模型时检查输出长度，0 表示不检查
+  pub fn labels(mut self, num_labels: usize) -> Self {
+    self.num_labels = (num_labels > 0).then_some(num_labels);
+    self
+  }
+
+  pub fn build_postprocess<T: WithLabel>(&self) -> Result<ClassifyPostprocess<T>, ClassifyError> {
+    Ok(ClassifyPostprocess {
+      top_k: self.top_k,
+      softmax: self.softmax,
+      classes: self.classes.resolve::<T>(self.min_score)?,
+      num_classes: label_num::<T>(),
+      _phantom: std::marker::PhantomData,
+    })
+  }
+
+  pub fn build_model<const W: u32, const H: u32, Frame>(
+    self,
+  ) -> Result<Classify<W, H, Frame>, ClassifyError> {
+    let model = RknnModel::load(&self.model_path, self.flags, Some(CLASSIFY_NUM_OUTPUTS))?;
+    if let Some(num_labels) = self.num_labels {
+      model
+        .info()
+        .check_labels(0, num_labels)
+        .map_err(RknnModelError::from)?;
+    }
+    Ok(model)
+  }
+}
+
+impl<T: WithLabel> Postprocess for ClassifyPostprocess<T> {
+  type Input = Tensors;
+  type Output = ClassifyResult<T>;
+  type Error = ClassifyError;
+
+  fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+    debug!("后处理分类模型输出");
+    let num_classes = self.num_classes;
+    let logits = match output.as_slice() {
+      [tensor] if tensor.len() == num_classes => tensor.to_f32(),
+      _ => {
+        return Err(ClassifyError::OutputMismatch(format!(
+          "预期一个大小为 {} 的输出, 实际输出大小为 {:?}",
+          num_classes,
+          output.iter().map(|t| t.len()).collect::<Vec<_>>()
+        )));
+      }
+    };
+
+    let scores: Vec<f32> = if self.softmax {
+      let max = logits.iter().copied().fold(f32::MIN, f32::max);
+      let exp: Vec<f32> = logits.iter().map(|x| (x - max).exp()).collect();
+      let sum: f32 = exp.iter().sum();
+      exp.into_iter().map(|e| e / sum).collect()
+    } else {
+      logits.into_owned()
+    };
+
+    let mut items: Vec<ClassifyItem<T>> = scores
+      .iter()
+      .enumerate()
+      .filter(|(id, score)| self.classes.accept(*id as u32, **score))
+      .map(|(id, score)| ClassifyItem {
+        kind: T::from_label_id(id as u32),
+        score: *score,
+      })
+      .collect();
+    items.sort_by(|a, b| b.score.total_cmp(&a.score));
+    items.truncate(self.top_k);
+
+    Ok(ClassifyResult {
+      items: items.into_boxed_slice(),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use shanan_trait::{Postprocess, WithLabel};
+
+  use super::{ClassifyBuilder, ClassifyError};
+  use crate::model::{ClassFilter, ClassifyResult, Tensor};
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct TestLabel(u32);
+
+  impl WithLabel for TestLabel {
+    const LABEL_NUM: u32 = 4;
+
+    fn from_label_id(id: u32) -> Self {
+      Self(id)
+    }
+
+    fn to_label_id(&self) -> u32 {
+      self.0
+    }
+
+    fn to_label_str(&self) -> String {
+      format!("class{}", self.0)
+    }
+  }
+
+  fn kinds(result: &ClassifyResult<TestLabel>) -> Vec<u32> {
+    result.items.iter().map(|item| item.kind.0).collect()
+  }
+
+  #[test]
+  fn softmax_top_k() {
+    let post = ClassifyBuilder::default()
+      .top_k(2)
+      .build_postprocess::<TestLabel>()
+      .unwrap();
+    let logits = [1.0f32, 3.0, 2.0, 0.0];
+    let result = post
+      .process(vec![Tensor::from_f32(vec![1, 4], logits.to_vec())])
+      .unwrap();
+    assert_eq!(kinds(&result), [1, 2]);
+
+    let sum: f32 = logits.iter().map(|x| x.exp()).sum();
+    assert!((result.items[0].score - 3.0f32.exp() / sum).abs() < 1e-6);
+    assert!((result.items[1].score - 2.0f32.exp() / sum).abs() < 1e-6);
+  }
+
+  #[test]
+  fn filters_by_min_score_and_class() {
+    let post = ClassifyBuilder::default()
+      .softmax(false)
+      .min_score(0.25)
+      .classes(ClassFilter::default().exclude("class1"))
+      .build_postprocess::<TestLabel>()
+      .unwrap();
+    let result = post
+      .process(vec![Tensor::from_f32(vec![4], vec![0.1, 0.5, 0.3, 0.1])])
+      .unwrap();
+    assert_eq!(kinds(&result), [2]);
+    assert_eq!(result.items[0].score, 0.3);
+  }
+
+  #[test]
+  fn rejects_mismatched_output() {
+    let post = ClassifyBuilder::default()
+      .build_postprocess::<TestLabel>()
+      .unwrap();
+    assert!(matches!(
+      post.process(vec![Tensor::from_f32(vec![3], vec![0.0; 3])]),
+      Err(ClassifyError::OutputMismatch(_))
+    ));
+  }
+}
@@ -0,0 +1,269 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/info.rs - 模型输入输出信息
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 模型输入输出信息
+//!
+//! [`ModelInfo`] 记录模型输入输出张量的形状、布局、数据类型与量化参数，加载模型时用于检查
+//! 模型与流水线的输入大小、类别数量是否一致，避免导出尺寸不同的模型静默地输出错误结果。
+
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::model::Quantization;
+
+#[derive(Error, Debug)]
+pub enum ModelInfoError {
+  #[error("模型没有输入")]
+  NoInput,
+  #[error("无法识别输入张量 {name} 的形状 {shape:?}，布局为 {layout}")]
+  UnknownInputShape {
+    name: String,
+    shape: Vec<usize>,
+    layout: TensorLayout,
+  },
+  #[error("模型输入大小为 {actual_w}×{actual_h}，流水线期望 {expected_w}×{expected_h}")]
+  InputSizeMismatch {
+    expected_w: usize,
+    expected_h: usize,
+    actual_w: usize,
+    actual_h: usize,
+  },
+  #[error(
+    "模型输出 {index}（{name}，形状 {shape:?}）对应 {actual} 个类别，类别表有 {expected} 个类别"
+  )]
+  LabelNumMismatch {
+    index: usize,
+    name: String,
+    shape: Vec<usize>,
+    expected: usize,
+    actual: usize,
+  },
+}
+
+/// 张量布局
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TensorLayout {
+  Nchw,
+  Nhwc,
+  Undefined,
+}
+
+impl fmt::Display for TensorLayout {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      TensorLayout::Nchw => write!(f, "NCHW"),
+      TensorLayout::Nhwc => write!(f, "NHWC"),
+      TensorLayout::Undefined => write!(f, "未定义"),
+    }
+  }
+}
+
+/// 单个输入或输出张量的属性
+#[derive(Debug, Clone)]
+pub struct TensorInfo {
+  pub index: u32,
+  pub name: String,
+  pub shape: Vec<usize>,
+  pub layout: TensorLayout,
+  /// 后端报告的数据类型名称
+  pub dtype: String,
+  pub quantization: Option<Quantization>,
+}
+
+impl TensorInfo {
+  /// 四维张量的宽与高
+  pub fn size(&self) -> Option<(usize, usize)> {
+    match (self.layout, self.shape.as_slice()) {
+      (TensorLayout::Nhwc, [_, h, w, _]) => Some((*w, *h)),
+      (TensorLayout::Nchw, [_, _, h, w]) => Some((*w, *h)),
+      _ => None,
+    }
+  }
+
+  /// 通道数，NHWC 为最后一维，其他布局为第二维
+  pub fn channels(&self) -> Option<usize> {
+    match self.layout {
+      TensorLayout::Nhwc => self.shape.last().copied(),
+      _ => self.shape.get(1).copied(),
+    }
+  }
+}
+
+impl fmt::Display for TensorInfo {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "[{}] {} 形状 {:?} 布局 {} 类型 {}",
+      self.index, self.name, self.shape, self.layout, self.dtype
+    )?;
+    if let Some(q) = &self.quantization {
+      write!(f, " 量化 scale={} zero_point={}", q.scale, q.zero_point)?;
+    }
+    Ok(())
+  }
+}
+
+/// 模型的输入输出信息
+#[derive(Debug, Clone, Default)]
+pub struct ModelInfo {
+  pub api_version: Option<String>,
+  pub driver_version: Option<String>,
+  pub inputs: Vec<TensorInfo>,
+  pub outputs: Vec<TensorInfo>,
+}
+
+impl ModelInfo {
+  /// 检查第一个输入的宽高
+  pub fn check_input_size(&self, width: usize, height: usize) -> Result<(), ModelInfoError> {
+    let input = self.inputs.first().ok_or(ModelInfoError::NoInput)?;
+    let (actual_w, actual_h) = input
+      .size()
+      .ok_or_else(|| ModelInfoError::UnknownInputShape {
+        name: input.name.clone(),
+        shape: input.shape.clone(),
+        layout: input.layout,
+      })?;
+    if (actual_w, actual_h) != (width, height) {
+      return Err(ModelInfoError::InputSizeMismatch {
+        expected_w: width,
+        expected_h: height,
+        actual_w,
+        actual_h,
+      });
+    }
+    Ok(())
+  }
+
+  /// 检查每个输出的通道数是否为 `extra + num_labels`，`extra` 为边框等非类别通道数
+  pub fn check_labels(&self, extra: usize, num_labels: usize) -> Result<(), ModelInfoError> {
+    for index in 0..self.outputs.len() {
+      self.check_output_labels(index, 1, extra, num_labels)?;
+    }
+    Ok(())
+  }
+
+  /// 检查第 `index` 个输出的通道数是否为 `groups × (extra + num_labels)`，例如 YOLOv5
+  /// 每个锚框为一组，输出不存在或通道数未知时不检查
+  pub fn check_output_labels(
+    &self,
+    index: usize,
+    groups: usize,
+    extra: usize,
+    num_labels: usize,
+  ) -> Result<(), ModelInfoError> {
+    let Some(output) = self.outputs.get(index) else {
+      return Ok(());
+    };
+    let Some(channels) = output.channels() else {
+      return Ok(());
+    };
+    if channels != groups * (extra + num_labels) {
+      return Err(ModelInfoError::LabelNumMismatch {
+        index,
+        name: output.name.clone(),
+        shape: output.shape.clone(),
+        expected: num_labels,
+        actual: (channels / groups.max(1)).saturating_sub(extra),
+      });
+    }
+    Ok(())
+  }
+}
+
+impl fmt::Display for ModelInfo {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if let Some(version) = &self.api_version {
+      writeln!(f, "API 版本: {}", version)?;
+    }
+    if let Some(version) = &self.driver_version {
+      writeln!(f, "驱动版本: {}", version)?;
+    }
+    writeln!(f, "输入 ({}):", self.inputs.len())?;
+    for input in &self.inputs {
+      writeln!(f, "  {}", input)?;
+    }
+    writeln!(f, "输出 ({}):", self.outputs.len())?;
+    for output in &self.outputs {
+      writeln!(f, "  {}", output)?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{ModelInfo, ModelInfoError, TensorInfo, TensorLayout};
+
+  fn output(shape: &[usize], layout: TensorLayout) -> TensorInfo {
+    TensorInfo {
+      index: 0,
+      name: "output".to_string(),
+      shape: shape.to_vec(),
+      layout,
+      dtype: "INT8".to_string(),
+      quantization: None,
+    }
+  }
+
+  fn info(outputs: Vec<TensorInfo>) -> ModelInfo {
+    ModelInfo {
+      api_version: None,
+      driver_version: None,
+      inputs: Vec::new(),
+      outputs,
+    }
+  }
+
+  #[test]
+  fn checks_grouped_channels() {
+    // YOLOv5 每个检测头 3 个锚框，每个锚框 5 + 80 个通道
+    let info = info(vec![output(&[1, 255, 80, 80], TensorLayout::Nchw)]);
+    assert!(info.check_output_labels(0, 3, 5, 80).is_ok());
+    match info.check_output_labels(0, 3, 5, 20) {
+      Err(ModelInfoError::LabelNumMismatch {
+        expected, actual, ..
+      }) => assert_eq!((expected, actual), (20, 80)),
+      other => panic!("预期类别数量不匹配, 实际为 {:?}", other.err()),
+    }
+  }
+
+  #[test]
+  fn skips_unknown_outputs() {
+    let info = info(vec![
+      output(&[1, 84, 8400], TensorLayout::Undefined),
+      output(&[], TensorLayout::Nhwc),
+    ]);
+    assert!(info.check_output_labels(0, 1, 4, 80).is_ok());
+    assert!(info.check_output_labels(0, 1, 4, 20).is_err());
+    // 通道数未知或输出不存在
+    assert!(info.check_output_labels(1, 1, 4, 20).is_ok());
+    assert!(info.check_output_labels(2, 1, 4, 20).is_ok());
+  }
+
+  #[test]
+  fn checks_non_square_input_size() {
+    let mut info = info(Vec::new());
+    info.inputs = vec![output(&[1, 384, 640, 3], TensorLayout::Nhwc)];
+    assert!(info.check_input_size(640, 384).is_ok());
+    match info.check_input_size(384, 640) {
+      Err(ModelInfoError::InputSizeMismatch {
+        actual_w, actual_h, ..
+      }) => assert_eq!((actual_w, actual_h), (640, 384)),
+      other => panic!("预期输入尺寸不匹配, 实际为 {:?}", other.err()),
+    }
+
+    info.inputs = vec![output(&[1, 3, 384, 640], TensorLayout::Nchw)];
+    assert!(info.check_input_size(640, 384).is_ok());
+  }
+}
@@ -0,0 +1,446 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/label_map.rs - 类别映射与归并
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 类别映射与归并
+//!
+//! [`LabelMap`] 将检测结果的类别改写为另一组类别：多个类别可以归并为一个（例如把
+//! car、truck、bus、motorcycle 归为 vehicle），也可以只改名，未映射的类别被丢弃。
+//! 映射后的类别为 [`MappedLabel`]，编号为分组在文件中的顺序，名称由 [`Remap`] 附在每个
+//! 类别上，不同的映射互不影响。
+//!
+//! 映射作为后处理的最后一步，通过 [`LabelMap::apply`] 包装原有的后处理。未配置映射时
+//! 保留所有类别与名称不变。
+//!
+//! ## 文件格式
+//!
+//! ```toml
+//! [[group]]
+//! name = "vehicle"
+//! classes = ["car", "truck", "bus", "motorcycle"]
+//!
+//! [[group]]
+//! name = "pedestrian"
+//! classes = ["person"]
+//! ```
+//!
+//! 源类别使用名称或编号指定，同一源类别不能属于多个分组。
+//!
+//! ## URL 参数
+//!
+//! - `label_map`: 映射文件路径，在模型 URL 上指定
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use shanan_trait::Postprocess;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+  FromUrl,
+  model::{DetectItem, DetectResult, WithLabel, label_num},
+};
+
+#[derive(Error, Debug)]
+pub enum LabelMapError {
+  #[error("映射文件读取错误: {0}")]
+  IoError(#[from] std::io::Error),
+  #[error("映射文件解析错误: {0}")]
+  TomlError(#[from] toml::de::Error),
+  #[error("无效的映射文件: {0}")]
+  InvalidFile(String),
+  #[error("未知的类别: {0}")]
+  UnknownClass(String),
+}
+
+/// 映射后的类别，携带 [`Remap`] 的类别名称表
+///
+/// 类别数量在运行时确定，不能作为模型输出的类别。通过 [`WithLabel::from_label_id`]
+/// 构造的类别没有名称表，以编号作为名称。
+#[derive(Debug, Clone)]
+pub struct MappedLabel {
+  id: u32,
+  names: Option<Arc<[String]>>,
+}
+
+impl PartialEq for MappedLabel {
+  fn eq(&self, other: &Self) -> bool {
+    self.id == other.id
+  }
+}
+
+impl Eq for MappedLabel {}
+
+impl Hash for MappedLabel {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.id.hash(state);
+  }
+}
+
+impl WithLabel for MappedLabel {
+  const LABEL_NUM: u32 = 0;
+
+  fn from_label_id(id: u32) -> Self {
+    Self { id, names: None }
+  }
+
+  fn to_label_id(&self) -> u32 {
+    self.id
+  }
+
+  fn to_label_str(&self) -> String {
+    self
+      .names
+      .as_ref()
+      .and_then(|names| names.get(self.id as usize).cloned())
+      .unwrap_or_else(|| self.id.to_string())
+  }
+}
+
+/// 按名称指定的类别映射
+#[derive(Debug, Clone, Default)]
+pub struct LabelMap {
+  groups: Vec<(String, Vec<String>)>,
+}
+
+impl FromUrl for LabelMap {
+  type Error = LabelMapError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    match url.query_pairs().find(|(k, _)| k == "label_map") {
+      Some((_, path)) => Self::load(path.as_ref()),
+      None => Ok(Self::default()),
+    }
+  }
+}
+
+impl LabelMap {
+  /// 添加一个分组，`classes` 中的源类别映射为 `name`
+  pub fn group(mut self, name: &str, classes: &[&str]) -> Self {
+    self.groups.push((
+      name.to_string(),
+      classes.iter().map(|c| c.to_string()).collect(),
+    ));
+    self
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    !self.groups.is_empty()
+  }
+
+  /// 从 TOML 文件加载
+  pub fn load(path: &str) -> Result<Self, LabelMapError> {
+    let table = std::fs::read_to_string(path)?.parse::<toml::Table>()?;
+    let groups = table
+      .get("group")
+      .and_then(|v| v.as_array())
+      .ok_or_else(|| LabelMapError::InvalidFile(format!("{}: 缺少 [[group]]", path)))?;
+
+    let mut map = LabelMap::default();
+    for group in groups {
+      let name = group.get("name").and_then(|v| v.as_str());
+      let classes = group
+        .get("classes")
+        .and_then(|v| v.as_array())
+        .and_then(|a| {
+          a.iter()
+            .map(|v| v.as_str().map(String::from))
+            .collect::<Option<Vec<_>>>()
+        });
+      let (Some(name), Some(classes)) = (name, classes) else {
+        return Err(LabelMapError::InvalidFile(format!(
+          "{}: 分组必须包含 name 字符串与 classes 字符串数组",
+          path
+        )));
+      };
+      map.groups.push((name.to_string(), classes));
+    }
+    Ok(map)
+  }
+
+  /// 为后处理添加类别映射
+  ///
+  /// 源类别的数量与名称取自 `S`。对已映射的结果再次映射时使用 [`Remap::remap`]，
+  /// [`MappedLabel`] 的类别表在 [`Remap`] 上，而不是全局类别表。
+  pub fn apply<S: WithLabel, P>(&self, post: P) -> Result<Remap<P>, LabelMapError> {
+    let sources: Vec<String> = (0..label_num::<S>() as u32)
+      .map(|id| S::from_label_id(id).to_label_str())
+      .collect();
+    self.apply_names(sources.into(), post)
+  }
+
+  /// 以 `sources` 为源类别名称表添加类别映射，下标为源类别编号
+  fn apply_names<P>(&self, sources: Arc<[String]>, post: P) -> Result<Remap<P>, LabelMapError> {
+    let num = sources.len();
+    if !self.is_enabled() {
+      return Ok(Remap {
+        post,
+        table: (0..num as u32).map(Some).collect(),
+        names: sources,
+      });
+    }
+
+    let names: HashMap<&str, usize> = sources
+      .iter()
+      .enumerate()
+      .map(|(id, name)| (name.as_str(), id))
+      .collect();
+    let lookup = |name: &str| -> Result<usize, LabelMapError> {
+      names
+        .get(name)
+        .copied()
+        .or_else(|| name.parse::<usize>().ok().filter(|id| *id < num))
+        .ok_or_else(|| LabelMapError::UnknownClass(name.to_string()))
+    };
+
+    let mut table = vec![None; num];
+    for (target, (name, classes)) in self.groups.iter().enumerate() {
+      if classes.is_empty() {
+        return Err(LabelMapError::InvalidFile(format!("分组 {} 为空", name)));
+      }
+      for class in classes {
+        let source = lookup(class)?;
+        if table[source].is_some() {
+          return Err(LabelMapError::InvalidFile(format!(
+            "类别 {} 属于多个分组",
+            class
+          )));
+        }
+        table[source] = Some(target as u32);
+      }
+    }
+
+    Ok(Remap {
+      post,
+      table,
+      names: self.groups.iter().map(|(name, _)| name.clone()).collect(),
+    })
+  }
+}
+
+/// 带类别映射的后处理
+pub struct Remap<P> {
+  post: P,
+  /// 源类别编号到映射后类别编号，`None` 表示丢弃
+  table: Vec<Option<u32>>,
+  /// 映射后的类别名称
+  names: Arc<[String]>,
+}
+
+impl<P> Remap<P> {
+  /// 映射后的类别名称，下标为类别编号
+  pub fn label_names(&self) -> &[String] {
+    &self.names
+  }
+
+  /// 映射后的类别数量
+  pub fn label_num(&self) -> usize {
+    self.names.len()
+  }
+
+  /// 对映射结果再次映射，源类别取自本映射的类别名称表
+  pub fn remap(self, map: &LabelMap) -> Result<Remap<Self>, LabelMapError> {
+    let names = self.names.clone();
+    map.apply_names(names, self)
+  }
+
+  fn label(&self, id: u32) -> MappedLabel {
+    MappedLabel {
+      id,
+      names: Some(self.names.clone()),
+    }
+  }
+}
+
+impl<P, S> Postprocess for Remap<P>
+where
+  P: Postprocess<Output = DetectResult<S>>,
+  S: WithLabel,
+{
+  type Input = P::Input;
+  type Output = DetectResult<MappedLabel>;
+  type Error = P::Error;
+
+  fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+    let result = self.post.process(output)?;
+    let items: Vec<DetectItem<MappedLabel>> = result
+      .items
+      .into_vec()
+      .into_iter()
+      .filter_map(|item| {
+        let id = self
+          .table
+          .get(item.kind.to_label_id() as usize)
+          .copied()??;
+        Some(DetectItem {
+          mask: item.mask,
+          keypoints: item.keypoints,
+          obb: item.obb,
+          attributes: item.attributes,
+          ..DetectItem::new(self.label(id), item.score, item.bbox)
+        })
+      })
+      .collect();
+
+    Ok(DetectResult {
+      items: items.into_boxed_slice(),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use shanan_trait::{Postprocess, WithLabel};
+
+  use super::LabelMap;
+  use crate::model::{BBox, DetectItem, DetectResult, LabelSet, RuntimeLabel, label_num};
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct TestLabel(u32);
+
+  impl WithLabel for TestLabel {
+    const LABEL_NUM: u32 = 3;
+
+    fn from_label_id(id: u32) -> Self {
+      Self(id)
+    }
+
+    fn to_label_id(&self) -> u32 {
+      self.0
+    }
+
+    fn to_label_str(&self) -> String {
+      ["car", "truck", "person"][self.0 as usize].to_string()
+    }
+  }
+
+  /// 类别数量与映射后的类别数量不同的全局类别表
+  struct WideLabel(u32);
+
+  impl WithLabel for WideLabel {
+    const LABEL_NUM: u32 = 5;
+
+    fn from_label_id(id: u32) -> Self {
+      Self(id)
+    }
+
+    fn to_label_id(&self) -> u32 {
+      self.0
+    }
+
+    fn to_label_str(&self) -> String {
+      format!("wide{}", self.0)
+    }
+  }
+
+  /// 每个类别输出一个检测项
+  struct AllClasses;
+
+  impl Postprocess for AllClasses {
+    type Input = ();
+    type Output = DetectResult<TestLabel>;
+    type Error = std::convert::Infallible;
+
+    fn process(&self, _: Self::Input) -> Result<Self::Output, Self::Error> {
+      let bbox = BBox {
+        x_min: 0.0,
+        y_min: 0.0,
+        x_max: 1.0,
+        y_max: 1.0,
+      };
+      Ok(DetectResult {
+        items: (0..3)
+          .map(|id| DetectItem::new(TestLabel(id), 0.5, bbox))
+          .collect(),
+      })
+    }
+  }
+
+  fn names(map: &LabelMap) -> Vec<(u32, String)> {
+    let remap = map.apply::<TestLabel, _>(AllClasses).unwrap();
+    let result = remap.process(()).unwrap();
+    result
+      .items
+      .iter()
+      .map(|item| (item.kind.to_label_id(), item.kind.to_label_str()))
+      .collect()
+  }
+
+  #[test]
+  fn groups_and_drops_classes() {
+    let map = LabelMap::default().group("vehicle", &["car", "truck"]);
+    assert_eq!(
+      names(&map),
+      [(0, "vehicle".to_string()), (0, "vehicle".to_string())]
+    );
+  }
+
+  #[test]
+  fn maps_keep_their_own_names() {
+    let first = LabelMap::default()
+      .apply::<TestLabel, _>(AllClasses)
+      .unwrap();
+    let vehicles = LabelMap::default().group("vehicle", &["car", "truck"]);
+    // 之后创建的映射不影响已有映射的名称
+    assert_eq!(names(&vehicles)[0].1, "vehicle");
+    let result = first.process(()).unwrap();
+    assert_eq!(result.items[0].kind.to_label_str(), "car");
+    assert_eq!(result.items[2].kind.to_label_str(), "person");
+  }
+
+  #[test]
+  fn rejects_overlapping_groups() {
+    let map = LabelMap::default()
+      .group("vehicle", &["car"])
+      .group("other", &["car"]);
+    assert!(map.apply::<TestLabel, _>(AllClasses).is_err());
+  }
+
+  #[test]
+  fn remap_counts_from_name_table() {
+    // 全局类别表与映射后的类别数量不同，再次映射的源类别仍取自映射的名称表
+    RuntimeLabel::install(LabelSet::from_label::<WideLabel>());
+    let first = LabelMap::default()
+      .group("vehicle", &["car", "truck"])
+      .group("pedestrian", &["person"])
+      .apply::<TestLabel, _>(AllClasses)
+      .unwrap();
+    assert_eq!(first.label_num(), 2);
+    assert_eq!(label_num::<RuntimeLabel>(), 5);
+
+    let second = first
+      .remap(&LabelMap::default().group("moving", &["vehicle", "1"]))
+      .unwrap();
+    assert_eq!(second.label_names(), ["moving"]);
+    let result = second.process(()).unwrap();
+    assert_eq!(result.items.len(), 3);
+    assert!(
+      result
+        .items
+        .iter()
+        .all(|item| item.kind.to_label_str() == "moving")
+    );
+
+    // 名称表之外的编号不是有效的源类别
+    let first = LabelMap::default()
+      .group("vehicle", &["car"])
+      .apply::<TestLabel, _>(AllClasses)
+      .unwrap();
+    assert!(
+      first
+        .remap(&LabelMap::default().group("x", &["3"]))
+        .is_err()
+    );
+  }
+}
@@ -0,0 +1,172 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/mask.rs - 实例分割掩码
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 实例分割掩码
+//!
+//! [`Mask`] 保存裁剪到检测框范围内的二值掩码，坐标为模型输入图像的像素坐标。
+
+use std::collections::HashMap;
+
+/// 裁剪到检测框范围内的二值掩码
+#[derive(Debug, Clone)]
+pub struct Mask {
+  /// 掩码左上角在图像中的横坐标
+  pub x: u32,
+  /// 掩码左上角在图像中的纵坐标
+  pub y: u32,
+  pub width: u32,
+  pub height: u32,
+  data: Vec<bool>,
+}
+
+impl Mask {
+  pub fn new(x: u32, y: u32, width: u32, height: u32, data: Vec<bool>) -> Self {
+    assert_eq!(data.len(), (width * height) as usize, "掩码数据大小不匹配");
+    Self {
+      x,
+      y,
+      width,
+      height,
+      data,
+    }
+  }
+
+  /// 图像坐标 `(px, py)` 处的像素是否属于该实例
+  pub fn contains(&self, px: u32, py: u32) -> bool {
+    if px < self.x || py < self.y {
+      return false;
+    }
+    let (mx, my) = (px - self.x, py - self.y);
+    mx < self.width && my < self.height && self.data[(my * self.width + mx) as usize]
+  }
+
+  /// 缩放后平移到新的坐标系，`(x, y)` 映射到 `(x * sx + ox, y * sy + oy)`，按最近邻重采样
+  pub fn transform(&self, (ox, oy): (f32, f32), (sx, sy): (f32, f32)) -> Mask {
+    let x = (self.x as f32 * sx + ox).max(0.0).round() as u32;
+    let y = (self.y as f32 * sy + oy).max(0.0).round() as u32;
+    let width = (self.width as f32 * sx).round() as u32;
+    let height = (self.height as f32 * sy).round() as u32;
+    let mut data = Vec::with_capacity((width * height) as usize);
+    for my in 0..height {
+      for mx in 0..width {
+        // 以像素中心映射回原坐标系
+        let px = ((x + mx) as f32 + 0.5 - ox) / sx;
+        let py = ((y + my) as f32 + 0.5 - oy) / sy;
+        data.push(px >= 0.0 && py >= 0.0 && self.contains(px as u32, py as u32));
+      }
+    }
+    Mask::new(x, y, width, height, data)
+  }
+
+  /// 掩码覆盖的像素数量
+  pub fn area(&self) -> usize {
+    self.data.iter().filter(|v| **v).count()
+  }
+
+  /// 掩码轮廓多边形，顶点为图像坐标中的像素角点
+  ///
+  /// 轮廓沿像素边界追踪，围成的面积与 [`Mask::area`] 一致。外轮廓按顺时针方向
+  /// （图像坐标系，y 轴向下）排列，内部空洞按逆时针方向排列。
+  pub fn polygons(&self) -> Vec<Vec<(f32, f32)>> {
+    let (w, h) = (self.width as i32, self.height as i32);
+    let at = |x: i32, y: i32| x >= 0 && y >= 0 && x < w && y < h && self.data[(y * w + x) as usize];
+
+    // 收集前景与背景之间的有向边，前景位于行进方向的右侧
+    let mut edges: Vec<((i32, i32), (i32, i32))> = Vec::new();
+    for y in 0..h {
+      for x in 0..w {
+        if !at(x, y) {
+          continue;
+        }
+        if !at(x, y - 1) {
+          edges.push(((x, y), (x + 1, y)));
+        }
+        if !at(x + 1, y) {
+          edges.push(((x + 1, y), (x + 1, y + 1)));
+        }
+        if !at(x, y + 1) {
+          edges.push(((x + 1, y + 1), (x, y + 1)));
+        }
+        if !at(x - 1, y) {
+          edges.push(((x, y + 1), (x, y)));
+        }
+      }
+    }
+
+    let mut outgoing: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+    for (i, (from, _)) in edges.iter().enumerate() {
+      outgoing.entry(*from).or_default().push(i);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut polygons = Vec::new();
+    for first in 0..edges.len() {
+      if used[first] {
+        continue;
+      }
+
+      let mut points = Vec::new();
+      let mut current = first;
+      loop {
+        used[current] = true;
+        let (from, to) = edges[current];
+        points.push(from);
+
+        // 对角相接的像素处有两条出边，优先右转，使对角像素分属不同的轮廓
+        let dir = (to.0 - from.0, to.1 - from.1);
+        let next = outgoing.get(&to).and_then(|candidates| {
+          let turn = |i: &&usize| {
+            let (a, b) = edges[**i];
+            let d = (b.0 - a.0, b.1 - a.1);
+            if d == (-dir.1, dir.0) {
+              0
+            } else if d == dir {
+              1
+            } else {
+              2
+            }
+          };
+          candidates
+            .iter()
+            .filter(|i| !used[**i])
+            .min_by_key(turn)
+            .copied()
+        });
+        match next {
+          Some(next) => current = next,
+          None => break,
+        }
+      }
+
+      polygons.push(simplify(&points, (self.x as i32, self.y as i32)));
+    }
+    polygons
+  }
+}
+
+/// 去除共线顶点并转换为图像坐标
+fn simplify(points: &[(i32, i32)], offset: (i32, i32)) -> Vec<(f32, f32)> {
+  let n = points.len();
+  (0..n)
+    .filter(|&i| {
+      let (a, b, c) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+      (b.0 - a.0) * (c.1 - b.1) != (b.1 - a.1) * (c.0 - b.0)
+    })
+    .map(|i| {
+      (
+        (points[i].0 + offset.0) as f32,
+        (points[i].1 + offset.1) as f32,
+      )
+    })
+    .collect()
+}
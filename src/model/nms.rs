@@ -0,0 +1,435 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/nms.rs - 非极大值抑制
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 非极大值抑制
+//!
+//! [`Nms`] 作为后处理的最后一步去除重复框，参数从模型 URL 的查询参数中读取：
+//!
+//! - `nms`: `class` 按类别抑制，`agnostic` 不区分类别，`none` 关闭
+//! - `iou_thresh`: 交并比阈值，默认 0.45
+//! - `max_det`: 最多保留的检测数量，默认不限制
+//! - `soft_nms`: `linear` 或 `gaussian`，启用 Soft-NMS，默认使用硬抑制
+//! - `soft_sigma`: Gaussian Soft-NMS 的 sigma，默认 0.5
+//!
+//! YOLOv8、YOLOv5 等需要 NMS 的检测头默认按类别抑制；Yolo26 等 NMS-free 检测头
+//! 默认不进行抑制，设置 `nms` 参数后启用。带旋转边框的检测使用旋转交并比。
+
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+  FromUrl,
+  model::{DetectItem, DetectResult, WithLabel},
+};
+
+const NMS_IOU_THRESH: f32 = 0.45;
+const NMS_SOFT_SIGMA: f32 = 0.5;
+
+#[derive(Error, Debug)]
+pub enum NmsError {
+  #[error("未知的 NMS 模式: {0}")]
+  InvalidMode(String),
+  #[error("未知的 Soft-NMS 方法: {0}")]
+  InvalidMethod(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NmsMode {
+  /// 不进行抑制
+  Disabled,
+  /// 只在同一类别之间抑制
+  ClassAware,
+  /// 不区分类别
+  Agnostic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NmsMethod {
+  /// 删除重叠框
+  #[default]
+  Hard,
+  /// 按 `1 - IoU` 线性衰减重叠框的分数
+  SoftLinear,
+  /// 按 `exp(-IoU² / sigma)` 衰减重叠框的分数
+  SoftGaussian { sigma: f32 },
+}
+
+#[derive(Debug, Clone)]
+pub struct Nms {
+  mode: Option<NmsMode>,
+  method: NmsMethod,
+  iou_thresh: f32,
+  score_thresh: f32,
+  max_detections: Option<usize>,
+}
+
+impl Default for Nms {
+  fn default() -> Self {
+    Self {
+      mode: None,
+      method: NmsMethod::Hard,
+      iou_thresh: NMS_IOU_THRESH,
+      score_thresh: 0.0,
+      max_detections: None,
+    }
+  }
+}
+
+impl FromUrl for Nms {
+  type Error = NmsError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    let mut nms = Nms::default();
+    let mut soft = None;
+    let mut sigma = NMS_SOFT_SIGMA;
+    for (k, v) in url.query_pairs() {
+      match k.as_ref() {
+        "nms" => {
+          nms.mode = Some(match v.as_ref() {
+            "class" => NmsMode::ClassAware,
+            "agnostic" => NmsMode::Agnostic,
+            "none" => NmsMode::Disabled,
+            other => return Err(NmsError::InvalidMode(other.to_string())),
+          })
+        }
+        "iou_thresh" => nms.iou_thresh = v.parse().unwrap_or(NMS_IOU_THRESH),
+        "max_det" => nms.max_detections = v.parse().ok(),
+        "soft_nms" => soft = Some(v.into_owned()),
+        "soft_sigma" => sigma = v.parse().unwrap_or(NMS_SOFT_SIGMA),
+        _ => continue,
+      }
+    }
+    nms.method = match soft.as_deref() {
+      None | Some("none") => NmsMethod::Hard,
+      Some("linear") => NmsMethod::SoftLinear,
+      Some("gaussian") => NmsMethod::SoftGaussian { sigma },
+      Some(other) => return Err(NmsError::InvalidMethod(other.to_string())),
+    };
+    Ok(nms)
+  }
+}
+
+impl Nms {
+  pub fn mode(mut self, mode: NmsMode) -> Self {
+    self.mode = Some(mode);
+    self
+  }
+
+  /// 未通过 URL 指定模式时使用的模式
+  pub fn default_mode(mut self, mode: NmsMode) -> Self {
+    self.mode.get_or_insert(mode);
+    self
+  }
+
+  pub fn method(mut self, method: NmsMethod) -> Self {
+    self.method = method;
+    self
+  }
+
+  pub fn iou_thresh(mut self, iou_thresh: f32) -> Self {
+    self.iou_thresh = iou_thresh;
+    self
+  }
+
+  /// Soft-NMS 衰减后低于该分数的框会被删除
+  pub fn score_thresh(mut self, score_thresh: f32) -> Self {
+    self.score_thresh = score_thresh;
+    self
+  }
+
+  pub fn max_detections(mut self, max_detections: usize) -> Self {
+    self.max_detections = Some(max_detections);
+    self
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    !matches!(self.mode, None | Some(NmsMode::Disabled))
+  }
+
+  pub fn apply<T: WithLabel>(&self, result: DetectResult<T>) -> DetectResult<T> {
+    if !self.is_enabled() {
+      return result;
+    }
+    DetectResult {
+      items: self.suppress(result.items.into_vec()).into_boxed_slice(),
+    }
+  }
+
+  /// 对检测结果进行抑制，结果按分数从高到低排列
+  pub fn suppress<T: WithLabel>(&self, items: Vec<DetectItem<T>>) -> Vec<DetectItem<T>> {
+    if !self.is_enabled() {
+      return items;
+    }
+    let keep = self.keep(&items);
+    let mut items: Vec<Option<DetectItem<T>>> = items.into_iter().map(Some).collect();
+    keep
+      .into_iter()
+      .filter_map(|(i, score)| {
+        let mut item = items[i].take()?;
+        item.score = score;
+        Some(item)
+      })
+      .collect()
+  }
+
+  /// 对附带额外数据（如掩码系数、关键点）的检测进行抑制，数据随检测一起保留
+  pub fn suppress_with<T: WithLabel, A>(
+    &self,
+    items: Vec<(DetectItem<T>, A)>,
+  ) -> Vec<(DetectItem<T>, A)> {
+    let (items, extra): (Vec<_>, Vec<_>) = items.into_iter().unzip();
+    let keep = self.keep(&items);
+    let mut pairs: Vec<Option<(DetectItem<T>, A)>> =
+      items.into_iter().zip(extra).map(Some).collect();
+    keep
+      .into_iter()
+      .filter_map(|(i, score)| {
+        let (mut item, extra) = pairs[i].take()?;
+        item.score = score;
+        Some((item, extra))
+      })
+      .collect()
+  }
+
+  /// 返回保留的检测下标及其（Soft-NMS 衰减后的）分数，按分数从高到低排列
+  fn keep<T: WithLabel>(&self, items: &[DetectItem<T>]) -> Vec<(usize, f32)> {
+    let agnostic = match self.mode {
+      None | Some(NmsMode::Disabled) => {
+        return items.iter().map(|item| item.score).enumerate().collect();
+      }
+      Some(NmsMode::ClassAware) => false,
+      Some(NmsMode::Agnostic) => true,
+    };
+    let max_detections = self.max_detections.unwrap_or(usize::MAX);
+    let overlaps = |a: usize, b: usize| {
+      let (a, b) = (&items[a], &items[b]);
+      if !agnostic && a.kind.to_label_id() != b.kind.to_label_id() {
+        return 0.0;
+      }
+      // 旋转检测使用旋转交并比
+      match (&a.obb, &b.obb) {
+        (Some(a), Some(b)) => a.iou(b),
+        _ => a.bbox.iou(&b.bbox),
+      }
+    };
+
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    let mut keep: Vec<(usize, f32)> = Vec::with_capacity(items.len().min(max_detections));
+    if self.method == NmsMethod::Hard {
+      order.sort_by(|a, b| items[*b].score.total_cmp(&items[*a].score));
+      for i in order {
+        if keep.len() >= max_detections {
+          break;
+        }
+        if !keep.iter().any(|(k, _)| overlaps(*k, i) > self.iou_thresh) {
+          keep.push((i, items[i].score));
+        }
+      }
+      return keep;
+    }
+
+    // Soft-NMS：每次取出分数最高的框，衰减与其重叠的框
+    let mut scores: Vec<f32> = items.iter().map(|item| item.score).collect();
+    while !order.is_empty() && keep.len() < max_detections {
+      let best = order
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| scores[**a].total_cmp(&scores[**b]))
+        .map(|(pos, _)| pos)
+        .unwrap_or(0);
+      let best = order.swap_remove(best);
+      for &i in order.iter() {
+        let iou = overlaps(best, i);
+        scores[i] *= match self.method {
+          NmsMethod::SoftLinear if iou > self.iou_thresh => 1.0 - iou,
+          NmsMethod::SoftGaussian { sigma } => (-(iou * iou) / sigma).exp(),
+          _ => 1.0,
+        };
+      }
+      order.retain(|i| scores[*i] >= self.score_thresh);
+      keep.push((best, scores[best]));
+    }
+    keep
+  }
+}
+
+/// 按类别进行贪心非极大值抑制，结果按置信度从高到低排列
+pub fn nms<T: WithLabel>(items: Vec<DetectItem<T>>, iou_thresh: f32) -> Vec<DetectItem<T>> {
+  Nms::default()
+    .mode(NmsMode::ClassAware)
+    .iou_thresh(iou_thresh)
+    .suppress(items)
+}
+
+#[cfg(test)]
+mod tests {
+  use shanan_trait::WithLabel;
+  use url::Url;
+
+  use super::{Nms, NmsMethod, NmsMode};
+  use crate::{
+    FromUrl,
+    model::{BBox, DetectItem},
+  };
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct TestLabel(u32);
+
+  impl WithLabel for TestLabel {
+    const LABEL_NUM: u32 = 2;
+
+    fn from_label_id(id: u32) -> Self {
+      Self(id)
+    }
+
+    fn to_label_id(&self) -> u32 {
+      self.0
+    }
+
+    fn to_label_str(&self) -> String {
+      format!("class{}", self.0)
+    }
+  }
+
+  fn item(class: u32, score: f32, [x_min, y_min, x_max, y_max]: [f32; 4]) -> DetectItem<TestLabel> {
+    DetectItem::new(
+      TestLabel(class),
+      score,
+      BBox {
+        x_min,
+        y_min,
+        x_max,
+        y_max,
+      },
+    )
+  }
+
+  fn scores(items: &[DetectItem<TestLabel>]) -> Vec<f32> {
+    items.iter().map(|item| item.score).collect()
+  }
+
+  // 与 A 的交并比：B 为 50 / 150 = 1/3，C 为 50 / 100 = 0.5
+  const A: [f32; 4] = [0.0, 0.0, 10.0, 10.0];
+  const B: [f32; 4] = [5.0, 0.0, 15.0, 10.0];
+  const C: [f32; 4] = [0.0, 0.0, 10.0, 5.0];
+
+  #[test]
+  fn hand_computed_iou() {
+    let a = item(0, 1.0, A).bbox;
+    assert!((a.iou(&item(0, 1.0, B).bbox) - 1.0 / 3.0).abs() < 1e-6);
+    assert_eq!(a.iou(&item(0, 1.0, C).bbox), 0.5);
+  }
+
+  #[test]
+  fn class_aware_and_agnostic() {
+    let items = vec![item(0, 0.9, A), item(0, 0.8, C), item(1, 0.7, C)];
+
+    // 按类别抑制时只删除同类别的 C
+    let kept = Nms::default()
+      .mode(NmsMode::ClassAware)
+      .suppress(items.clone());
+    assert_eq!(scores(&kept), [0.9, 0.7]);
+
+    // 不区分类别时两个 C 都被删除
+    let kept = Nms::default()
+      .mode(NmsMode::Agnostic)
+      .suppress(items.clone());
+    assert_eq!(scores(&kept), [0.9]);
+
+    // 交并比不超过阈值时保留，两个完全重叠的 C 只保留一个
+    let kept = Nms::default()
+      .mode(NmsMode::Agnostic)
+      .iou_thresh(0.5)
+      .suppress(items.clone());
+    assert_eq!(scores(&kept), [0.9, 0.8]);
+
+    // 关闭时原样返回
+    let kept = Nms::default().mode(NmsMode::Disabled).suppress(items);
+    assert_eq!(scores(&kept), [0.9, 0.8, 0.7]);
+  }
+
+  #[test]
+  fn soft_nms_decays_overlaps() {
+    let items = vec![item(0, 0.8, B), item(0, 0.9, A)];
+
+    // 线性衰减：0.8 * (1 - 1/3)
+    let kept = Nms::default()
+      .mode(NmsMode::ClassAware)
+      .method(NmsMethod::SoftLinear)
+      .iou_thresh(0.3)
+      .suppress(items.clone());
+    assert_eq!(kept[0].score, 0.9);
+    assert!((kept[1].score - 0.8 * (2.0 / 3.0)).abs() < 1e-6);
+
+    // 交并比未超过阈值时线性衰减不生效
+    let kept = Nms::default()
+      .mode(NmsMode::ClassAware)
+      .method(NmsMethod::SoftLinear)
+      .suppress(items.clone());
+    assert_eq!(scores(&kept), [0.9, 0.8]);
+
+    // 高斯衰减：0.8 * exp(-(1/3)^2 / 0.5)
+    let kept = Nms::default()
+      .mode(NmsMode::ClassAware)
+      .method(NmsMethod::SoftGaussian { sigma: 0.5 })
+      .suppress(items);
+    let expected = 0.8 * (-(1.0f32 / 9.0) / 0.5).exp();
+    assert!((kept[1].score - expected).abs() < 1e-6);
+  }
+
+  #[test]
+  fn score_floor_drops_decayed_items() {
+    let items = vec![item(0, 0.9, A), item(0, 0.8, B), item(1, 0.5, C)];
+    // B 衰减到约 0.533，低于 0.6 被删除；不同类别的 C 不衰减，但本身低于 0.6 也被删除
+    let kept = Nms::default()
+      .mode(NmsMode::ClassAware)
+      .method(NmsMethod::SoftLinear)
+      .iou_thresh(0.3)
+      .score_thresh(0.6)
+      .suppress(items);
+    assert_eq!(scores(&kept), [0.9]);
+  }
+
+  #[test]
+  fn max_detections_keeps_highest_scores() {
+    let items = vec![
+      item(0, 0.5, [0.0, 0.0, 1.0, 1.0]),
+      item(0, 0.9, [10.0, 0.0, 11.0, 1.0]),
+      item(1, 0.7, [20.0, 0.0, 21.0, 1.0]),
+    ];
+    for method in [NmsMethod::Hard, NmsMethod::SoftLinear] {
+      let kept = Nms::default()
+        .mode(NmsMode::ClassAware)
+        .method(method)
+        .max_detections(2)
+        .suppress(items.clone());
+      assert_eq!(scores(&kept), [0.9, 0.7]);
+    }
+  }
+
+  #[test]
+  fn parses_url() {
+    let url =
+      Url::parse("yolov8:///m.rknn?nms=agnostic&soft_nms=gaussian&soft_sigma=0.3&max_det=5")
+        .unwrap();
+    let nms = Nms::from_url(&url)
+      .unwrap()
+      .default_mode(NmsMode::ClassAware);
+    assert_eq!(nms.mode, Some(NmsMode::Agnostic));
+    assert_eq!(nms.method, NmsMethod::SoftGaussian { sigma: 0.3 });
+    assert_eq!(nms.max_detections, Some(5));
+
+    let url = Url::parse("yolov8:///m.rknn?soft_nms=cubic").unwrap();
+    assert!(Nms::from_url(&url).is_err());
+  }
+}
@@ -0,0 +1,131 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/obb.rs - 旋转边框
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 旋转边框
+//!
+//! [`RotatedBox`] 以中心、宽高与旋转角表示有方向的边框，坐标为模型输入图像的像素坐标。
+//! 检测结果中的 [`BBox`] 为旋转边框的外接矩形。
+
+use crate::model::BBox;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RotatedBox {
+  pub cx: f32,
+  pub cy: f32,
+  pub width: f32,
+  pub height: f32,
+  /// 旋转角（弧度），图像坐标系中顺时针为正
+  pub angle: f32,
+}
+
+impl RotatedBox {
+  pub fn area(&self) -> f32 {
+    self.width.max(0.0) * self.height.max(0.0)
+  }
+
+  /// 四个角点，按顺时针排列
+  pub fn corners(&self) -> [(f32, f32); 4] {
+    let (sin, cos) = self.angle.sin_cos();
+    let (hw, hh) = (self.width / 2.0, self.height / 2.0);
+    [(-hw, -hh), (hw, -hh), (hw, hh), (-hw, hh)]
+      .map(|(x, y)| (self.cx + x * cos - y * sin, self.cy + x * sin + y * cos))
+  }
+
+  /// 外接矩形
+  pub fn bounds(&self) -> BBox {
+    let corners = self.corners();
+    let fold = |f: fn(f32, f32) -> f32, init: f32, pick: fn(&(f32, f32)) -> f32| {
+      corners.iter().map(pick).fold(init, f)
+    };
+    BBox {
+      x_min: fold(f32::min, f32::MAX, |p| p.0),
+      y_min: fold(f32::min, f32::MAX, |p| p.1),
+      x_max: fold(f32::max, f32::MIN, |p| p.0),
+      y_max: fold(f32::max, f32::MIN, |p| p.1),
+    }
+  }
+
+  /// 旋转交并比，交集通过凸多边形裁剪计算
+  pub fn iou(&self, other: &RotatedBox) -> f32 {
+    let inter = polygon_area(&clip(&self.corners(), &other.corners()));
+    let union = self.area() + other.area() - inter;
+    if union <= 0.0 { 0.0 } else { inter / union }
+  }
+}
+
+/// Sutherland–Hodgman 算法：用凸多边形 `clipper` 裁剪 `subject`，两者顶点同向排列
+fn clip(subject: &[(f32, f32)], clipper: &[(f32, f32)]) -> Vec<(f32, f32)> {
+  let mut output = subject.to_vec();
+  for i in 0..clipper.len() {
+    if output.is_empty() {
+      break;
+    }
+    let (a, b) = (clipper[i], clipper[(i + 1) % clipper.len()]);
+    // 顺时针排列（y 轴向下）时，内侧位于边的右侧
+    let side = |p: (f32, f32)| (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0);
+    let input = std::mem::take(&mut output);
+    for j in 0..input.len() {
+      let (p, q) = (input[j], input[(j + 1) % input.len()]);
+      let (sp, sq) = (side(p), side(q));
+      if sp >= 0.0 {
+        output.push(p);
+      }
+      if (sp >= 0.0) != (sq >= 0.0) {
+        let t = sp / (sp - sq);
+        output.push((p.0 + (q.0 - p.0) * t, p.1 + (q.1 - p.1) * t));
+      }
+    }
+  }
+  output
+}
+
+/// 鞋带公式计算多边形面积
+fn polygon_area(points: &[(f32, f32)]) -> f32 {
+  let n = points.len();
+  let twice: f32 = (0..n)
+    .map(|i| {
+      let (p, q) = (points[i], points[(i + 1) % n]);
+      p.0 * q.1 - q.0 * p.1
+    })
+    .sum();
+  twice.abs() / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+  use std::f32::consts::FRAC_PI_4;
+
+  use super::RotatedBox;
+
+  fn rotated(cx: f32, angle: f32) -> RotatedBox {
+    RotatedBox {
+      cx,
+      cy: 32.0,
+      width: 40.0,
+      height: 8.0,
+      angle,
+    }
+  }
+
+  #[test]
+  fn rotated_iou() {
+    let a = rotated(32.0, FRAC_PI_4);
+    assert!((a.iou(&a) - 1.0).abs() < 1e-5);
+    // 十字交叉：交集为 8x8，并集为 320 + 320 - 64
+    assert!((a.iou(&rotated(32.0, -FRAC_PI_4)) - 64.0 / 576.0).abs() < 1e-5);
+    // 外接矩形相同，轴对齐交并比为 1
+    assert_eq!(a.bounds().iou(&rotated(32.0, -FRAC_PI_4).bounds()), 1.0);
+    // 不相交
+    assert_eq!(a.iou(&rotated(100.0, FRAC_PI_4)), 0.0);
+  }
+}
@@ -0,0 +1,356 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/pool.rs - 模型池
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 模型池
+//!
+//! 单个模型实例（例如一个 RKNN 上下文）只能串行推理。[`ModelPool`] 持有 N 个相互独立的实例，
+//! 例如每个 NPU 核心一个上下文，或 N 个 CPU 后端实例，每次推理取出一个空闲实例，多个线程可以
+//! 同时调用 [`Model::infer`]。
+//!
+//! [`ModelPool::process_ordered`] 在 N 个工作线程上并发推理一个帧序列，并按输入顺序交付结果，
+//! 供连续流处理使用；模型池被其他模型包装时使用 [`infer_ordered`]。
+//!
+//! ## URL 参数
+//!
+//! - `pool`: 实例数量，默认 1
+//!
+//! ## 使用
+//!
+//! ```rust,ignore
+//! let pool = ModelPoolBuilder::from_url(&url)?.build(|_| Detection::from_url(&url))?;
+//! pool.process_ordered(frames, |frame, output| render(frame, output))?;
+//! ```
+
+use std::collections::BTreeMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Condvar, Mutex, mpsc};
+
+use thiserror::Error;
+use tracing::debug;
+use url::Url;
+
+use crate::{
+  FromUrl,
+  model::{BatchModel, Model},
+};
+
+const POOL_SIZE: usize = 1;
+
+#[derive(Error, Debug)]
+pub enum PoolError {
+  #[error("模型池至少需要一个实例")]
+  Empty,
+  #[error("第 {0} 个实例创建失败: {1}")]
+  BuildError(usize, Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// 持有多个独立实例的模型
+pub struct ModelPool<M> {
+  models: Vec<M>,
+  idle: Mutex<Vec<usize>>,
+  available: Condvar,
+}
+
+/// 取出的实例，释放时归还模型池
+struct Lease<'a, M> {
+  pool: &'a ModelPool<M>,
+  index: usize,
+}
+
+impl<M> Drop for Lease<'_, M> {
+  fn drop(&mut self) {
+    let mut idle = self.pool.idle.lock().unwrap_or_else(|e| e.into_inner());
+    idle.push(self.index);
+    self.pool.available.notify_one();
+  }
+}
+
+impl<M> ModelPool<M> {
+  pub fn new(models: Vec<M>) -> Result<Self, PoolError> {
+    if models.is_empty() {
+      return Err(PoolError::Empty);
+    }
+    // 倒序放入，优先取出编号小的实例
+    let idle = (0..models.len()).rev().collect();
+    Ok(Self {
+      models,
+      idle: Mutex::new(idle),
+      available: Condvar::new(),
+    })
+  }
+
+  pub fn len(&self) -> usize {
+    self.models.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.models.is_empty()
+  }
+
+  /// 当前空闲的实例数量
+  pub fn idle_num(&self) -> usize {
+    self.idle.lock().map(|idle| idle.len()).unwrap_or(0)
+  }
+
+  /// 等待并取出一个空闲实例
+  fn lease(&self) -> Lease<'_, M> {
+    let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+    loop {
+      if let Some(index) = idle.pop() {
+        return Lease { pool: self, index };
+      }
+      idle = self.available.wait(idle).unwrap_or_else(|e| e.into_inner());
+    }
+  }
+}
+
+impl<M: Model> Model for ModelPool<M> {
+  type Input = M::Input;
+  type Output = M::Output;
+  type Error = M::Error;
+
+  fn infer(&self, input: &Self::Input) -> Result<Self::Output, Self::Error> {
+    let lease = self.lease();
+    debug!("使用第 {} 个实例推理", lease.index);
+    self.models[lease.index].infer(input)
+  }
+}
+
+impl<M: Model> BatchModel for ModelPool<M> {}
+
+impl<M> ModelPool<M>
+where
+  M: Model + Sync,
+  M::Input: Send,
+  M::Output: Send,
+  M::Error: Send,
+{
+  /// 以与实例数量相同的工作线程调用 [`infer_ordered`]
+  pub fn process_ordered<I, S, E>(&self, inputs: I, sink: S) -> Result<(), E>
+  where
+    I: IntoIterator<Item = M::Input>,
+    S: FnMut(M::Input, M::Output) -> Result<(), E>,
+    E: From<M::Error>,
+  {
+    infer_ordered(self, self.len(), inputs, sink)
+  }
+}
+
+/// 在 `workers` 个工作线程上并发推理，`sink` 按输入顺序接收每一帧及其输出
+///
+/// `model` 通常是 [`ModelPool`] 或包装了模型池的模型。同时处理的帧数不超过工作线程数量的两倍。
+/// 推理或 `sink` 出错时停止读取输入并返回错误，已提交的帧仍会推理完成但不再交付。
+/// 推理发生 panic 时在调用线程上重新抛出。
+pub fn infer_ordered<M, I, S, E>(model: &M, workers: usize, inputs: I, mut sink: S) -> Result<(), E>
+where
+  M: Model + Sync,
+  M::Input: Send,
+  M::Output: Send,
+  M::Error: Send,
+  I: IntoIterator<Item = M::Input>,
+  S: FnMut(M::Input, M::Output) -> Result<(), E>,
+  E: From<M::Error>,
+{
+  let workers = workers.max(1);
+  let capacity = workers * 2;
+  let (job_tx, job_rx) = mpsc::channel::<(usize, M::Input)>();
+  let (done_tx, done_rx) = mpsc::channel();
+  let job_rx = Mutex::new(job_rx);
+
+  std::thread::scope(|scope| {
+    for _ in 0..workers {
+      let job_rx = &job_rx;
+      let done_tx = done_tx.clone();
+      scope.spawn(move || {
+        loop {
+          // 取出任务后立即释放锁，其他线程可以同时等待下一个任务
+          let job = job_rx.lock().unwrap_or_else(|e| e.into_inner()).recv();
+          let Ok((seq, input)) = job else {
+            break;
+          };
+          // 捕获 panic 交给调用线程，否则调用线程会一直等待这一帧的结果
+          let output = std::panic::catch_unwind(AssertUnwindSafe(|| model.infer(&input)));
+          if done_tx.send((seq, input, output)).is_err() {
+            break;
+          }
+        }
+      });
+    }
+    drop(done_tx);
+
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+    let mut submitted = 0;
+    let mut deliver = |pending: &mut BTreeMap<_, _>, next: &mut usize| -> Result<(), E> {
+      let (seq, input, output) = done_rx.recv().expect("工作线程意外退出");
+      let output = output.unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+      pending.insert(seq, (input, output));
+      while let Some((input, output)) = pending.remove(next) {
+        *next += 1;
+        sink(input, output?)?;
+      }
+      Ok(())
+    };
+
+    for input in inputs {
+      while submitted - next >= capacity {
+        deliver(&mut pending, &mut next)?;
+      }
+      job_tx.send((submitted, input)).expect("工作线程意外退出");
+      submitted += 1;
+    }
+    drop(job_tx);
+
+    while next < submitted {
+      deliver(&mut pending, &mut next)?;
+    }
+    Ok(())
+  })
+}
+
+#[derive(Debug, Clone)]
+pub struct ModelPoolBuilder {
+  size: usize,
+}
+
+impl Default for ModelPoolBuilder {
+  fn default() -> Self {
+    Self { size: POOL_SIZE }
+  }
+}
+
+impl FromUrl for ModelPoolBuilder {
+  type Error = PoolError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    let mut builder = ModelPoolBuilder::default();
+    for (k, v) in url.query_pairs() {
+      if k == "pool" {
+        builder.size = v.parse().unwrap_or(POOL_SIZE);
+      }
+    }
+    Ok(builder)
+  }
+}
+
+impl ModelPoolBuilder {
+  pub fn size(mut self, size: usize) -> Self {
+    self.size = size;
+    self
+  }
+
+  /// 将要创建的实例数量
+  pub fn instances(&self) -> usize {
+    self.size
+  }
+
+  /// 以实例编号调用 `make` 创建每个实例，例如为不同实例指定不同的 NPU 核心
+  pub fn build<M, E, F>(&self, mut make: F) -> Result<ModelPool<M>, PoolError>
+  where
+    F: FnMut(usize) -> Result<M, E>,
+    E: std::error::Error + Send + Sync + 'static,
+  {
+    debug!("创建模型池，实例数量: {}", self.size);
+    let models = (0..self.size)
+      .map(|index| make(index).map_err(|e| PoolError::BuildError(index, Box::new(e))))
+      .collect::<Result<Vec<_>, _>>()?;
+    ModelPool::new(models)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::atomic::{AtomicUsize, Ordering};
+  use std::time::Duration;
+
+  use super::{ModelPool, ModelPoolBuilder, infer_ordered};
+  use crate::model::Model;
+
+  /// 测试用模型，延迟随输入变化，返回输入的两倍，输入为 `panic_at` 时 panic
+  struct MockModel {
+    panic_at: Option<u32>,
+  }
+
+  impl Model for MockModel {
+    type Input = u32;
+    type Output = u32;
+    type Error = std::convert::Infallible;
+
+    fn infer(&self, input: &Self::Input) -> Result<Self::Output, Self::Error> {
+      if self.panic_at == Some(*input) {
+        panic!("模拟推理失败");
+      }
+      std::thread::sleep(Duration::from_millis(u64::from((input * 7) % 5)));
+      Ok(input * 2)
+    }
+  }
+
+  #[derive(Debug, PartialEq)]
+  struct Stop;
+
+  impl From<std::convert::Infallible> for Stop {
+    fn from(e: std::convert::Infallible) -> Self {
+      match e {}
+    }
+  }
+
+  fn pool(size: usize, panic_at: Option<u32>) -> ModelPool<MockModel> {
+    ModelPoolBuilder::default()
+      .size(size)
+      .build(|_| Ok::<_, std::io::Error>(MockModel { panic_at }))
+      .unwrap()
+  }
+
+  #[test]
+  fn delivers_in_input_order() {
+    let pool = pool(4, None);
+    let mut delivered = Vec::new();
+    pool
+      .process_ordered(0..50, |input, output| {
+        delivered.push((input, output));
+        Ok::<(), Stop>(())
+      })
+      .unwrap();
+    assert_eq!(delivered, (0..50).map(|i| (i, i * 2)).collect::<Vec<_>>());
+    assert_eq!(pool.idle_num(), 4);
+  }
+
+  #[test]
+  fn stops_reading_input_on_sink_error() {
+    let pool = pool(2, None);
+    let read = AtomicUsize::new(0);
+    let inputs = (0..1000).inspect(|_| {
+      read.fetch_add(1, Ordering::SeqCst);
+    });
+    let mut delivered = Vec::new();
+    let result = infer_ordered(&pool, 2, inputs, |input, _| {
+      if input == 5 {
+        return Err(Stop);
+      }
+      delivered.push(input);
+      Ok(())
+    });
+
+    assert_eq!(result, Err(Stop));
+    assert_eq!(delivered, [0, 1, 2, 3, 4]);
+    // 最多多读取同时处理的帧数
+    assert!(read.load(Ordering::SeqCst) <= 6 + 2 * 2);
+  }
+
+  #[test]
+  #[should_panic(expected = "模拟推理失败")]
+  fn propagates_worker_panic() {
+    let pool = pool(2, Some(3));
+    let _ = infer_ordered(&pool, 2, 0..100, |_, _| Ok::<(), Stop>(()));
+  }
+}
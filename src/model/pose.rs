@@ -0,0 +1,84 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/pose.rs - 姿态关键点
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 姿态关键点
+//!
+//! [`Pose`] 保存一个实例的关键点及骨架连接关系，坐标为模型输入图像的像素坐标。
+//! 默认骨架为 COCO 17 点人体骨架。
+
+use std::sync::Arc;
+
+/// COCO 人体关键点数量
+pub const COCO_KEYPOINTS: usize = 17;
+
+/// COCO 人体骨架，关键点顺序为鼻、左右眼、左右耳、左右肩、左右肘、左右腕、左右髋、左右膝、左右踝
+pub const COCO_SKELETON: [(usize, usize); 19] = [
+  (15, 13),
+  (13, 11),
+  (16, 14),
+  (14, 12),
+  (11, 12),
+  (5, 11),
+  (6, 12),
+  (5, 6),
+  (5, 7),
+  (6, 8),
+  (7, 9),
+  (8, 10),
+  (1, 2),
+  (0, 1),
+  (0, 2),
+  (1, 3),
+  (2, 4),
+  (3, 5),
+  (4, 6),
+];
+
+#[derive(Debug, Clone, Copy)]
+pub struct Keypoint {
+  pub x: f32,
+  pub y: f32,
+  /// 关键点可见置信度
+  pub score: f32,
+}
+
+/// 一个实例的关键点
+#[derive(Debug, Clone)]
+pub struct Pose {
+  pub keypoints: Vec<Keypoint>,
+  /// 骨架连接的关键点下标对，同一模型的所有实例共享
+  pub skeleton: Arc<[(usize, usize)]>,
+}
+
+impl Pose {
+  /// 两端关键点置信度都超过阈值的骨架连线
+  pub fn limbs(&self, thresh: f32) -> impl Iterator<Item = (&Keypoint, &Keypoint)> + '_ {
+    self.skeleton.iter().filter_map(move |(a, b)| {
+      let (a, b) = (self.keypoints.get(*a)?, self.keypoints.get(*b)?);
+      (a.score > thresh && b.score > thresh).then_some((a, b))
+    })
+  }
+}
+
+/// 解析骨架定义，形如 `0-1,1-2,2-3`
+pub fn parse_skeleton(value: &str) -> Option<Vec<(usize, usize)>> {
+  value
+    .split(',')
+    .map(str::trim)
+    .filter(|s| !s.is_empty())
+    .map(|pair| {
+      let (a, b) = pair.split_once('-')?;
+      Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+    })
+    .collect()
+}
@@ -0,0 +1,716 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/reload.rs - 模型热重载
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 模型热重载
+//!
+//! 在不重启流水线的情况下重新加载模型文件与后处理参数（`object_thresh`、`pdim`、类别过滤等）。
+//! 模型与后处理作为一个 [`Stage`] 整体替换：[`ReloadModel`] 推理时取得当前的 [`Stage`]，
+//! 并随输出传递给 [`ReloadPostprocess`]，已开始推理的帧始终由同一个旧 [`Stage`] 完成。
+//!
+//! 新的 [`Stage`] 构建成功，并且探测帧（若设置）推理与后处理均无错误后才会替换，
+//! 否则记录错误并继续使用原模型。
+//!
+//! ## 触发
+//!
+//! - 模型文件、参数文件或 `class_file` 被修改或替换
+//! - 进程收到 `SIGHUP`
+//!
+//! ## URL 参数
+//!
+//! - `reload`: 启用热重载
+//! - `reload_params`: 参数文件路径，TOML 表，其中的键值覆盖模型 URL 的同名查询参数，
+//!   `model_path` 可替换模型文件路径
+//! - `reload_settle_ms`: 最后一次文件变化后等待多久开始重载，默认 500
+//!
+//! ```toml
+//! object_thresh = 0.35
+//! classes = ["person", "car"]
+//! ```
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{Sender, channel};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use shanan_trait::Postprocess;
+use signal_hook::{consts::SIGHUP, iterator::Signals};
+use thiserror::Error;
+use tracing::{debug, error, info, warn};
+use url::Url;
+
+use crate::{FromUrl, model::Model};
+
+const RELOAD_SETTLE_MS: u64 = 500;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Error, Debug)]
+pub enum HotReloadError {
+  #[error("参数文件读取错误: {0}")]
+  IoError(#[from] std::io::Error),
+  #[error("参数文件解析错误: {0}")]
+  TomlError(#[from] toml::de::Error),
+  #[error("无效的参数: {0}")]
+  InvalidParams(String),
+  #[error("文件监控错误: {0}")]
+  NotifyError(#[from] notify::Error),
+  #[error("模型构建错误: {0}")]
+  BuildError(BoxError),
+  #[error("模型验证错误: {0}")]
+  ValidationError(BoxError),
+}
+
+/// 一起替换的模型与后处理
+pub struct Stage<M, P> {
+  pub model: M,
+  pub post: P,
+}
+
+/// 模型输出，附带产生该输出的 [`Stage`]
+pub struct Reloaded<M, P, O> {
+  stage: Arc<Stage<M, P>>,
+  output: O,
+}
+
+impl<M, P, O: AsRef<T>, T: ?Sized> AsRef<T> for Reloaded<M, P, O> {
+  fn as_ref(&self) -> &T {
+    self.output.as_ref()
+  }
+}
+
+struct Slot<M, P> {
+  current: RwLock<Arc<Stage<M, P>>>,
+  generation: AtomicUsize,
+}
+
+impl<M, P> Slot<M, P> {
+  fn current(&self) -> Arc<Stage<M, P>> {
+    self
+      .current
+      .read()
+      .unwrap_or_else(|e| e.into_inner())
+      .clone()
+  }
+
+  fn replace(&self, stage: Stage<M, P>) -> usize {
+    *self.current.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(stage);
+    self.generation.fetch_add(1, Ordering::SeqCst) + 1
+  }
+}
+
+/// 可热重载的模型
+pub struct ReloadModel<M, P> {
+  slot: Arc<Slot<M, P>>,
+}
+
+/// 使用推理时的 [`Stage`] 做后处理
+pub struct ReloadPostprocess<M, P> {
+  _phantom: std::marker::PhantomData<fn() -> (M, P)>,
+}
+
+impl<M: Model, P> Model for ReloadModel<M, P> {
+  type Input = M::Input;
+  type Output = Reloaded<M, P, M::Output>;
+  type Error = M::Error;
+
+  fn infer(&self, input: &Self::Input) -> Result<Self::Output, Self::Error> {
+    let stage = self.slot.current();
+    let output = stage.model.infer(input)?;
+    Ok(Reloaded { stage, output })
+  }
+}
+
+impl<M, P> Postprocess for ReloadPostprocess<M, P>
+where
+  M: Model,
+  P: Postprocess<Input = M::Output>,
+{
+  type Input = Reloaded<M, P, M::Output>;
+  type Output = P::Output;
+  type Error = P::Error;
+
+  fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+    let Reloaded { stage, output } = output;
+    stage.post.process(output)
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct HotReloadBuilder {
+  enabled: bool,
+  params: Option<PathBuf>,
+  settle: Duration,
+}
+
+impl Default for HotReloadBuilder {
+  fn default() -> Self {
+    Self {
+      enabled: false,
+      params: None,
+      settle: Duration::from_millis(RELOAD_SETTLE_MS),
+    }
+  }
+}
+
+impl FromUrl for HotReloadBuilder {
+  type Error = HotReloadError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    let mut builder = HotReloadBuilder::default();
+    for (k, v) in url.query_pairs() {
+      match k.as_ref() {
+        "reload" => builder.enabled = v != "false",
+        "reload_params" => {
+          builder.enabled = true;
+          builder.params = Some(PathBuf::from(v.as_ref()));
+        }
+        "reload_settle_ms" => {
+          builder.settle = Duration::from_millis(v.parse().unwrap_or(RELOAD_SETTLE_MS))
+        }
+        _ => {}
+      }
+    }
+    Ok(builder)
+  }
+}
+
+impl HotReloadBuilder {
+  pub fn enabled(mut self, enabled: bool) -> Self {
+    self.enabled = enabled;
+    self
+  }
+
+  pub fn params(mut self, params: PathBuf) -> Self {
+    self.params = Some(params);
+    self
+  }
+
+  pub fn settle(mut self, settle: Duration) -> Self {
+    self.settle = settle;
+    self
+  }
+
+  /// 合并参数文件后的模型 URL，即构建初始 [`Stage`] 使用的 URL
+  pub fn effective_url(&self, url: &Url) -> Result<Url, HotReloadError> {
+    effective_url(url, self.params.as_deref())
+  }
+
+  /// 以合并参数文件后的 URL 调用 `make` 构建初始的 [`Stage`]，重载时使用同一个 `make`
+  pub fn build<M, P, E, F>(&self, url: &Url, make: F) -> Result<HotReload<M, P>, HotReloadError>
+  where
+    M: Model,
+    F: Fn(&Url) -> Result<Stage<M, P>, E> + Send + Sync + 'static,
+    E: Into<BoxError>,
+  {
+    let effective = effective_url(url, self.params.as_deref())?;
+    let stage = make(&effective).map_err(|e| HotReloadError::BuildError(e.into()))?;
+    Ok(HotReload {
+      slot: Arc::new(Slot {
+        current: RwLock::new(Arc::new(stage)),
+        generation: AtomicUsize::new(0),
+      }),
+      url: url.clone(),
+      builder: self.clone(),
+      make: Box::new(move |url| make(url).map_err(Into::into)),
+      probe: None,
+    })
+  }
+}
+
+type MakeStage<M, P> = Box<dyn Fn(&Url) -> Result<Stage<M, P>, BoxError> + Send + Sync>;
+
+/// 热重载控制，提供流水线使用的模型与后处理
+pub struct HotReload<M: Model, P> {
+  slot: Arc<Slot<M, P>>,
+  url: Url,
+  builder: HotReloadBuilder,
+  make: MakeStage<M, P>,
+  probe: Option<M::Input>,
+}
+
+/// 文件与信号监控，释放时停止文件监控
+pub struct ReloadWatcher {
+  _watcher: Option<Arc<Mutex<FileWatcher>>>,
+}
+
+impl<M: Model, P> HotReload<M, P> {
+  /// 验证新模型使用的探测帧，例如一帧全黑图像
+  pub fn probe(mut self, input: M::Input) -> Self {
+    self.probe = Some(input);
+    self
+  }
+
+  pub fn model(&self) -> ReloadModel<M, P> {
+    ReloadModel {
+      slot: self.slot.clone(),
+    }
+  }
+
+  pub fn postprocess(&self) -> ReloadPostprocess<M, P> {
+    ReloadPostprocess {
+      _phantom: std::marker::PhantomData,
+    }
+  }
+
+  /// 已完成的重载次数
+  pub fn generation(&self) -> usize {
+    self.slot.generation.load(Ordering::SeqCst)
+  }
+}
+
+impl<M, P> HotReload<M, P>
+where
+  M: Model,
+  M::Error: std::error::Error + Send + Sync + 'static,
+  P: Postprocess<Input = M::Output>,
+  P::Error: std::error::Error + Send + Sync + 'static,
+{
+  /// 构建并验证新的 [`Stage`]，成功后替换，失败时保留原模型
+  pub fn reload(&self) -> Result<(), HotReloadError> {
+    let url = effective_url(&self.url, self.builder.params.as_deref())?;
+    info!("重新加载模型: {}", url);
+    let stage = (self.make)(&url).map_err(HotReloadError::BuildError)?;
+
+    if let Some(probe) = &self.probe {
+      let output = stage
+        .model
+        .infer(probe)
+        .map_err(|e| HotReloadError::ValidationError(Box::new(e)))?;
+      stage
+        .post
+        .process(output)
+        .map_err(|e| HotReloadError::ValidationError(Box::new(e)))?;
+      debug!("新模型验证通过");
+    }
+
+    let generation = self.slot.replace(stage);
+    info!("模型已切换，第 {} 次重载", generation);
+    Ok(())
+  }
+}
+
+impl<M, P> HotReload<M, P>
+where
+  M: Model + Send + Sync + 'static,
+  M::Input: Send,
+  M::Error: std::error::Error + Send + Sync + 'static,
+  P: Postprocess<Input = M::Output> + Send + Sync + 'static,
+  P::Error: std::error::Error + Send + Sync + 'static,
+{
+  /// 启动后台线程，在文件变化或收到 `SIGHUP` 时重载，未启用热重载时不做任何事
+  ///
+  /// 每次重载成功后重新计算监控的文件，参数文件修改 `model_path` 或 `class_file`
+  /// 后监控新的文件。
+  pub fn watch(self) -> Result<ReloadWatcher, HotReloadError> {
+    if !self.builder.enabled {
+      return Ok(ReloadWatcher { _watcher: None });
+    }
+
+    let (tx, rx) = channel();
+    let watcher = Arc::new(Mutex::new(FileWatcher::new(tx.clone())?));
+    watcher
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .update(self.watch_files()?)?;
+
+    let mut signals = Signals::new([SIGHUP])?;
+    std::thread::spawn(move || {
+      for _ in signals.forever() {
+        info!("收到 SIGHUP，准备重载模型");
+        if tx.send(()).is_err() {
+          break;
+        }
+      }
+    });
+
+    let settle = self.builder.settle;
+    let files = Arc::downgrade(&watcher);
+    std::thread::spawn(move || {
+      while rx.recv().is_ok() {
+        // 文件通常分多次写入，等待一段时间没有新的变化后再重载
+        while rx.recv_timeout(settle).is_ok() {}
+        if let Err(e) = self.reload() {
+          error!("模型重载失败，继续使用原模型: {}", e);
+          continue;
+        }
+        // 监控已释放时只响应 SIGHUP
+        let Some(watcher) = files.upgrade() else {
+          continue;
+        };
+        let updated = self.watch_files().and_then(|files| {
+          watcher
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .update(files)
+        });
+        if let Err(e) = updated {
+          warn!("更新监控文件失败: {}", e);
+        }
+      }
+    });
+
+    Ok(ReloadWatcher {
+      _watcher: Some(watcher),
+    })
+  }
+}
+
+impl<M: Model, P> HotReload<M, P> {
+  /// 需要监控的文件：模型文件、参数文件与 `class_file`
+  fn watch_files(&self) -> Result<Vec<PathBuf>, HotReloadError> {
+    let url = effective_url(&self.url, self.builder.params.as_deref())?;
+    let mut files = vec![PathBuf::from(url.path())];
+    files.extend(self.builder.params.clone());
+    files.extend(
+      url
+        .query_pairs()
+        .filter(|(k, _)| k == "class_file")
+        .map(|(_, v)| PathBuf::from(v.as_ref())),
+    );
+    Ok(files)
+  }
+}
+
+/// 所在目录与文件名
+type WatchTarget = (PathBuf, OsString);
+
+/// 按目录监控一组文件，文件被替换（重命名覆盖）时同样能收到事件
+struct FileWatcher {
+  watcher: RecommendedWatcher,
+  targets: Arc<RwLock<Vec<WatchTarget>>>,
+  dirs: Vec<PathBuf>,
+}
+
+impl FileWatcher {
+  fn new(tx: Sender<()>) -> Result<Self, HotReloadError> {
+    let targets: Arc<RwLock<Vec<WatchTarget>>> = Arc::default();
+    let watched = targets.clone();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+      let event = match event {
+        Ok(event) => event,
+        Err(e) => {
+          warn!("文件监控事件错误: {}", e);
+          return;
+        }
+      };
+      if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+        return;
+      }
+      let watched = watched.read().unwrap_or_else(|e| e.into_inner());
+      let changed = event.paths.iter().any(|path| {
+        watched.iter().any(|(dir, name)| {
+          path.parent() == Some(dir.as_path()) && path.file_name() == Some(name.as_os_str())
+        })
+      });
+      if changed {
+        debug!("检测到文件变化: {:?}", event.paths);
+        let _ = tx.send(());
+      }
+    })?;
+    Ok(Self {
+      watcher,
+      targets,
+      dirs: Vec::new(),
+    })
+  }
+
+  /// 替换监控的文件，只增减变化的目录
+  fn update(&mut self, files: Vec<PathBuf>) -> Result<(), HotReloadError> {
+    let targets = watch_targets(&files)?;
+    let mut dirs: Vec<PathBuf> = targets.iter().map(|(dir, _)| dir.clone()).collect();
+    dirs.sort();
+    dirs.dedup();
+
+    for dir in dirs.iter().filter(|dir| !self.dirs.contains(dir)) {
+      self.watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    }
+    for dir in self.dirs.iter().filter(|dir| !dirs.contains(dir)) {
+      if let Err(e) = self.watcher.unwatch(dir) {
+        debug!("取消监控目录 {:?} 失败: {}", dir, e);
+      }
+    }
+    self.dirs = dirs;
+    *self.targets.write().unwrap_or_else(|e| e.into_inner()) = targets;
+    info!("热重载监控文件: {:?}", files);
+    Ok(())
+  }
+}
+
+fn watch_targets(files: &[PathBuf]) -> Result<Vec<WatchTarget>, HotReloadError> {
+  let mut targets = Vec::new();
+  for file in files {
+    let (Some(parent), Some(name)) = (file.parent(), file.file_name()) else {
+      continue;
+    };
+    let parent = if parent.as_os_str().is_empty() {
+      Path::new(".")
+    } else {
+      parent
+    };
+    targets.push((parent.canonicalize()?, name.to_owned()));
+  }
+  Ok(targets)
+}
+
+/// 以参数文件中的键值覆盖模型 URL 的同名查询参数
+pub fn effective_url(url: &Url, params: Option<&Path>) -> Result<Url, HotReloadError> {
+  let Some(path) = params else {
+    return Ok(url.clone());
+  };
+  let table = std::fs::read_to_string(path)?.parse::<toml::Table>()?;
+
+  let mut effective = url.clone();
+  let mut overrides = Vec::new();
+  for (key, value) in &table {
+    let value = match value {
+      toml::Value::String(s) => s.clone(),
+      toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => {
+        value.to_string()
+      }
+      toml::Value::Array(items) => items
+        .iter()
+        .map(|item| match item {
+          toml::Value::String(s) => s.clone(),
+          other => other.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(","),
+      _ => {
+        return Err(HotReloadError::InvalidParams(format!(
+          "{}: 不支持的参数值 {}",
+          key, value
+        )));
+      }
+    };
+    if key == "model_path" {
+      effective.set_path(&value);
+    } else {
+      overrides.push((key.clone(), value));
+    }
+  }
+
+  let pairs: Vec<(String, String)> = url
+    .query_pairs()
+    .filter(|(k, _)| !table.contains_key(k.as_ref()))
+    .map(|(k, v)| (k.into_owned(), v.into_owned()))
+    .chain(overrides)
+    .collect();
+  effective.set_query(None);
+  if !pairs.is_empty() {
+    effective.query_pairs_mut().extend_pairs(pairs);
+  }
+  Ok(effective)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::{Path, PathBuf};
+  use std::sync::mpsc::channel;
+  use std::time::Duration;
+
+  use shanan_trait::Postprocess;
+  use url::Url;
+
+  use super::{FileWatcher, HotReload, HotReloadBuilder, HotReloadError, Stage, effective_url};
+  use crate::model::Model;
+
+  fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("shanan-reload-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  fn write_params(path: &Path, content: &str) -> PathBuf {
+    std::fs::write(path, content).unwrap();
+    path.to_path_buf()
+  }
+
+  /// 输入乘以 `scale`
+  struct Scale(i32);
+
+  impl Model for Scale {
+    type Input = i32;
+    type Output = i32;
+    type Error = std::convert::Infallible;
+
+    fn infer(&self, input: &Self::Input) -> Result<Self::Output, Self::Error> {
+      Ok(input * self.0)
+    }
+  }
+
+  /// 拒绝负数输出
+  struct NonNegative;
+
+  impl Postprocess for NonNegative {
+    type Input = i32;
+    type Output = i32;
+    type Error = std::io::Error;
+
+    fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+      if output < 0 {
+        return Err(std::io::Error::other("负数输出"));
+      }
+      Ok(output)
+    }
+  }
+
+  fn build(params: PathBuf) -> HotReload<Scale, NonNegative> {
+    let url = Url::parse("file:///models/a.rknn").unwrap();
+    HotReloadBuilder::default()
+      .params(params)
+      .build(&url, |url| -> Result<_, HotReloadError> {
+        let scale = url
+          .query_pairs()
+          .find(|(k, _)| k == "scale")
+          .and_then(|(_, v)| v.parse().ok())
+          .ok_or_else(|| HotReloadError::InvalidParams(format!("scale: {}", url)))?;
+        Ok(Stage {
+          model: Scale(scale),
+          post: NonNegative,
+        })
+      })
+      .unwrap()
+  }
+
+  fn run(reload: &HotReload<Scale, NonNegative>, input: i32) -> i32 {
+    let output = reload.model().infer(&input).unwrap();
+    reload.postprocess().process(output).unwrap()
+  }
+
+  #[test]
+  fn effective_url_overrides_query() {
+    let url = Url::parse("file:///models/a.rknn?object_thresh=0.5&pdim=8&reload=true").unwrap();
+    assert_eq!(effective_url(&url, None).unwrap(), url);
+
+    let dir = temp_dir("effective");
+    let params = write_params(
+      &dir.join("params.toml"),
+      "object_thresh = 0.35\nclasses = [\"person\", \"car\"]\nmodel_path = \"/models/b.rknn\"\n",
+    );
+    let effective = effective_url(&url, Some(&params)).unwrap();
+    assert_eq!(effective.path(), "/models/b.rknn");
+    let pairs: Vec<(String, String)> = effective
+      .query_pairs()
+      .map(|(k, v)| (k.into_owned(), v.into_owned()))
+      .collect();
+    assert_eq!(
+      pairs,
+      [
+        ("pdim", "8"),
+        ("reload", "true"),
+        ("classes", "person,car"),
+        ("object_thresh", "0.35"),
+      ]
+      .map(|(k, v)| (k.to_string(), v.to_string()))
+    );
+
+    let params = write_params(&dir.join("bad.toml"), "[nested]\nkey = 1\n");
+    assert!(matches!(
+      effective_url(&url, Some(&params)),
+      Err(HotReloadError::InvalidParams(_))
+    ));
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn reload_swaps_stage() {
+    let dir = temp_dir("swap");
+    let params = write_params(&dir.join("params.toml"), "scale = 2\n");
+    let reload = build(params.clone());
+    assert_eq!(run(&reload, 3), 6);
+
+    // 重载前开始推理的帧由旧的 Stage 完成
+    let in_flight = reload.model().infer(&3).unwrap();
+    write_params(&params, "scale = 5\n");
+    reload.reload().unwrap();
+    assert_eq!(reload.generation(), 1);
+    assert_eq!(run(&reload, 3), 15);
+    assert_eq!(reload.postprocess().process(in_flight).unwrap(), 6);
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn failed_reload_keeps_previous_stage() {
+    let dir = temp_dir("rollback");
+    let params = write_params(&dir.join("params.toml"), "scale = 2\n");
+    let reload = build(params.clone()).probe(1);
+
+    // 构建失败
+    write_params(&params, "scale = \"x\"\n");
+    assert!(matches!(
+      reload.reload(),
+      Err(HotReloadError::BuildError(_))
+    ));
+    // 探测帧后处理失败
+    write_params(&params, "scale = -1\n");
+    assert!(matches!(
+      reload.reload(),
+      Err(HotReloadError::ValidationError(_))
+    ));
+
+    assert_eq!(reload.generation(), 0);
+    assert_eq!(run(&reload, 3), 6);
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn watch_files_follow_params() {
+    let dir = temp_dir("watch");
+    let params = write_params(&dir.join("params.toml"), "scale = 2\n");
+    let reload = build(params.clone());
+    assert_eq!(
+      reload.watch_files().unwrap(),
+      [PathBuf::from("/models/a.rknn"), params.clone()]
+    );
+
+    write_params(
+      &params,
+      "scale = 2\nmodel_path = \"/models/b.rknn\"\nclass_file = \"/models/classes.toml\"\n",
+    );
+    assert_eq!(
+      reload.watch_files().unwrap(),
+      [
+        PathBuf::from("/models/b.rknn"),
+        params.clone(),
+        PathBuf::from("/models/classes.toml"),
+      ]
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn file_watcher_moves_to_new_files() {
+    let dir = temp_dir("watcher");
+    let (old, new) = (dir.join("old"), dir.join("new"));
+    std::fs::create_dir_all(&old).unwrap();
+    std::fs::create_dir_all(&new).unwrap();
+
+    let (tx, rx) = channel();
+    let mut watcher = FileWatcher::new(tx).unwrap();
+    watcher.update(vec![old.join("a.rknn")]).unwrap();
+    watcher.update(vec![new.join("b.rknn")]).unwrap();
+
+    std::fs::write(old.join("a.rknn"), b"a").unwrap();
+    std::fs::write(new.join("b.rknn"), b"b").unwrap();
+    rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    // 只有新文件触发重载
+    while rx.recv_timeout(Duration::from_millis(200)).is_ok() {}
+    std::fs::write(old.join("a.rknn"), b"aa").unwrap();
+    assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+}
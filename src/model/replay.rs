@@ -0,0 +1,642 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/replay.rs - 模型输出录制与回放
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 模型输出录制与回放
+//!
+//! [`TensorRecorder`] 在后处理之前将每帧的原始输出张量（含形状）按帧顺序写入目录，
+//! [`ReplayModel`] 按顺序读回这些张量，使后处理、渲染与记录可以在没有 NPU
+//! 的机器上以完全相同的输入运行。
+//!
+//! ## 录制
+//!
+//! 在模型 URL 上添加 `record` 查询参数：
+//!
+//! ```text
+//! yolo26:///path/to/model.rknn?record=/data/tensors
+//! ```
+//!
+//! 目录中会生成 `model.url`（原始模型 URL）以及 `000000.tensor`、`000001.tensor` 等文件。
+//!
+//! ## 回放
+//!
+//! ```text
+//! replay:///data/tensors?loop=true
+//! ```
+//!
+//! - `loop`: 读完所有文件后是否从头开始，默认 `false`
+//!
+//! 后处理根据 `model.url` 中记录的模型构建，回放 URL 上的其他查询参数
+//! （例如 `object_thresh`）会覆盖录制时的参数。
+//!
+//! ## 文件格式
+//!
+//! 所有整数与浮点数均为小端序：
+//!
+//! ```text
+//! "SNTR" | u32 版本 | u32 张量数量 |
+//!   { u8 数据类型 | u8 是否量化 | f32 scale | i32 zero_point |
+//!     u32 维度数量 | u64 维度... | u64 元素数量 | 数据... } ...
+//! ```
+//!
+//! 数据类型：0 为 f32，1 为 u8，2 为 i8。
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use shanan_trait::Postprocess;
+use thiserror::Error;
+use tracing::{debug, error, info};
+use url::Url;
+
+use crate::{
+  FromUrl, FromUrlWithScheme,
+  model::{Model, Quantization, Tensor, TensorDType, TensorData, Tensors},
+};
+
+const REPLAY_MAGIC: &[u8; 4] = b"SNTR";
+const REPLAY_VERSION: u32 = 1;
+/// 单个张量头部的字节数：数据类型、是否量化、scale、zero_point、维度数量与元素数量
+const REPLAY_TENSOR_HEADER_SIZE: u64 = 2 + 4 + 4 + 4 + 8;
+const REPLAY_EXTENSION: &str = "tensor";
+const REPLAY_MODEL_URL_FILE: &str = "model.url";
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+  #[error("模型路径错误: {0}")]
+  ModelPathError(String),
+  #[error("I/O 错误: {0}")]
+  IoError(#[from] std::io::Error),
+  #[error("URL 解析错误: {0}")]
+  UrlError(#[from] url::ParseError),
+  #[error("张量文件无效: {0}")]
+  InvalidFile(String),
+  #[error("录制的张量已全部回放")]
+  Exhausted,
+}
+
+fn dtype_code(dtype: TensorDType) -> u8 {
+  match dtype {
+    TensorDType::F32 => 0,
+    TensorDType::U8 => 1,
+    TensorDType::I8 => 2,
+  }
+}
+
+/// 将张量写入文件
+pub fn write_tensors(path: &Path, tensors: &[Tensor]) -> Result<(), ReplayError> {
+  let mut writer = BufWriter::new(File::create(path)?);
+  writer.write_all(REPLAY_MAGIC)?;
+  writer.write_all(&REPLAY_VERSION.to_le_bytes())?;
+  writer.write_all(&(tensors.len() as u32).to_le_bytes())?;
+  for tensor in tensors {
+    let quantization = tensor.quantization.unwrap_or(Quantization::new(1.0, 0));
+    writer.write_all(&[
+      dtype_code(tensor.dtype()),
+      tensor.quantization.is_some() as u8,
+    ])?;
+    writer.write_all(&quantization.scale.to_le_bytes())?;
+    writer.write_all(&quantization.zero_point.to_le_bytes())?;
+    writer.write_all(&(tensor.shape.len() as u32).to_le_bytes())?;
+    for dim in &tensor.shape {
+      writer.write_all(&(*dim as u64).to_le_bytes())?;
+    }
+    writer.write_all(&(tensor.len() as u64).to_le_bytes())?;
+    match &tensor.data {
+      TensorData::F32(data) => {
+        for value in data {
+          writer.write_all(&value.to_le_bytes())?;
+        }
+      }
+      TensorData::U8(data) => writer.write_all(data)?,
+      TensorData::I8(data) => {
+        let bytes = data.iter().map(|v| *v as u8).collect::<Vec<_>>();
+        writer.write_all(&bytes)?;
+      }
+    }
+  }
+  writer.flush()?;
+  Ok(())
+}
+
+/// 从文件读取张量
+///
+/// 文件中记录的长度不可信，分配内存前先与文件剩余长度比较，并检查形状与元素数量一致。
+pub fn read_tensors(path: &Path) -> Result<Tensors, ReplayError> {
+  let file = File::open(path)?;
+  let file_len = file.metadata()?.len();
+  let mut reader = BufReader::new(file);
+  let invalid = |msg: String| ReplayError::InvalidFile(format!("{}: {}", path.display(), msg));
+
+  let mut magic = [0u8; 4];
+  reader.read_exact(&mut magic)?;
+  if &magic != REPLAY_MAGIC {
+    return Err(invalid("文件头不匹配".to_string()));
+  }
+  let version = read_u32(&mut reader)?;
+  if version != REPLAY_VERSION {
+    return Err(invalid(format!("不支持的版本 {}", version)));
+  }
+
+  let count = read_u32(&mut reader)? as u64;
+  if count * REPLAY_TENSOR_HEADER_SIZE > remaining(&mut reader, file_len)? {
+    return Err(invalid(format!("张量数量 {} 超出文件长度", count)));
+  }
+  let mut tensors = Vec::with_capacity(count as usize);
+  for _ in 0..count {
+    let mut header = [0u8; 2];
+    reader.read_exact(&mut header)?;
+    let scale = f32::from_bits(read_u32(&mut reader)?);
+    let zero_point = read_u32(&mut reader)? as i32;
+    let quantization = (header[1] != 0).then_some(Quantization::new(scale, zero_point));
+
+    let ndim = read_u32(&mut reader)? as u64;
+    if ndim * 8 > remaining(&mut reader, file_len)? {
+      return Err(invalid(format!("维度数量 {} 超出文件长度", ndim)));
+    }
+    let mut shape = Vec::with_capacity(ndim as usize);
+    for _ in 0..ndim {
+      shape.push(read_u64(&mut reader)?);
+    }
+    let len = read_u64(&mut reader)?;
+    if shape.iter().try_fold(1u64, |n, d| n.checked_mul(*d)) != Some(len) {
+      return Err(invalid(format!(
+        "形状 {:?} 与元素数量 {} 不一致",
+        shape, len
+      )));
+    }
+
+    let element_size = match header[0] {
+      0 => 4,
+      1 | 2 => 1,
+      other => return Err(invalid(format!("未知的数据类型 {}", other))),
+    };
+    let left = remaining(&mut reader, file_len)?;
+    let size = len
+      .checked_mul(element_size)
+      .filter(|size| *size <= left)
+      .ok_or_else(|| invalid(format!("元素数量 {} 超出文件长度", len)))?;
+    let mut bytes = vec![0u8; size as usize];
+    reader.read_exact(&mut bytes)?;
+    let data = match header[0] {
+      0 => TensorData::F32(
+        bytes
+          .chunks_exact(4)
+          .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+          .collect(),
+      ),
+      1 => TensorData::U8(bytes),
+      _ => TensorData::I8(bytes.into_iter().map(|v| v as i8).collect()),
+    };
+    tensors.push(Tensor {
+      shape: shape.into_iter().map(|d| d as usize).collect(),
+      quantization,
+      data,
+    });
+  }
+  Ok(tensors)
+}
+
+/// 文件中尚未读取的字节数
+fn remaining(reader: &mut BufReader<File>, file_len: u64) -> Result<u64, ReplayError> {
+  Ok(file_len.saturating_sub(reader.stream_position()?))
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, ReplayError> {
+  let mut buf = [0u8; 4];
+  reader.read_exact(&mut buf)?;
+  Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> Result<u64, ReplayError> {
+  let mut buf = [0u8; 8];
+  reader.read_exact(&mut buf)?;
+  Ok(u64::from_le_bytes(buf))
+}
+
+/// 录制模型输出张量
+///
+/// 参数从模型 URL 的 `record` 查询参数中读取，未设置时不录制。
+#[derive(Debug, Clone, Default)]
+pub struct TensorRecorder {
+  directory: Option<PathBuf>,
+  model_url: Option<Url>,
+}
+
+impl FromUrl for TensorRecorder {
+  type Error = ReplayError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    let Some(directory) = url
+      .query_pairs()
+      .find(|(k, _)| k == "record")
+      .map(|(_, v)| PathBuf::from(v.as_ref()))
+    else {
+      return Ok(Self::default());
+    };
+
+    // 录制的模型 URL 去掉 record 参数，回放时用于构建后处理
+    let mut model_url = url.clone();
+    let pairs: Vec<(String, String)> = url
+      .query_pairs()
+      .filter(|(k, _)| k != "record")
+      .map(|(k, v)| (k.into_owned(), v.into_owned()))
+      .collect();
+    model_url.set_query(None);
+    if !pairs.is_empty() {
+      model_url.query_pairs_mut().extend_pairs(pairs);
+    }
+
+    Ok(Self {
+      directory: Some(directory),
+      model_url: Some(model_url),
+    })
+  }
+}
+
+impl TensorRecorder {
+  pub fn directory(mut self, directory: PathBuf) -> Self {
+    self.directory = Some(directory);
+    self
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    self.directory.is_some()
+  }
+
+  /// 为后处理添加录制功能
+  ///
+  /// 录制在后处理之前进行。后处理按帧的输入顺序调用，模型并发推理时文件编号仍与帧顺序一致。
+  pub fn apply<P>(self, post: P) -> Result<RecordingPostprocess<P>, ReplayError> {
+    if let Some(directory) = &self.directory {
+      std::fs::create_dir_all(directory)?;
+      if let Some(model_url) = &self.model_url {
+        std::fs::write(directory.join(REPLAY_MODEL_URL_FILE), model_url.as_str())?;
+      }
+      info!("录制模型输出张量到: {}", directory.display());
+    }
+    Ok(RecordingPostprocess {
+      post,
+      directory: self.directory,
+      index: AtomicUsize::new(0),
+    })
+  }
+}
+
+/// 录制模型输出后再后处理
+///
+/// 后处理的输入可以是模型输出，也可以是附带模型输出的类型，例如热重载的 `Reloaded`。
+/// 此时录制包装在整个热重载后处理之外，重载前后的录制共用编号，验证新模型的探测帧也不会被录制。
+pub struct RecordingPostprocess<P> {
+  post: P,
+  directory: Option<PathBuf>,
+  index: AtomicUsize,
+}
+
+impl<P> Postprocess for RecordingPostprocess<P>
+where
+  P: Postprocess,
+  P::Input: AsRef<[Tensor]>,
+  P::Error: From<ReplayError>,
+{
+  type Input = P::Input;
+  type Output = P::Output;
+  type Error = P::Error;
+
+  fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+    self.record(output.as_ref())?;
+    self.post.process(output)
+  }
+}
+
+impl<P> RecordingPostprocess<P> {
+  fn record(&self, output: &[Tensor]) -> Result<(), ReplayError> {
+    if let Some(directory) = &self.directory {
+      let index = self.index.fetch_add(1, Ordering::Relaxed);
+      let path = directory.join(format!("{:06}.{}", index, REPLAY_EXTENSION));
+      write_tensors(&path, output)?;
+      debug!("已录制模型输出: {}", path.display());
+    }
+    Ok(())
+  }
+}
+
+/// 回放录制的模型输出
+pub struct ReplayModel<const W: u32, const H: u32, Frame> {
+  files: Vec<PathBuf>,
+  looping: bool,
+  index: Mutex<usize>,
+  _phantom: std::marker::PhantomData<Frame>,
+}
+
+pub struct ReplayBuilder {
+  directory: PathBuf,
+  looping: bool,
+  overrides: Vec<(String, String)>,
+}
+
+impl FromUrlWithScheme for ReplayBuilder {
+  const SCHEME: &'static str = "replay";
+}
+
+impl FromUrl for ReplayBuilder {
+  type Error = ReplayError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    if url.scheme() != Self::SCHEME {
+      return Err(ReplayError::ModelPathError(format!(
+        "模型路径必须使用 {} 方案",
+        Self::SCHEME
+      )));
+    }
+
+    let mut builder = ReplayBuilder {
+      directory: PathBuf::from(url.path()),
+      looping: false,
+      overrides: Vec::new(),
+    };
+    for (k, v) in url.query_pairs() {
+      match k.as_ref() {
+        "loop" => builder.looping = v.parse().unwrap_or(false),
+        _ => builder.overrides.push((k.into_owned(), v.into_owned())),
+      }
+    }
+    Ok(builder)
+  }
+}
+
+impl ReplayBuilder {
+  pub fn looping(mut self, looping: bool) -> Self {
+    self.looping = looping;
+    self
+  }
+
+  /// 录制时的模型 URL，回放 URL 上的查询参数覆盖录制时的参数
+  pub fn model_url(&self) -> Result<Url, ReplayError> {
+    let path = self.directory.join(REPLAY_MODEL_URL_FILE);
+    let recorded = std::fs::read_to_string(&path)?;
+    let mut url = Url::parse(recorded.trim())?;
+    if url.scheme() == Self::SCHEME {
+      return Err(ReplayError::InvalidFile(format!(
+        "{}: 录制的模型不能是回放模型",
+        path.display()
+      )));
+    }
+
+    let mut pairs: Vec<(String, String)> = url
+      .query_pairs()
+      .filter(|(k, _)| !self.overrides.iter().any(|(o, _)| o == k))
+      .map(|(k, v)| (k.into_owned(), v.into_owned()))
+      .collect();
+    pairs.extend(self.overrides.iter().cloned());
+    url.set_query(None);
+    if !pairs.is_empty() {
+      url.query_pairs_mut().extend_pairs(pairs);
+    }
+    Ok(url)
+  }
+
+  pub fn build_model<const W: u32, const H: u32, Frame>(
+    self,
+  ) -> Result<ReplayModel<W, H, Frame>, ReplayError> {
+    let mut files = std::fs::read_dir(&self.directory)?
+      .filter_map(|entry| entry.ok().map(|e| e.path()))
+      .filter(|path| path.extension().is_some_and(|ext| ext == REPLAY_EXTENSION))
+      .collect::<Vec<_>>();
+    files.sort();
+
+    if files.is_empty() {
+      error!("回放目录中没有张量文件: {}", self.directory.display());
+      return Err(ReplayError::ModelPathError(format!(
+        "回放目录中没有张量文件: {}",
+        self.directory.display()
+      )));
+    }
+    info!(
+      "从 {} 回放 {} 次推理输出",
+      self.directory.display(),
+      files.len()
+    );
+
+    Ok(ReplayModel {
+      files,
+      looping: self.looping,
+      index: Mutex::new(0),
+      _phantom: std::marker::PhantomData,
+    })
+  }
+}
+
+impl<const W: u32, const H: u32, Frame> Model for ReplayModel<W, H, Frame> {
+  type Input = Frame;
+  type Output = Tensors;
+  type Error = ReplayError;
+
+  fn infer(&self, _input: &Self::Input) -> Result<Self::Output, Self::Error> {
+    let path = {
+      let mut index = self.index.lock().unwrap();
+      if *index >= self.files.len() {
+        if !self.looping {
+          return Err(ReplayError::Exhausted);
+        }
+        *index = 0;
+      }
+      let path = &self.files[*index];
+      *index += 1;
+      path
+    };
+
+    debug!("回放模型输出: {}", path.display());
+    read_tensors(path)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::PathBuf;
+
+  use shanan_trait::Postprocess;
+  use url::Url;
+
+  use super::{
+    REPLAY_MODEL_URL_FILE, ReplayBuilder, ReplayError, TensorRecorder, read_tensors, write_tensors,
+  };
+  use crate::{
+    FromUrl,
+    model::{Model, Quantization, Tensor, Tensors},
+  };
+
+  fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("shanan-replay-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  fn sample() -> Vec<Tensor> {
+    vec![
+      Tensor::from_f32(
+        vec![1, 2, 3],
+        vec![0.0, -1.5, 2.25, 3.0, f32::MAX, f32::MIN],
+      ),
+      Tensor::from_u8(
+        vec![2, 2],
+        vec![0, 1, 128, 255],
+        Quantization::new(0.5, 128),
+      ),
+      Tensor::from_i8(
+        vec![4],
+        vec![-128, -1, 0, 127],
+        Quantization::new(0.025, -3),
+      ),
+      Tensor::from_f32(vec![0], vec![]),
+    ]
+  }
+
+  #[test]
+  fn round_trip() {
+    let dir = temp_dir("round-trip");
+    let path = dir.join("000000.tensor");
+    write_tensors(&path, &sample()).unwrap();
+    assert_eq!(read_tensors(&path).unwrap(), sample());
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn rejects_truncated_file() {
+    let dir = temp_dir("truncated");
+    let path = dir.join("000000.tensor");
+    write_tensors(&path, &sample()).unwrap();
+    let data = std::fs::read(&path).unwrap();
+    for len in [0, 7, 12, 30, data.len() - 1] {
+      std::fs::write(&path, &data[..len]).unwrap();
+      assert!(read_tensors(&path).is_err(), "截断到 {} 字节", len);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn rejects_corrupt_lengths() {
+    let dir = temp_dir("corrupt");
+    let path = dir.join("000000.tensor");
+    write_tensors(&path, &[Tensor::from_f32(vec![2, 2], vec![1.0; 4])]).unwrap();
+    let data = std::fs::read(&path).unwrap();
+    // 头部 12 字节，张量头部 14 字节后为两个维度与元素数量
+    let len_offset = 12 + 14 + 16;
+
+    let mut huge = data.clone();
+    huge[len_offset..len_offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+    std::fs::write(&path, &huge).unwrap();
+    assert!(matches!(
+      read_tensors(&path),
+      Err(ReplayError::InvalidFile(_))
+    ));
+
+    let mut mismatch = data.clone();
+    mismatch[len_offset..len_offset + 8].copy_from_slice(&3u64.to_le_bytes());
+    std::fs::write(&path, &mismatch).unwrap();
+    assert!(matches!(
+      read_tensors(&path),
+      Err(ReplayError::InvalidFile(_))
+    ));
+
+    let mut count = data.clone();
+    count[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+    std::fs::write(&path, &count).unwrap();
+    assert!(matches!(
+      read_tensors(&path),
+      Err(ReplayError::InvalidFile(_))
+    ));
+
+    let mut ndim = data;
+    ndim[12 + 10..12 + 14].copy_from_slice(&u32::MAX.to_le_bytes());
+    std::fs::write(&path, &ndim).unwrap();
+    assert!(matches!(
+      read_tensors(&path),
+      Err(ReplayError::InvalidFile(_))
+    ));
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn replays_in_order() {
+    let dir = temp_dir("replay");
+    std::fs::write(
+      dir.join(REPLAY_MODEL_URL_FILE),
+      "yolov8:///models/yolov8n.rknn?object_thresh=0.5",
+    )
+    .unwrap();
+    for i in 0..3 {
+      let tensors = vec![Tensor::from_f32(vec![1], vec![i as f32])];
+      write_tensors(&dir.join(format!("{:06}.tensor", i)), &tensors).unwrap();
+    }
+
+    let url = Url::parse(&format!("replay://{}?object_thresh=0.25", dir.display())).unwrap();
+    let builder = ReplayBuilder::from_url(&url).unwrap();
+    let model_url = builder.model_url().unwrap();
+    assert_eq!(model_url.scheme(), "yolov8");
+    assert_eq!(model_url.query(), Some("object_thresh=0.25"));
+
+    let model = builder.build_model::<640, 640, ()>().unwrap();
+    for i in 0..3 {
+      assert_eq!(model.infer(&()).unwrap()[0].as_f32(), Some(&[i as f32][..]));
+    }
+    assert!(matches!(model.infer(&()), Err(ReplayError::Exhausted)));
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  /// 返回第一个张量的第一个值
+  struct First;
+
+  impl Postprocess for First {
+    type Input = Tensors;
+    type Output = f32;
+    type Error = ReplayError;
+
+    fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+      Ok(output[0].to_f32()[0])
+    }
+  }
+
+  #[test]
+  fn records_before_postprocess() {
+    let dir = temp_dir("record");
+    let url = Url::parse(&format!(
+      "yolov8:///models/yolov8n.rknn?object_thresh=0.5&record={}",
+      dir.display()
+    ))
+    .unwrap();
+    let post = TensorRecorder::from_url(&url)
+      .unwrap()
+      .apply(First)
+      .unwrap();
+    for i in 0..3 {
+      let value = post
+        .process(vec![Tensor::from_f32(vec![1], vec![i as f32])])
+        .unwrap();
+      assert_eq!(value, i as f32);
+    }
+
+    assert_eq!(
+      std::fs::read_to_string(dir.join(REPLAY_MODEL_URL_FILE)).unwrap(),
+      "yolov8:///models/yolov8n.rknn?object_thresh=0.5"
+    );
+    for i in 0..3 {
+      let tensors = read_tensors(&dir.join(format!("{:06}.tensor", i))).unwrap();
+      assert_eq!(tensors[0].as_f32(), Some(&[i as f32][..]));
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+}
@@ -0,0 +1,399 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/rknn_model.rs - RKNN 模型推理
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # RKNN 模型推理
+//!
+//! 加载 RKNN 模型并在 NPU 上执行推理，输出转换为 [`Tensors`]。
+//! 不同的检测头（Yolo26、YOLOv8 等）共用该模型，只在后处理上有所区别。
+//!
+//! 加载时查询输入输出张量的属性（[`ModelInfo`]），并检查模型输入大小与流水线一致。
+//!
+//! 所有输出均为 int8/uint8 仿射量化时直接读取量化数据，连同 scale/zero_point 交给后处理，
+//! 不在 CPU 上反量化整个输出；其他模型读取反量化后的浮点输出。
+
+use rknpu::{Context, InitFlags, QuantType, TensorAttr, TensorFormat, TensorType};
+use thiserror::Error;
+use tracing::{debug, error, info};
+
+use crate::{
+  input::AsNhwcFrame,
+  model::{
+    BatchModel, Model, ModelInfo, ModelInfoError, Quantization, Tensor, TensorInfo, TensorLayout,
+    Tensors,
+  },
+};
+
+const RKNN_NUM_INPUTS: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum RknnModelError {
+  #[error("模型加载错误: {0}")]
+  ModelLoadError(#[from] std::io::Error),
+  #[error("模型无效: {0}, 错误: {1}")]
+  ModelInvalid(String, rknpu::Error),
+  #[error("RKNN 错误: {0}")]
+  RknnError(#[from] rknpu::Error),
+  #[error("模型与流水线不匹配: {0}")]
+  Mismatch(#[from] ModelInfoError),
+  #[error("输出 {index} 的大小 {len} 无法按批大小 {batch} 拆分")]
+  BatchMismatch {
+    index: usize,
+    len: usize,
+    batch: usize,
+  },
+}
+
+impl RknnModelError {
+  pub fn invalid(msg: &str, e: rknpu::Error) -> Self {
+    RknnModelError::ModelInvalid(msg.to_string(), e)
+  }
+}
+
+pub struct RknnModel<const W: u32, const H: u32, Frame> {
+  context: Context,
+  info: ModelInfo,
+  /// 每个输出的量化类型，全部为 int8/uint8 量化时为 `Some`
+  raw_outputs: Option<Vec<RawOutput>>,
+  _phantom: std::marker::PhantomData<Frame>,
+}
+
+/// 不经反量化直接读取的输出
+#[derive(Debug, Clone, Copy)]
+enum RawOutput {
+  I8(Quantization),
+  U8(Quantization),
+}
+
+fn raw_output(attr: &TensorAttr) -> Option<RawOutput> {
+  let quantization = match attr.quant_type() {
+    QuantType::AffineAsymmetric => Quantization::new(attr.scale(), attr.zero_point()),
+    _ => return None,
+  };
+  match attr.data_type() {
+    TensorType::Int8 => Some(RawOutput::I8(quantization)),
+    TensorType::UInt8 => Some(RawOutput::U8(quantization)),
+    _ => None,
+  }
+}
+
+/// 加载模型文件并创建推理上下文
+fn create_context(model_path: &str, flags: InitFlags) -> Result<Context, RknnModelError> {
+  info!("加载模型文件: {}", model_path);
+  let mode_data = std::fs::read(model_path)?;
+  debug!(
+    "模型文件大小: {:.2} MB",
+    mode_data.len() as f64 / (1024.0 * 1024.0)
+  );
+
+  info!("创建 RKNN 推理上下文");
+  let context = Context::new(&mode_data, flags)?;
+  info!("模型加载完成");
+  Ok(context)
+}
+
+/// 查询 SDK 版本与输入输出张量属性
+fn query_info(context: &Context) -> Result<ModelInfo, RknnModelError> {
+  let version = context.sdk_version().map_err(|e| {
+    error!(" 查询 SDK 版本失败: {}", e);
+    RknnModelError::invalid("无法查询 SDK 版本", e)
+  })?;
+  let api_version = version.api_version().ok().map(|v| v.to_string());
+  let driver_version = version.driver_version().ok().map(|v| v.to_string());
+  if let Some(api_ver) = &api_version {
+    debug!("模型 API 版本: {}", api_ver);
+  }
+  if let Some(drv_ver) = &driver_version {
+    debug!("模型驱动版本: {}", drv_ver);
+  }
+
+  let num_inputs = context
+    .num_inputs()
+    .map_err(|e| RknnModelError::invalid("无法获取输入数量", e))?;
+  let num_outputs = context
+    .num_outputs()
+    .map_err(|e| RknnModelError::invalid("无法获取输出数量", e))?;
+
+  let inputs = (0..num_inputs)
+    .map(|i| {
+      context
+        .input_attr(i)
+        .map(|attr| tensor_info(&attr))
+        .map_err(|e| RknnModelError::invalid("无法获取输入属性", e))
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+  let outputs = (0..num_outputs)
+    .map(|i| {
+      context
+        .output_attr(i)
+        .map(|attr| tensor_info(&attr))
+        .map_err(|e| RknnModelError::invalid("无法获取输出属性", e))
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+
+  Ok(ModelInfo {
+    api_version,
+    driver_version,
+    inputs,
+    outputs,
+  })
+}
+
+fn tensor_info(attr: &TensorAttr) -> TensorInfo {
+  TensorInfo {
+    index: attr.index(),
+    name: attr.name().to_string(),
+    shape: attr.dims().iter().map(|d| *d as usize).collect(),
+    layout: match attr.format() {
+      TensorFormat::NCHW => TensorLayout::Nchw,
+      TensorFormat::NHWC => TensorLayout::Nhwc,
+      _ => TensorLayout::Undefined,
+    },
+    dtype: format!("{:?}", attr.data_type()),
+    quantization: match attr.quant_type() {
+      QuantType::AffineAsymmetric => Some(Quantization::new(attr.scale(), attr.zero_point())),
+      _ => None,
+    },
+  }
+}
+
+/// 只读取模型的输入输出信息，不检查输入大小
+pub fn inspect_rknn(model_path: &str, flags: InitFlags) -> Result<ModelInfo, RknnModelError> {
+  let context = create_context(model_path, flags)?;
+  query_info(&context)
+}
+
+impl<const W: u32, const H: u32, Frame> RknnModel<W, H, Frame> {
+  /// 加载 RKNN 模型
+  ///
+  /// `num_outputs` 为 `Some` 时检查模型输出数量。
+  pub fn load(
+    model_path: &str,
+    flags: InitFlags,
+    num_outputs: Option<u32>,
+  ) -> Result<Self, RknnModelError> {
+    let context = create_context(model_path, flags)?;
+    let info = query_info(&context)?;
+    let actual_inputs = info.inputs.len() as u32;
+    let actual_outputs = info.outputs.len() as u32;
+
+    if actual_inputs != RKNN_NUM_INPUTS {
+      error!(
+        "预期模型输入数量为 {}, 实际为 {}",
+        RKNN_NUM_INPUTS, actual_inputs
+      );
+      return Err(RknnModelError::invalid(
+        &format!(
+          "预期模型输入数量为 {}, 实际为 {}",
+          RKNN_NUM_INPUTS, actual_inputs
+        ),
+        rknpu::Error::InvalidModel,
+      ));
+    }
+
+    if let Some(num_outputs) = num_outputs.filter(|n| *n != actual_outputs) {
+      error!(
+        "预期模型输出数量为 {}, 实际为 {}",
+        num_outputs, actual_outputs
+      );
+      return Err(RknnModelError::invalid(
+        &format!(
+          "预期模型输出数量为 {}, 实际为 {}",
+          num_outputs, actual_outputs
+        ),
+        rknpu::Error::InvalidModel,
+      ));
+    }
+
+    debug!("模型输入数量: {}", actual_inputs);
+    debug!("模型输出数量: {}", actual_outputs);
+    debug!("模型信息:\n{}", info);
+
+    if let Err(e) = info.check_input_size(W as usize, H as usize) {
+      error!("{}", e);
+      return Err(e.into());
+    }
+
+    let raw_outputs = (0..actual_outputs)
+      .map(|i| context.output_attr(i).map(|attr| raw_output(&attr)))
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(|e| RknnModelError::invalid("无法获取输出属性", e))?
+      .into_iter()
+      .collect::<Option<Vec<_>>>();
+    if raw_outputs.is_some() {
+      debug!("模型输出均为量化张量，直接读取量化数据");
+    }
+
+    let _phantom = std::marker::PhantomData;
+    Ok(RknnModel {
+      context,
+      info,
+      raw_outputs,
+      _phantom,
+    })
+  }
+
+  /// 模型的输入输出信息
+  pub fn info(&self) -> &ModelInfo {
+    &self.info
+  }
+
+  /// 第 `index` 个输出中单帧的形状，`batch` 大于 1 时第一维为批大小，单帧形状的第一维取 1
+  ///
+  /// 模型未报告形状或形状与数据长度不一致时按一维张量保存。
+  fn output_shape(&self, index: usize, batch: usize, len: usize) -> Vec<usize> {
+    let mut shape = self
+      .info
+      .outputs
+      .get(index)
+      .map(|output| output.shape.clone())
+      .unwrap_or_default();
+    if batch > 1 && shape.first() == Some(&batch) {
+      shape[0] = 1;
+    }
+    if shape.is_empty() || shape.iter().product::<usize>() != len {
+      debug!(
+        "输出 {} 的形状 {:?} 与数据长度 {} 不一致",
+        index, shape, len
+      );
+      return vec![len];
+    }
+    shape
+  }
+
+  /// 读取输出并按第一维拆分为 `frames` 帧，`batch` 为本次推理的批大小
+  fn read_outputs(&self, batch: usize, frames: usize) -> Result<Vec<Tensors>, RknnModelError> {
+    let mut outputs: Vec<Tensors> = (0..frames)
+      .map(|_| Vec::with_capacity(self.info.outputs.len()))
+      .collect();
+    match &self.raw_outputs {
+      Some(raw_outputs) => {
+        let output = self.context.get_raw_outputs()?;
+        debug!("模型推理结果：{:?}", output);
+        for (i, raw) in raw_outputs.iter().enumerate() {
+          match raw {
+            RawOutput::I8(q) => {
+              self.split_output(i, batch, output.get_i8(i)?, &mut outputs, |shape, data| {
+                Tensor::from_i8(shape, data.to_vec(), *q)
+              })?
+            }
+            RawOutput::U8(q) => {
+              self.split_output(i, batch, output.get_u8(i)?, &mut outputs, |shape, data| {
+                Tensor::from_u8(shape, data.to_vec(), *q)
+              })?
+            }
+          }
+        }
+      }
+      None => {
+        let output = self.context.get_outputs()?;
+        debug!("模型推理结果：{:?}", output);
+        for i in 0..output.len() {
+          self.split_output(i, batch, output.get_f32(i)?, &mut outputs, |shape, data| {
+            Tensor::from_f32(shape, data.to_vec())
+          })?;
+        }
+      }
+    }
+    Ok(outputs)
+  }
+
+  /// 将第 `index` 个输出按帧拆分，追加到每帧的输出中
+  fn split_output<V>(
+    &self,
+    index: usize,
+    batch: usize,
+    data: &[V],
+    outputs: &mut [Tensors],
+    make: impl Fn(Vec<usize>, &[V]) -> Tensor,
+  ) -> Result<(), RknnModelError> {
+    if batch == 0 || data.len() % batch != 0 {
+      return Err(RknnModelError::BatchMismatch {
+        index,
+        len: data.len(),
+        batch,
+      });
+    }
+    let len = data.len() / batch;
+    let shape = self.output_shape(index, batch, len);
+    for (frame, tensors) in outputs.iter_mut().enumerate() {
+      tensors.push(make(shape.clone(), &data[frame * len..(frame + 1) * len]));
+    }
+    Ok(())
+  }
+
+  /// 模型导出时的批大小，即输入张量的第一维
+  pub fn batch_size(&self) -> usize {
+    self
+      .info
+      .inputs
+      .first()
+      .and_then(|input| input.shape.first().copied())
+      .filter(|n| *n > 0)
+      .unwrap_or(1)
+  }
+}
+
+impl<const W: u32, const H: u32, Frame: AsNhwcFrame<W, H>> Model for RknnModel<W, H, Frame> {
+  type Input = Frame;
+  type Output = Tensors;
+  type Error = RknnModelError;
+
+  fn infer(&self, input: &Self::Input) -> Result<Self::Output, Self::Error> {
+    // 设置输入
+    debug!("设置模型输入");
+    self.context.set_input(
+      0,
+      input.as_nhwc(),
+      rknpu::TensorFormat::NHWC,
+      TensorType::UInt8,
+    )?;
+
+    // 执行推理
+    debug!("执行模型推理");
+    self.context.run()?;
+
+    // 获取输出
+    debug!("获取模型输出");
+    let mut outputs = self.read_outputs(1, 1)?;
+    Ok(outputs.pop().unwrap_or_default())
+  }
+}
+
+impl<const W: u32, const H: u32, Frame: AsNhwcFrame<W, H>> BatchModel for RknnModel<W, H, Frame> {
+  /// 多批次模型一次推理一批，输出按第一维拆分到每帧；单批次模型逐帧推理
+  fn infer_batch(&self, inputs: &[Self::Input]) -> Result<Vec<Self::Output>, Self::Error> {
+    let batch = self.batch_size();
+    if batch <= 1 {
+      return inputs.iter().map(|input| self.infer(input)).collect();
+    }
+
+    let mut outputs = Vec::with_capacity(inputs.len());
+    for chunk in inputs.chunks(batch) {
+      // 不足一批时以全零帧补齐，补齐部分的输出丢弃
+      let frame_len = chunk[0].as_nhwc().len();
+      let mut data = vec![0u8; frame_len * batch];
+      for (input, slot) in chunk.iter().zip(data.chunks_mut(frame_len)) {
+        slot.copy_from_slice(input.as_nhwc());
+      }
+
+      debug!("批量推理 {} 帧，批大小 {}", chunk.len(), batch);
+      self
+        .context
+        .set_input(0, &data, rknpu::TensorFormat::NHWC, TensorType::UInt8)?;
+      self.context.run()?;
+      outputs.extend(self.read_outputs(batch, chunk.len())?);
+    }
+
+    Ok(outputs)
+  }
+}
@@ -0,0 +1,273 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/runtime_label.rs - 运行时加载的类别表
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 运行时加载的类别表
+//!
+//! [`CocoLabel`](crate::model::CocoLabel) 等类别在编译期由 `#[toml_label]` 生成。[`RuntimeLabel`]
+//! 的类别名称与数量在运行时从文件加载，自定义训练的模型无需重新编译即可使用。
+//!
+//! 类别表为进程内全局共享，需在构建后处理之前通过 [`RuntimeLabel::install`] 安装。
+//! `RuntimeLabel::LABEL_NUM` 为 0，表示类别数量在运行时确定，后处理通过 [`label_num`]
+//! 获取类别数量。
+//!
+//! ## 文件格式
+//!
+//! 按扩展名识别：
+//!
+//! - `.toml`: 与 `labels/coco.toml` 相同，`"名称" = 编号`
+//! - `.json`: 名称数组 `["person", "bicycle"]`，或名称到编号的对象 `{"person": 0}`
+//! - 其他: 纯文本，每行一个名称，行号（从 0 开始）为编号，忽略空行
+//!
+//! 编号不连续时，缺少名称的编号以编号本身作为名称。编号不能超过 65535，
+//! 避免错误的编号分配过大的类别表。
+//!
+//! ## URL 参数
+//!
+//! - `labels`: 类别文件路径，在模型 URL 上指定
+
+use std::sync::RwLock;
+
+use thiserror::Error;
+use url::Url;
+
+use crate::{FromUrl, model::WithLabel};
+
+static LABELS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// 类别编号的上限
+const MAX_LABEL_ID: u32 = 65_535;
+
+#[derive(Error, Debug)]
+pub enum RuntimeLabelError {
+  #[error("类别文件读取错误: {0}")]
+  IoError(#[from] std::io::Error),
+  #[error("类别文件 TOML 解析错误: {0}")]
+  TomlError(#[from] toml::de::Error),
+  #[error("类别文件 JSON 解析错误: {0}")]
+  JsonError(#[from] serde_json::Error),
+  #[error("无效的类别文件: {0}")]
+  InvalidFile(String),
+}
+
+/// 运行时加载的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RuntimeLabel {
+  id: u32,
+}
+
+impl WithLabel for RuntimeLabel {
+  const LABEL_NUM: u32 = 0;
+
+  fn from_label_id(id: u32) -> Self {
+    Self { id }
+  }
+
+  fn to_label_id(&self) -> u32 {
+    self.id
+  }
+
+  fn to_label_str(&self) -> String {
+    LABELS
+      .read()
+      .ok()
+      .and_then(|labels| labels.get(self.id as usize).cloned())
+      .unwrap_or_else(|| self.id.to_string())
+  }
+}
+
+impl RuntimeLabel {
+  /// 安装全局类别表，替换之前安装的类别表
+  pub fn install(labels: LabelSet) {
+    *LABELS.write().unwrap_or_else(|e| e.into_inner()) = labels.names;
+  }
+
+  /// 安装类别表，类别表为空时安装编译期类别 `T`
+  pub fn install_or<T: WithLabel>(labels: LabelSet) {
+    if labels.is_empty() {
+      Self::install(LabelSet::from_label::<T>());
+    } else {
+      Self::install(labels);
+    }
+  }
+
+  /// 已安装的类别数量
+  pub fn installed_num() -> usize {
+    LABELS.read().map(|labels| labels.len()).unwrap_or(0)
+  }
+}
+
+/// 类别数量，`LABEL_NUM` 为 0 的类别（[`RuntimeLabel`]）使用已安装的类别表
+pub fn label_num<T: WithLabel>() -> usize {
+  if T::LABEL_NUM == 0 {
+    RuntimeLabel::installed_num()
+  } else {
+    T::LABEL_NUM as usize
+  }
+}
+
+/// 类别名称表，下标为类别编号
+#[derive(Debug, Clone, Default)]
+pub struct LabelSet {
+  names: Vec<String>,
+}
+
+impl FromUrl for LabelSet {
+  type Error = RuntimeLabelError;
+
+  /// 从模型 URL 的 `labels` 参数加载，未设置时返回空表
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    match url.query_pairs().find(|(k, _)| k == "labels") {
+      Some((_, path)) => Self::load(path.as_ref()),
+      None => Ok(Self::default()),
+    }
+  }
+}
+
+impl LabelSet {
+  /// 从编译期类别复制类别表
+  pub fn from_label<T: WithLabel>() -> Self {
+    Self {
+      names: (0..T::LABEL_NUM)
+        .map(|id| T::from_label_id(id).to_label_str())
+        .collect(),
+    }
+  }
+
+  /// 按扩展名从 TOML、JSON 或纯文本文件加载
+  pub fn load(path: &str) -> Result<Self, RuntimeLabelError> {
+    let content = std::fs::read_to_string(path)?;
+    let labels = match std::path::Path::new(path)
+      .extension()
+      .and_then(|ext| ext.to_str())
+    {
+      Some("toml") => {
+        let table = content.parse::<toml::Table>()?;
+        let pairs = table
+          .iter()
+          .map(|(name, value)| {
+            let id = value
+              .as_integer()
+              .and_then(|id| u32::try_from(id).ok())
+              .ok_or_else(|| RuntimeLabelError::InvalidFile(format!("{} = {}", name, value)))?;
+            Ok((name.clone(), id))
+          })
+          .collect::<Result<Vec<_>, RuntimeLabelError>>()?;
+        Self::from_pairs(pairs)?
+      }
+      Some("json") => match serde_json::from_str::<serde_json::Value>(&content)? {
+        serde_json::Value::Array(names) => Self {
+          names: names
+            .iter()
+            .map(|v| {
+              v.as_str()
+                .map(String::from)
+                .ok_or_else(|| RuntimeLabelError::InvalidFile(format!("{} 不是字符串", v)))
+            })
+            .collect::<Result<_, _>>()?,
+        },
+        serde_json::Value::Object(map) => {
+          let pairs = map
+            .iter()
+            .map(|(name, value)| {
+              let id = value
+                .as_u64()
+                .and_then(|id| u32::try_from(id).ok())
+                .ok_or_else(|| RuntimeLabelError::InvalidFile(format!("{}: {}", name, value)))?;
+              Ok((name.clone(), id))
+            })
+            .collect::<Result<Vec<_>, RuntimeLabelError>>()?;
+          Self::from_pairs(pairs)?
+        }
+        other => {
+          return Err(RuntimeLabelError::InvalidFile(format!(
+            "JSON 类别文件必须是数组或对象: {}",
+            other
+          )));
+        }
+      },
+      _ => Self {
+        names: content
+          .lines()
+          .map(str::trim)
+          .filter(|line| !line.is_empty())
+          .map(String::from)
+          .collect(),
+      },
+    };
+
+    if labels.is_empty() {
+      return Err(RuntimeLabelError::InvalidFile(format!(
+        "{}: 没有类别",
+        path
+      )));
+    }
+    Ok(labels)
+  }
+
+  fn from_pairs(pairs: Vec<(String, u32)>) -> Result<Self, RuntimeLabelError> {
+    if let Some((name, id)) = pairs.iter().find(|(_, id)| *id > MAX_LABEL_ID) {
+      return Err(RuntimeLabelError::InvalidFile(format!(
+        "类别 {} 的编号 {} 超过上限 {}",
+        name, id, MAX_LABEL_ID
+      )));
+    }
+
+    let num = pairs
+      .iter()
+      .map(|(_, id)| *id as usize + 1)
+      .max()
+      .unwrap_or(0);
+    let mut names: Vec<String> = (0..num).map(|id| id.to_string()).collect();
+    for (name, id) in pairs {
+      names[id as usize] = name;
+    }
+    Ok(Self { names })
+  }
+
+  pub fn len(&self) -> usize {
+    self.names.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.names.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{LabelSet, MAX_LABEL_ID};
+
+  fn pairs(ids: &[u32]) -> Vec<(String, u32)> {
+    ids.iter().map(|id| (format!("class{}", id), *id)).collect()
+  }
+
+  #[test]
+  fn fills_missing_ids() {
+    let labels = LabelSet::from_pairs(pairs(&[0, 2])).unwrap();
+    assert_eq!(labels.names, ["class0", "1", "class2"]);
+  }
+
+  #[test]
+  fn accepts_sparse_ids() {
+    let labels = LabelSet::from_pairs(pairs(&[0, 5, 90])).unwrap();
+    assert_eq!(labels.len(), 91);
+    assert_eq!(labels.names[90], "class90");
+    assert!(LabelSet::from_pairs(pairs(&[MAX_LABEL_ID])).is_ok());
+  }
+
+  #[test]
+  fn rejects_ids_above_cap() {
+    assert!(LabelSet::from_pairs(pairs(&[0, MAX_LABEL_ID + 1])).is_err());
+    assert!(LabelSet::from_pairs(pairs(&[u32::MAX])).is_err());
+  }
+}
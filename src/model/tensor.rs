@@ -0,0 +1,364 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/tensor.rs - 与推理后端无关的张量
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 模型输出张量
+//!
+//! 所有推理后端（RKNN、ONNX、回放）都将输出转换为 [`Tensor`]，
+//! 后处理只依赖该类型，因此同一个解码器可以服务于不同的后端。
+//!
+//! YOLOv8、YOLOv5 解码器通过 [`TensorView`] 直接读取 int8/uint8 量化输出：
+//! 阈值比较与类别最大值在量化域中完成，只有通过阈值的候选才会被反量化。
+
+use std::borrow::Cow;
+
+/// 张量数据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TensorDType {
+  F32,
+  U8,
+  I8,
+}
+
+/// 仿射量化参数，`real = (q - zero_point) * scale`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantization {
+  pub scale: f32,
+  pub zero_point: i32,
+}
+
+impl Quantization {
+  pub fn new(scale: f32, zero_point: i32) -> Self {
+    Self { scale, zero_point }
+  }
+
+  #[inline]
+  pub fn dequantize(&self, q: i32) -> f32 {
+    (q - self.zero_point) as f32 * self.scale
+  }
+
+  /// 不大于 `value` 对应量化值的最大整数，`q > quantize_floor(v)` 等价于 `dequantize(q) > v`
+  #[inline]
+  pub fn quantize_floor(&self, value: f32) -> i32 {
+    let q = (value / self.scale).floor() as i32 + self.zero_point;
+    // 修正除法的舍入误差，保证与 `dequantize` 的比较结果一致
+    if self.dequantize(q) > value {
+      q - 1
+    } else if self.dequantize(q + 1) <= value {
+      q + 1
+    } else {
+      q
+    }
+  }
+}
+
+/// 张量数据
+#[derive(Debug, Clone, PartialEq)]
+pub enum TensorData {
+  F32(Vec<f32>),
+  U8(Vec<u8>),
+  I8(Vec<i8>),
+}
+
+/// 与推理后端无关的张量
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tensor {
+  pub shape: Vec<usize>,
+  pub quantization: Option<Quantization>,
+  pub data: TensorData,
+}
+
+/// 张量数据的只读视图
+///
+/// 量化张量按元素读取时才反量化，比较与求最大值直接在量化域中进行，
+/// 解码时只需要反量化通过阈值的候选。
+#[derive(Debug, Clone, Copy)]
+pub enum TensorView<'a> {
+  F32(&'a [f32]),
+  U8(&'a [u8], Quantization),
+  I8(&'a [i8], Quantization),
+}
+
+/// 模型的全部输出张量
+pub type Tensors = Vec<Tensor>;
+
+impl Tensor {
+  pub fn from_f32(shape: Vec<usize>, data: Vec<f32>) -> Self {
+    Self {
+      shape,
+      quantization: None,
+      data: TensorData::F32(data),
+    }
+  }
+
+  pub fn from_u8(shape: Vec<usize>, data: Vec<u8>, quantization: Quantization) -> Self {
+    Self {
+      shape,
+      quantization: Some(quantization),
+      data: TensorData::U8(data),
+    }
+  }
+
+  pub fn from_i8(shape: Vec<usize>, data: Vec<i8>, quantization: Quantization) -> Self {
+    Self {
+      shape,
+      quantization: Some(quantization),
+      data: TensorData::I8(data),
+    }
+  }
+
+  pub fn dtype(&self) -> TensorDType {
+    match &self.data {
+      TensorData::F32(_) => TensorDType::F32,
+      TensorData::U8(_) => TensorDType::U8,
+      TensorData::I8(_) => TensorDType::I8,
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    match &self.data {
+      TensorData::F32(data) => data.len(),
+      TensorData::U8(data) => data.len(),
+      TensorData::I8(data) => data.len(),
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// 浮点数据，量化张量返回 `None`
+  pub fn as_f32(&self) -> Option<&[f32]> {
+    match &self.data {
+      TensorData::F32(data) => Some(data),
+      _ => None,
+    }
+  }
+
+  pub fn view(&self) -> TensorView<'_> {
+    let quantization = self.quantization.unwrap_or(Quantization::new(1.0, 0));
+    match &self.data {
+      TensorData::F32(data) => TensorView::F32(data),
+      TensorData::U8(data) => TensorView::U8(data, quantization),
+      TensorData::I8(data) => TensorView::I8(data, quantization),
+    }
+  }
+
+  /// 转换为浮点数据，量化张量会按量化参数反量化
+  pub fn to_f32(&self) -> Cow<'_, [f32]> {
+    let quantization = self.quantization.unwrap_or(Quantization::new(1.0, 0));
+    match &self.data {
+      TensorData::F32(data) => Cow::Borrowed(data),
+      TensorData::U8(data) => Cow::Owned(
+        data
+          .iter()
+          .map(|q| quantization.dequantize(*q as i32))
+          .collect(),
+      ),
+      TensorData::I8(data) => Cow::Owned(
+        data
+          .iter()
+          .map(|q| quantization.dequantize(*q as i32))
+          .collect(),
+      ),
+    }
+  }
+}
+
+impl TensorView<'_> {
+  pub fn len(&self) -> usize {
+    match self {
+      TensorView::F32(data) => data.len(),
+      TensorView::U8(data, _) => data.len(),
+      TensorView::I8(data, _) => data.len(),
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  pub fn is_quantized(&self) -> bool {
+    !matches!(self, TensorView::F32(_))
+  }
+
+  /// 连续子区间的视图，用于拆分拼接在一起的输出
+  pub fn slice(&self, range: std::ops::Range<usize>) -> Self {
+    match self {
+      TensorView::F32(data) => TensorView::F32(&data[range]),
+      TensorView::U8(data, q) => TensorView::U8(&data[range], *q),
+      TensorView::I8(data, q) => TensorView::I8(&data[range], *q),
+    }
+  }
+
+  /// 读取并反量化单个元素
+  #[inline]
+  pub fn get(&self, i: usize) -> f32 {
+    match self {
+      TensorView::F32(data) => data[i],
+      TensorView::U8(data, q) => q.dequantize(data[i] as i32),
+      TensorView::I8(data, q) => q.dequantize(data[i] as i32),
+    }
+  }
+
+  /// 判断元素是否大于阈值，量化张量将阈值转换到量化域后比较
+  #[inline]
+  pub fn exceeds(&self, i: usize, thresh: f32) -> bool {
+    match self {
+      TensorView::F32(data) => data[i] > thresh,
+      TensorView::U8(data, q) => data[i] as i32 > q.quantize_floor(thresh),
+      TensorView::I8(data, q) => data[i] as i32 > q.quantize_floor(thresh),
+    }
+  }
+
+  /// 在 `offset + k * stride`（`k < count`）中查找最大值，返回 (k, 反量化后的值)
+  pub fn argmax(&self, offset: usize, count: usize, stride: usize) -> (usize, f32) {
+    fn find<V: Copy + PartialOrd>(data: &[V], offset: usize, count: usize, stride: usize) -> usize {
+      let mut best = 0;
+      for k in 1..count {
+        if data[offset + k * stride] > data[offset + best * stride] {
+          best = k;
+        }
+      }
+      best
+    }
+
+    let best = match self {
+      TensorView::F32(data) => find(data, offset, count, stride),
+      TensorView::U8(data, _) => find(data, offset, count, stride),
+      TensorView::I8(data, _) => find(data, offset, count, stride),
+    };
+    (best, self.get(offset + best * stride))
+  }
+
+  /// 与 [`argmax`](Self::argmax) 相同，但跳过 `allowed[k]` 为假的位置，全部跳过时返回 `None`
+  pub fn argmax_allowed(
+    &self,
+    offset: usize,
+    count: usize,
+    stride: usize,
+    allowed: &[bool],
+  ) -> Option<(usize, f32)> {
+    fn find<V: Copy + PartialOrd>(
+      data: &[V],
+      offset: usize,
+      count: usize,
+      stride: usize,
+      allowed: &[bool],
+    ) -> Option<usize> {
+      let mut best: Option<usize> = None;
+      for k in (0..count).filter(|k| allowed.get(*k).copied().unwrap_or(true)) {
+        if best.is_none_or(|b| data[offset + k * stride] > data[offset + b * stride]) {
+          best = Some(k);
+        }
+      }
+      best
+    }
+
+    let best = match self {
+      TensorView::F32(data) => find(data, offset, count, stride, allowed),
+      TensorView::U8(data, _) => find(data, offset, count, stride, allowed),
+      TensorView::I8(data, _) => find(data, offset, count, stride, allowed),
+    }?;
+    Some((best, self.get(offset + best * stride)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Quantization, Tensor};
+
+  const QUANTIZATIONS: [(f32, i32); 4] = [(0.003_921_569, -128), (0.1, 3), (0.017, 0), (1.5, -7)];
+
+  /// 覆盖量化网格点及其两侧的阈值
+  fn thresholds(q: Quantization) -> impl Iterator<Item = f32> {
+    (-130..130).flat_map(move |v| {
+      let value = q.dequantize(v);
+      [value, value - q.scale / 2.0, value + q.scale / 3.0]
+    })
+  }
+
+  #[test]
+  fn quantization_round_trip() {
+    for (scale, zero_point) in QUANTIZATIONS {
+      let q = Quantization::new(scale, zero_point);
+      for v in -128..=255 {
+        assert_eq!(q.quantize_floor(q.dequantize(v)), v, "{:?} {}", q, v);
+      }
+      for thresh in thresholds(q) {
+        for v in -128..=255 {
+          assert_eq!(v > q.quantize_floor(thresh), q.dequantize(v) > thresh);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn exceeds_matches_dequantized() {
+    for (scale, zero_point) in QUANTIZATIONS {
+      let q = Quantization::new(scale, zero_point);
+      let i8_tensor = Tensor::from_i8(vec![256], (-128..=127).collect(), q);
+      let u8_tensor = Tensor::from_u8(vec![256], (0..=255).collect(), q);
+      for tensor in [i8_tensor, u8_tensor] {
+        let view = tensor.view();
+        let values = tensor.to_f32();
+        for thresh in thresholds(q) {
+          for (i, value) in values.iter().enumerate() {
+            assert_eq!(view.exceeds(i, thresh), *value > thresh);
+          }
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn argmax_matches_dequantized() {
+    let q = Quantization::new(0.05, -20);
+    // 3 个候选、4 个类别，类别按步长 3 排列
+    let raw: Vec<i8> = vec![-5, 7, 100, 3, -128, 99, 3, 50, 99, 127, 2, 0];
+    let i8_tensor = Tensor::from_i8(vec![4, 3], raw.clone(), q);
+    let u8_tensor = Tensor::from_u8(
+      vec![4, 3],
+      raw.iter().map(|v| (*v as i32 + 128) as u8).collect(),
+      Quantization::new(0.05, 108),
+    );
+    for tensor in [i8_tensor, u8_tensor] {
+      let view = tensor.view();
+      let f32_tensor = Tensor::from_f32(vec![4, 3], tensor.to_f32().into_owned());
+      for idx in 0..3 {
+        let expected = f32_tensor.view().argmax(idx, 4, 3);
+        assert_eq!(view.argmax(idx, 4, 3), expected);
+      }
+      assert_eq!(view.argmax(0, 4, 3).0, 3);
+      assert_eq!(view.argmax(1, 4, 3).0, 2);
+      assert_eq!(view.argmax(2, 4, 3).0, 0);
+    }
+  }
+
+  #[test]
+  fn argmax_allowed_skips_disallowed() {
+    let q = Quantization::new(0.5, -10);
+    let f32_tensor = Tensor::from_f32(vec![4], vec![0.9, 0.1, 0.6, 0.3]);
+    let i8_tensor = Tensor::from_i8(vec![4], vec![9, 1, 6, 3], q);
+    for tensor in [f32_tensor, i8_tensor] {
+      let view = tensor.view();
+      assert_eq!(view.argmax(0, 4, 1).0, 0);
+      assert_eq!(
+        view
+          .argmax_allowed(0, 4, 1, &[false, true, true, true])
+          .map(|(k, _)| k),
+        Some(2)
+      );
+      assert_eq!(view.argmax_allowed(0, 4, 1, &[false; 4]), None);
+    }
+  }
+}
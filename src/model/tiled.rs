@@ -0,0 +1,514 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/tiled.rs - 分块推理
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # 分块推理
+//!
+//! 高分辨率画面缩放到模型输入大小后，小目标往往无法检出。[`Tiled`] 将大画面切分为互相重叠的分块，
+//! 每个分块缩放到模型输入大小后分别推理与后处理，再把结果映射回画面坐标；[`TileMerge`]
+//! 合并分块接缝处的重复检测。可选地再对整幅画面推理一次，用于检出跨越多个分块的大目标。
+//!
+//! 各分块的后处理在 [`Tiled`] 的推理阶段完成，流水线的后处理阶段只包含合并。
+//!
+//! ## 参数说明
+//!
+//! 参数从模型 URL 的查询参数中读取：
+//!
+//! ```text
+//! yolov8:///path/to/yolov8n.rknn?tile=640&tile_overlap=0.2&tile_merge=wbf&tile_full=true
+//! ```
+//!
+//! - `tile`: 分块在画面中的大小，形如 `640` 或 `800x600`，默认与模型输入大小相同
+//! - `tile_overlap`: 相邻分块的重叠比例，默认 0.2
+//! - `tile_merge`: 合并方式，`nms` 保留分数最高的框，`wbf` 按分数加权融合重叠框，默认 `nms`
+//! - `tile_iou`: 合并时判定为同一目标的交并比阈值，默认 0.5
+//! - `tile_full`: 是否额外对整幅画面推理，默认 `false`
+//!
+//! ## 使用
+//!
+//! ```rust,ignore
+//! let tiled = TiledBuilder::from_url(&model_url)?;
+//! let model: Tiled<3840, 2160, 640, 640, _, _> = tiled.apply(model, post);
+//! let post = tiled.build_postprocess::<CocoLabel>();
+//! ```
+
+use shanan_trait::Postprocess;
+use thiserror::Error;
+use tracing::debug;
+use url::Url;
+
+use crate::{
+  FromUrl,
+  frame::{RgbNhwcFrame, crop_resize},
+  input::AsNhwcFrame,
+  model::{BBox, DetectItem, DetectResult, Model, Nms, NmsMode, WithLabel},
+};
+
+const TILE_OVERLAP: f32 = 0.2;
+const TILE_IOU_THRESH: f32 = 0.5;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Error, Debug)]
+pub enum TiledError {
+  #[error("模型错误: {0}")]
+  ModelError(BoxError),
+  #[error("后处理错误: {0}")]
+  PostprocessError(BoxError),
+  #[error("无效的分块大小: {0}")]
+  InvalidTileSize(String),
+  #[error("未知的合并方式: {0}")]
+  InvalidMerge(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TileMergeMethod {
+  /// 保留分数最高的框
+  #[default]
+  Nms,
+  /// 按分数加权融合重叠框（Weighted Boxes Fusion）
+  Wbf,
+}
+
+/// 分块推理模型，`FW`、`FH` 为画面大小，`W`、`H` 为模型输入大小
+pub struct Tiled<const FW: u32, const FH: u32, const W: u32, const H: u32, M, P> {
+  model: M,
+  post: P,
+  tile: Option<(u32, u32)>,
+  overlap: f32,
+  full_frame: bool,
+}
+
+/// 合并分块之间的重复检测
+pub struct TileMerge<T> {
+  method: TileMergeMethod,
+  iou_thresh: f32,
+  _phantom: std::marker::PhantomData<T>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TiledBuilder {
+  tile: Option<(u32, u32)>,
+  overlap: f32,
+  method: TileMergeMethod,
+  iou_thresh: f32,
+  full_frame: bool,
+}
+
+impl Default for TiledBuilder {
+  fn default() -> Self {
+    Self {
+      tile: None,
+      overlap: TILE_OVERLAP,
+      method: TileMergeMethod::default(),
+      iou_thresh: TILE_IOU_THRESH,
+      full_frame: false,
+    }
+  }
+}
+
+fn parse_tile_size(value: &str) -> Option<(u32, u32)> {
+  let (w, h) = value.split_once('x').unwrap_or((value, value));
+  let (w, h) = (w.trim().parse().ok()?, h.trim().parse().ok()?);
+  (w > 0 && h > 0).then_some((w, h))
+}
+
+impl FromUrl for TiledBuilder {
+  type Error = TiledError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    let mut builder = TiledBuilder::default();
+    for (k, v) in url.query_pairs() {
+      match k.as_ref() {
+        "tile" => {
+          builder.tile =
+            Some(parse_tile_size(&v).ok_or_else(|| TiledError::InvalidTileSize(v.to_string()))?)
+        }
+        "tile_overlap" => builder.overlap = v.parse().unwrap_or(TILE_OVERLAP),
+        "tile_merge" => {
+          builder.method = match v.as_ref() {
+            "nms" => TileMergeMethod::Nms,
+            "wbf" => TileMergeMethod::Wbf,
+            other => return Err(TiledError::InvalidMerge(other.to_string())),
+          }
+        }
+        "tile_iou" => builder.iou_thresh = v.parse().unwrap_or(TILE_IOU_THRESH),
+        "tile_full" => builder.full_frame = v.parse().unwrap_or(false),
+        _ => {}
+      }
+    }
+    Ok(builder)
+  }
+}
+
+impl TiledBuilder {
+  /// 分块在画面中的大小，默认与模型输入大小相同
+  pub fn tile(mut self, width: u32, height: u32) -> Self {
+    self.tile = Some((width, height));
+    self
+  }
+
+  pub fn overlap(mut self, overlap: f32) -> Self {
+    self.overlap = overlap;
+    self
+  }
+
+  pub fn method(mut self, method: TileMergeMethod) -> Self {
+    self.method = method;
+    self
+  }
+
+  pub fn iou_thresh(mut self, iou_thresh: f32) -> Self {
+    self.iou_thresh = iou_thresh;
+    self
+  }
+
+  pub fn full_frame(mut self, full_frame: bool) -> Self {
+    self.full_frame = full_frame;
+    self
+  }
+
+  /// 为模型及其后处理添加分块推理
+  pub fn apply<const FW: u32, const FH: u32, const W: u32, const H: u32, M, P>(
+    &self,
+    model: M,
+    post: P,
+  ) -> Tiled<FW, FH, W, H, M, P> {
+    Tiled {
+      model,
+      post,
+      tile: self.tile,
+      overlap: self.overlap.clamp(0.0, 0.9),
+      full_frame: self.full_frame,
+    }
+  }
+
+  pub fn build_postprocess<T>(&self) -> TileMerge<T> {
+    TileMerge {
+      method: self.method,
+      iou_thresh: self.iou_thresh,
+      _phantom: std::marker::PhantomData,
+    }
+  }
+}
+
+/// 一个方向上各分块的起点，最后一个分块与画面边缘对齐
+fn tile_starts(frame: u32, tile: u32, overlap: f32) -> Vec<u32> {
+  if tile >= frame {
+    return vec![0];
+  }
+  let step = ((tile as f32 * (1.0 - overlap)) as u32).max(1);
+  let mut starts: Vec<u32> = (0..)
+    .map(|i| i * step)
+    .take_while(|s| s + tile < frame)
+    .collect();
+  starts.push(frame - tile);
+  starts
+}
+
+/// 将模型输入坐标系中的检测映射到画面坐标系，`(x, y)` 映射到 `(x * sx + ox, y * sy + oy)`
+///
+/// 横纵缩放比例不同时旋转边框只是近似。
+fn place<T>(item: &mut DetectItem<T>, (ox, oy): (f32, f32), (sx, sy): (f32, f32)) {
+  let bbox = item.bbox;
+  item.bbox = BBox {
+    x_min: bbox.x_min * sx + ox,
+    y_min: bbox.y_min * sy + oy,
+    x_max: bbox.x_max * sx + ox,
+    y_max: bbox.y_max * sy + oy,
+  };
+  item.mask = item.mask.as_ref().map(|m| m.transform((ox, oy), (sx, sy)));
+  if let Some(pose) = &mut item.keypoints {
+    for point in pose.keypoints.iter_mut() {
+      point.x = point.x * sx + ox;
+      point.y = point.y * sy + oy;
+    }
+  }
+  if let Some(obb) = &mut item.obb {
+    obb.cx = obb.cx * sx + ox;
+    obb.cy = obb.cy * sy + oy;
+    obb.width *= sx;
+    obb.height *= sy;
+  }
+}
+
+impl<const FW: u32, const FH: u32, const W: u32, const H: u32, M, P, T> Tiled<FW, FH, W, H, M, P>
+where
+  M: Model<Input = RgbNhwcFrame<W, H>>,
+  M::Error: std::error::Error + Send + Sync + 'static,
+  P: Postprocess<Input = M::Output, Output = DetectResult<T>>,
+  P::Error: std::error::Error + Send + Sync + 'static,
+{
+  /// 对画面中的一个区域推理，结果为画面坐标
+  fn infer_region(
+    &self,
+    frame: &[u8],
+    region: BBox,
+    items: &mut Vec<DetectItem<T>>,
+  ) -> Result<(), TiledError> {
+    let data = crop_resize(
+      frame,
+      (FW as usize, FH as usize),
+      &region,
+      (W as usize, H as usize),
+    );
+    let output = self
+      .model
+      .infer(&RgbNhwcFrame::from(data))
+      .map_err(|e| TiledError::ModelError(Box::new(e)))?;
+    let result = self
+      .post
+      .process(output)
+      .map_err(|e| TiledError::PostprocessError(Box::new(e)))?;
+
+    let offset = (region.x_min, region.y_min);
+    let scale = (region.width() / W as f32, region.height() / H as f32);
+    for mut item in result.items.into_vec() {
+      place(&mut item, offset, scale);
+      items.push(item);
+    }
+    Ok(())
+  }
+}
+
+impl<const FW: u32, const FH: u32, const W: u32, const H: u32, M, P, T> Model
+  for Tiled<FW, FH, W, H, M, P>
+where
+  M: Model<Input = RgbNhwcFrame<W, H>>,
+  M::Error: std::error::Error + Send + Sync + 'static,
+  P: Postprocess<Input = M::Output, Output = DetectResult<T>>,
+  P::Error: std::error::Error + Send + Sync + 'static,
+{
+  type Input = RgbNhwcFrame<FW, FH>;
+  type Output = DetectResult<T>;
+  type Error = TiledError;
+
+  fn infer(&self, input: &Self::Input) -> Result<Self::Output, Self::Error> {
+    let frame = input.as_nhwc();
+    let (tile_w, tile_h) = self.tile.unwrap_or((W, H));
+    let (tile_w, tile_h) = (tile_w.min(FW), tile_h.min(FH));
+
+    let mut items = Vec::new();
+    for y in tile_starts(FH, tile_h, self.overlap) {
+      for x in tile_starts(FW, tile_w, self.overlap) {
+        let region = BBox {
+          x_min: x as f32,
+          y_min: y as f32,
+          x_max: (x + tile_w) as f32,
+          y_max: (y + tile_h) as f32,
+        };
+        self.infer_region(frame, region, &mut items)?;
+      }
+    }
+    if self.full_frame {
+      let region = BBox {
+        x_min: 0.0,
+        y_min: 0.0,
+        x_max: FW as f32,
+        y_max: FH as f32,
+      };
+      self.infer_region(frame, region, &mut items)?;
+    }
+    debug!("分块检测数量: {}", items.len());
+
+    Ok(DetectResult {
+      items: items.into_boxed_slice(),
+    })
+  }
+}
+
+impl<T: WithLabel> Postprocess for TileMerge<T> {
+  type Input = DetectResult<T>;
+  type Output = DetectResult<T>;
+  type Error = TiledError;
+
+  fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+    let items = output.items.into_vec();
+    let items = match self.method {
+      TileMergeMethod::Nms => Nms::default()
+        .mode(NmsMode::ClassAware)
+        .iou_thresh(self.iou_thresh)
+        .suppress(items),
+      TileMergeMethod::Wbf => self.fuse(items),
+    };
+    debug!("合并后检测数量: {}", items.len());
+
+    Ok(DetectResult {
+      items: items.into_boxed_slice(),
+    })
+  }
+}
+
+/// 融合后的检测及组内成员的边框与分数
+type FuseGroup<T> = (DetectItem<T>, Vec<(BBox, f32)>);
+
+impl<T: WithLabel> TileMerge<T> {
+  /// 加权框融合：同一类别中交并比超过阈值的框归为一组，边框按分数加权平均，分数取平均值
+  ///
+  /// 掩码、关键点与旋转边框取组内分数最高的检测。
+  fn fuse(&self, mut items: Vec<DetectItem<T>>) -> Vec<DetectItem<T>> {
+    items.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let mut groups: Vec<FuseGroup<T>> = Vec::new();
+    for item in items {
+      let group = groups.iter_mut().find(|(fused, _)| {
+        fused.kind.to_label_id() == item.kind.to_label_id()
+          && fused.bbox.iou(&item.bbox) > self.iou_thresh
+      });
+      let Some((fused, members)) = group else {
+        let members = vec![(item.bbox, item.score)];
+        groups.push((item, members));
+        continue;
+      };
+
+      members.push((item.bbox, item.score));
+      let total: f32 = members.iter().map(|(_, s)| s).sum();
+      let weighted =
+        |pick: fn(&BBox) -> f32| members.iter().map(|(b, s)| pick(b) * s).sum::<f32>() / total;
+      fused.bbox = BBox {
+        x_min: weighted(|b| b.x_min),
+        y_min: weighted(|b| b.y_min),
+        x_max: weighted(|b| b.x_max),
+        y_max: weighted(|b| b.y_max),
+      };
+      fused.score = total / members.len() as f32;
+    }
+
+    let mut items: Vec<DetectItem<T>> = groups.into_iter().map(|(fused, _)| fused).collect();
+    items.sort_by(|a, b| b.score.total_cmp(&a.score));
+    items
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use shanan_trait::{Postprocess, WithLabel};
+
+  use super::{TileMergeMethod, TiledBuilder, place, tile_starts};
+  use crate::model::{BBox, DetectItem, DetectResult, Keypoint, Pose, RotatedBox};
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct TestLabel(u32);
+
+  impl WithLabel for TestLabel {
+    const LABEL_NUM: u32 = 2;
+
+    fn from_label_id(id: u32) -> Self {
+      Self(id)
+    }
+
+    fn to_label_id(&self) -> u32 {
+      self.0
+    }
+
+    fn to_label_str(&self) -> String {
+      format!("class{}", self.0)
+    }
+  }
+
+  fn item(class: u32, score: f32, [x_min, y_min, x_max, y_max]: [f32; 4]) -> DetectItem<TestLabel> {
+    DetectItem::new(
+      TestLabel(class),
+      score,
+      BBox {
+        x_min,
+        y_min,
+        x_max,
+        y_max,
+      },
+    )
+  }
+
+  fn corners(bbox: &BBox) -> [f32; 4] {
+    [bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max]
+  }
+
+  #[test]
+  fn tile_starts_cover_frame() {
+    // 步长 32，最后一个分块与画面边缘对齐
+    assert_eq!(tile_starts(100, 40, 0.2), [0, 32, 60]);
+    // 无重叠且画面大小不是分块的整数倍
+    assert_eq!(tile_starts(100, 40, 0.0), [0, 40, 60]);
+    // 无重叠且画面大小是分块的整数倍，不产生重复分块
+    assert_eq!(tile_starts(120, 40, 0.0), [0, 40, 80]);
+    // 分块不小于画面
+    assert_eq!(tile_starts(40, 40, 0.2), [0]);
+    assert_eq!(tile_starts(30, 40, 0.2), [0]);
+  }
+
+  #[test]
+  fn place_maps_to_frame() {
+    let mut item = item(0, 0.9, [10.0, 20.0, 30.0, 40.0]);
+    item.keypoints = Some(Pose {
+      keypoints: vec![Keypoint {
+        x: 10.0,
+        y: 20.0,
+        score: 1.0,
+      }],
+      skeleton: Vec::new().into(),
+    });
+    item.obb = Some(RotatedBox {
+      cx: 20.0,
+      cy: 30.0,
+      width: 20.0,
+      height: 20.0,
+      angle: 0.0,
+    });
+
+    place(&mut item, (100.0, 50.0), (2.0, 0.5));
+    assert_eq!(corners(&item.bbox), [120.0, 60.0, 160.0, 70.0]);
+    let point = item.keypoints.as_ref().unwrap().keypoints[0];
+    assert_eq!((point.x, point.y), (120.0, 60.0));
+    let obb = item.obb.unwrap();
+    assert_eq!(
+      (obb.cx, obb.cy, obb.width, obb.height),
+      (140.0, 65.0, 40.0, 10.0)
+    );
+  }
+
+  #[test]
+  fn fuses_object_split_across_tiles() {
+    // 同一目标在两个相邻分块中各检出一部分，交并比为 1440 / 1600 = 0.9
+    let items = vec![
+      item(0, 0.6, [92.0, 0.0, 130.0, 40.0]),
+      item(0, 0.9, [90.0, 0.0, 128.0, 40.0]),
+      // 不同类别不融合
+      item(1, 0.8, [90.0, 0.0, 128.0, 40.0]),
+    ];
+    let builder = TiledBuilder::default().method(TileMergeMethod::Wbf);
+    let result = builder
+      .build_postprocess::<TestLabel>()
+      .process(DetectResult {
+        items: items.clone().into_boxed_slice(),
+      })
+      .unwrap();
+    assert_eq!(result.items.len(), 2);
+    let fused = &result.items[1];
+    assert_eq!(fused.kind, TestLabel(0));
+    assert!((fused.score - 0.75).abs() < 1e-6);
+    // 边框按分数加权平均：(90 * 0.9 + 92 * 0.6) / 1.5，(128 * 0.9 + 130 * 0.6) / 1.5
+    let expected = [90.8, 0.0, 128.8, 40.0];
+    for (a, e) in corners(&fused.bbox).into_iter().zip(expected) {
+      assert!((a - e).abs() < 1e-4, "{:?}", fused.bbox);
+    }
+
+    // NMS 合并只保留分数最高的框
+    let result = TiledBuilder::default()
+      .build_postprocess::<TestLabel>()
+      .process(DetectResult {
+        items: items.into_boxed_slice(),
+      })
+      .unwrap();
+    let scores: Vec<_> = result.items.iter().map(|item| item.score).collect();
+    assert_eq!(scores, [0.9, 0.8]);
+  }
+}
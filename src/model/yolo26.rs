@@ -12,24 +12,27 @@
 // Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
 
 // use image::Frame;
-use rknpu::{Context, InitFlags, TensorType};
+use std::borrow::Cow;
+
+use rknpu::InitFlags;
 use shanan_cv::cubecl::Runtime;
 use shanan_cv::cubecl::client::ComputeClient;
 use shanan_cv::data::DataBuffer;
 use shanan_trait::Postprocess;
 use thiserror::Error;
-use tracing::{debug, error, info};
+use tracing::debug;
 use url::Url;
 
 use crate::{
   FromUrl,
   FromUrlWithScheme,
-  input::AsNhwcFrame,
-  model::{BBox, DetectItem, DetectResult, Model, WithLabel},
+  model::{
+    BBox, ClassFilter, ClassFilterError, ClassRules, DetectItem, DetectResult, Nms, NmsError,
+    RknnModel, RknnModelError, TensorView, Tensors, WithLabel, label_num,
+  },
   // utils::sigmoid,
 };
 
-const YOLO26_NUM_INPUTS: u32 = 1;
 const YOLO26_NUM_OUTPUTS: u32 = 3;
 const YOLO26_OBJECT_THRESH: f32 = 0.5;
 
@@ -38,28 +41,30 @@ const SCV_P_DIM: u32 = 1;
 #[cfg(feature = "cubecl-wgpu")]
 const SCV_P_DIM: u32 = 256;
 
-pub type Yolo26Nhwc<const W: u32, const H: u32> = Yolo26<W, H, crate::frame::RgbNhwcFrame<H, W>>;
+pub type Yolo26Nhwc<const W: u32, const H: u32> = Yolo26<W, H, crate::frame::RgbNhwcFrame<W, H>>;
 
-pub struct Yolo26<const W: u32, const H: u32, Frame> {
-  context: Context,
-  _phantom: std::marker::PhantomData<Frame>,
-}
+/// Yolo26 在 NPU 上的推理与通用 RKNN 模型相同
+pub type Yolo26<const W: u32, const H: u32, Frame> = RknnModel<W, H, Frame>;
 
 pub struct Yolo26Postprocess<const W: u32, const H: u32, T, R: Runtime> {
   object_thresh: f32,
+  classes: ClassRules,
+  nms: Nms,
   postprocess: shanan_cv::postprocess::detection::Yolo26Bc<R, f32, u32>,
   cl_client: ComputeClient<R>,
+  /// 类别数量，构建时确定
+  num_classes: usize,
   _phantom: std::marker::PhantomData<T>,
 }
 
 #[derive(Error, Debug)]
 pub enum Yolo26Error {
-  #[error("模型加载错误: {0}")]
-  ModelLoadError(#[from] std::io::Error),
-  #[error("模型无效: {0}, 错误: {1}")]
-  ModelInvalid(String, rknpu::Error),
-  #[error("RKNN 错误: {0}")]
-  RknnError(#[from] rknpu::Error),
+  #[error("RKNN 模型错误: {0}")]
+  RknnModelError(#[from] RknnModelError),
+  #[error("类别过滤配置错误: {0}")]
+  ClassFilterError(#[from] ClassFilterError),
+  #[error("NMS 配置错误: {0}")]
+  NmsError(#[from] NmsError),
   #[error("模型路径错误: {0}")]
   ModelPathError(String),
   #[error("shanan-cv 后处理错误: {0}")]
@@ -68,17 +73,14 @@ pub enum Yolo26Error {
   DataBufferError(#[from] shanan_cv::data::DataBufferError),
 }
 
-impl Yolo26Error {
-  pub fn invalid(msg: &str, e: rknpu::Error) -> Self {
-    Yolo26Error::ModelInvalid(msg.to_string(), e)
-  }
-}
-
 pub struct Yolo26Builder {
   model_path: String,
   flags: InitFlags,
   object_thresh: f32,
   pdim: u32,
+  classes: ClassFilter,
+  nms: Nms,
+  num_labels: Option<usize>,
 }
 
 impl FromUrlWithScheme for Yolo26Builder {
@@ -92,6 +94,9 @@ impl Default for Yolo26Builder {
       flags: InitFlags::default(),
       object_thresh: YOLO26_OBJECT_THRESH,
       pdim: SCV_P_DIM,
+      classes: ClassFilter::default(),
+      nms: Nms::default(),
+      num_labels: None,
     }
   }
 }
@@ -134,6 +139,9 @@ impl FromUrl for Yolo26Builder {
       flags: InitFlags::default(),
       object_thresh,
       pdim,
+      classes: ClassFilter::from_url(url)?,
+      nms: Nms::from_url(url)?,
+      num_labels: None,
     })
   }
 }
@@ -159,111 +167,48 @@ impl Yolo26Builder {
     self
   }
 
+  pub fn classes(mut self, classes: ClassFilter) -> Self {
+    self.classes = classes;
+    self
+  }
+
+  /// 类别数量，构建模型时检查每个检测头的通道数，0 表示不检查
+  pub fn labels(mut self, num_labels: usize) -> Self {
+    self.num_labels = (num_labels > 0).then_some(num_labels);
+    self
+  }
+
+  /// Yolo26 为 NMS-free 检测头，默认不进行 NMS
+  pub fn nms(mut self, nms: Nms) -> Self {
+    self.nms = nms;
+    self
+  }
+
   pub fn build_postprocess<const W: u32, const H: u32, T: WithLabel, R: Runtime>(
     &self,
   ) -> Result<Yolo26Postprocess<W, H, T, R>, Yolo26Error> {
-    Yolo26Postprocess::new(self.object_thresh, self.pdim)
+    let classes = self.classes.resolve::<T>(self.object_thresh)?;
+    // 解码阈值与 NMS 的分数下限取允许类别中的最低阈值，各类别的阈值由 `classes` 检查
+    let object_thresh = classes.min_thresh();
+    Ok(
+      Yolo26Postprocess::new(object_thresh, self.pdim)?
+        .with_classes(classes)
+        .with_nms(self.nms.clone().score_thresh(object_thresh)),
+    )
   }
 
   pub fn build_model<const W: u32, const H: u32, Frame>(
     self,
   ) -> Result<Yolo26<W, H, Frame>, Yolo26Error> {
-    info!("加载模型文件: {}", self.model_path);
-    let mode_data = std::fs::read(&self.model_path)?;
-    debug!(
-      "模型文件大小: {:.2} MB",
-      mode_data.len() as f64 / (1024.0 * 1024.0)
-    );
-
-    info!("创建 RKNN 推理上下文");
-    let context = Context::new(&mode_data, self.flags)?;
-    info!("模型加载完成");
-
-    match context.sdk_version() {
-      Ok(version) => {
-        if let Ok(api_ver) = version.api_version() {
-          debug!("模型 API 版本: {}", api_ver);
-        }
-        if let Ok(drv_ver) = version.driver_version() {
-          debug!("模型驱动版本: {}", drv_ver);
-        }
-      }
-      Err(e) => {
-        error!(" 查询 SDK 版本失败: {}", e);
-        return Err(Yolo26Error::invalid("无法查询 SDK 版本", e));
-      }
+    let model = RknnModel::load(&self.model_path, self.flags, Some(YOLO26_NUM_OUTPUTS))?;
+    if let Some(num_labels) = self.num_labels {
+      // 每个检测头的通道为 4 个边框值加类别分数
+      model
+        .info()
+        .check_labels(4, num_labels)
+        .map_err(RknnModelError::from)?;
     }
-
-    let num_inputs = context
-      .num_inputs()
-      .map_err(|e| Yolo26Error::invalid("无法获取输入数量", e))?;
-    let num_outputs = context
-      .num_outputs()
-      .map_err(|e| Yolo26Error::invalid("无法获取输出数量", e))?;
-
-    if num_inputs != YOLO26_NUM_INPUTS {
-      error!(
-        "预期模型输入数量为 {}, 实际为 {}",
-        YOLO26_NUM_INPUTS, num_inputs
-      );
-      return Err(Yolo26Error::invalid(
-        &format!(
-          "预期模型输入数量为 {}, 实际为 {}",
-          YOLO26_NUM_INPUTS, num_inputs
-        ),
-        rknpu::Error::InvalidModel,
-      ));
-    }
-
-    if num_outputs != YOLO26_NUM_OUTPUTS {
-      error!(
-        "预期模型输出数量为 {}, 实际为 {}",
-        YOLO26_NUM_OUTPUTS, num_outputs
-      );
-      return Err(Yolo26Error::invalid(
-        &format!(
-          "预期模型输出数量为 {}, 实际为 {}",
-          YOLO26_NUM_OUTPUTS, num_outputs
-        ),
-        rknpu::Error::InvalidModel,
-      ));
-    }
-
-    debug!("模型输入数量: {}", num_inputs);
-    debug!("模型输出数量: {}", num_outputs);
-
-    let _phantom = std::marker::PhantomData;
-    Ok(Yolo26 { context, _phantom })
-  }
-}
-
-impl<const W: u32, const H: u32, Frame: AsNhwcFrame<H, W>> Model for Yolo26<W, H, Frame> {
-  // type Input = RgbNchwFrame; // 输入为 NCHW 格式的字节数组
-  type Input = Frame;
-  type Output = rknpu::Output; // 输出为浮点数组
-  type Error = Yolo26Error;
-
-  fn infer(&self, input: &Self::Input) -> Result<Self::Output, Self::Error> {
-    // 设置输入
-    debug!("设置模型输入");
-    self.context.set_input(
-      0,
-      input.as_nhwc(),
-      rknpu::TensorFormat::NHWC,
-      TensorType::UInt8,
-    )?;
-
-    // 执行推理
-    debug!("执行模型推理");
-    self.context.run()?;
-
-    // 获取输出
-    debug!("获取模型输出");
-
-    let output = self.context.get_outputs()?;
-    debug!("模型推理结果：{:?}", output);
-
-    Ok(output)
+    Ok(model)
   }
 }
 
@@ -277,19 +222,35 @@ impl<const W: u32, const H: u32, T: WithLabel, R: Runtime> Yolo26Postprocess<W,
 
     let cl_client = R::client(&R::Device::default());
 
+    let num_classes = label_num::<T>();
     Ok(Self {
       object_thresh,
+      classes: ClassRules::uniform(num_classes, object_thresh),
+      nms: Nms::default(),
       postprocess,
       cl_client,
+      num_classes,
       _phantom: std::marker::PhantomData,
     })
   }
+
+  /// 按类别的阈值与过滤，解码阈值取允许类别中的最低阈值
+  pub fn with_classes(mut self, classes: ClassRules) -> Self {
+    self.object_thresh = classes.min_thresh();
+    self.classes = classes;
+    self
+  }
+
+  pub fn with_nms(mut self, nms: Nms) -> Self {
+    self.nms = nms;
+    self
+  }
 }
 
 impl<const W: u32, const H: u32, T: WithLabel, R: Runtime> Postprocess
   for Yolo26Postprocess<W, H, T, R>
 {
-  type Input = rknpu::Output; // 输出为浮点数组
+  type Input = Tensors;
   type Output = DetectResult<T>;
   type Error = Yolo26Error;
 
@@ -298,58 +259,173 @@ impl<const W: u32, const H: u32, T: WithLabel, R: Runtime> Postprocess
     debug!("后处理模型输出");
     let mut items = Vec::new();
 
-    for i in 0..output.len() {
-      let pred = match output.get_f32(i) {
-        Ok(data) => data,
-        Err(e) => {
-          error!("获取输出张量失败: {}", e);
-          return Err(Yolo26Error::RknnError(e));
-        }
-      };
-
-      let head_len = pred.len() / (4 + T::LABEL_NUM as usize);
-
-      // 调用 shanan-cv 的后处理函数
-      let pred: DataBuffer<R, _> = DataBuffer::from_slice(
-        pred,
-        &[1, 4 + T::LABEL_NUM as usize, head_len], // 可以变化?
-        &self.cl_client,
-      )?;
-
-      let (score, index, bbox) =
-        self
-          .postprocess
-          .execute(&self.cl_client, &pred, self.object_thresh)?;
-
-      let bbox = bbox.into_vec(&self.cl_client)?;
-      let index = index.into_vec(&self.cl_client)?;
-      let score = score.into_vec(&self.cl_client)?;
-
-      for s in 0..head_len {
-        let score_value = score[s];
-        if score_value > self.object_thresh {
-          let class_id = index[s];
-          let x_min = bbox[s];
-          let y_min = bbox[head_len + s];
-          let x_max = bbox[2 * head_len + s];
-          let y_max = bbox[3 * head_len + s];
-
-          items.push(DetectItem {
-            kind: T::from_label_id(class_id),
-            score: score_value,
-            bbox: BBox {
-              x_min,
-              y_min,
-              x_max,
-              y_max,
-            },
-          });
-        }
+    for tensor in output.iter() {
+      if let Some(pred) = self.candidates(tensor.view()) {
+        self.process_head(&pred, &mut items)?;
       }
     }
+    self.classes.retain(&mut items);
 
     Ok(DetectResult {
-      items: items.into_boxed_slice(),
+      items: self.nms.suppress(items).into_boxed_slice(),
     })
   }
 }
+
+impl<const W: u32, const H: u32, T: WithLabel, R: Runtime> Yolo26Postprocess<W, H, T, R> {
+  /// 检测头输出的浮点数据，返回 `None` 表示没有候选通过阈值
+  ///
+  /// 量化输出在量化域中查找每个候选的最高类别分数并比较阈值，只反量化通过阈值的候选，
+  /// 其余候选保持为零。
+  fn candidates<'a>(&self, pred: TensorView<'a>) -> Option<Cow<'a, [f32]>> {
+    if let TensorView::F32(data) = pred {
+      return Some(Cow::Borrowed(data));
+    }
+
+    let rows = 4 + self.num_classes;
+    let head_len = pred.len() / rows;
+    let mut data = vec![0.0; pred.len()];
+    let mut found = false;
+    for s in 0..head_len {
+      let offset = 4 * head_len + s;
+      let Some((class, _)) =
+        pred.argmax_allowed(offset, self.num_classes, head_len, self.classes.allowed())
+      else {
+        continue;
+      };
+      if !pred.exceeds(offset + class * head_len, self.object_thresh) {
+        continue;
+      }
+      for r in 0..rows {
+        data[r * head_len + s] = pred.get(r * head_len + s);
+      }
+      found = true;
+    }
+    found.then_some(Cow::Owned(data))
+  }
+
+  fn process_head(&self, pred: &[f32], items: &mut Vec<DetectItem<T>>) -> Result<(), Yolo26Error> {
+    let head_len = pred.len() / (4 + self.num_classes);
+
+    // 调用 shanan-cv 的后处理函数
+    let pred: DataBuffer<R, _> = DataBuffer::from_slice(
+      pred,
+      &[1, 4 + self.num_classes, head_len], // 可以变化?
+      &self.cl_client,
+    )?;
+
+    let (score, index, bbox) =
+      self
+        .postprocess
+        .execute(&self.cl_client, &pred, self.object_thresh)?;
+
+    let bbox = bbox.into_vec(&self.cl_client)?;
+    let index = index.into_vec(&self.cl_client)?;
+    let score = score.into_vec(&self.cl_client)?;
+
+    for s in 0..head_len {
+      let score_value = score[s];
+      if score_value > self.object_thresh {
+        let class_id = index[s];
+        let x_min = bbox[s];
+        let y_min = bbox[head_len + s];
+        let x_max = bbox[2 * head_len + s];
+        let y_max = bbox[3 * head_len + s];
+
+        items.push(DetectItem::new(
+          T::from_label_id(class_id),
+          score_value,
+          BBox {
+            x_min,
+            y_min,
+            x_max,
+            y_max,
+          },
+        ));
+      }
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use shanan_cv::cubecl::cpu::CpuRuntime;
+  use shanan_trait::{Postprocess, WithLabel};
+
+  use super::{Yolo26Builder, Yolo26Postprocess};
+  use crate::model::{Quantization, Tensor};
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct TestLabel(u32);
+
+  impl WithLabel for TestLabel {
+    const LABEL_NUM: u32 = 3;
+
+    fn from_label_id(id: u32) -> Self {
+      Self(id)
+    }
+
+    fn to_label_id(&self) -> u32 {
+      self.0
+    }
+
+    fn to_label_str(&self) -> String {
+      format!("class{}", self.0)
+    }
+  }
+
+  /// 线性同余生成的伪随机字节
+  fn random_bytes(len: usize, seed: u32) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(2_654_435_761).wrapping_add(1);
+    (0..len)
+      .map(|_| {
+        state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        (state >> 16) as u8
+      })
+      .collect()
+  }
+
+  #[test]
+  fn quantized_decode_matches_f32() {
+    let post: Yolo26Postprocess<64, 64, TestLabel, CpuRuntime> = Yolo26Builder::default()
+      .object_thresh(0.9)
+      .build_postprocess()
+      .unwrap();
+    // 步长 8、16、32 的三个检测头，每个候选 4 个边框值与 3 个类别分数
+    let quantization = Quantization::new(1.0 / 255.0, 0);
+    let output: Vec<Tensor> = [64usize, 16, 4]
+      .into_iter()
+      .enumerate()
+      .map(|(i, len)| {
+        Tensor::from_u8(
+          vec![1, 7, len],
+          random_bytes(7 * len, i as u32),
+          quantization,
+        )
+      })
+      .collect();
+    let dequantized: Vec<Tensor> = output
+      .iter()
+      .map(|t| Tensor::from_f32(t.shape.clone(), t.to_f32().into_owned()))
+      .collect();
+
+    let quantized = post.process(output).unwrap();
+    let float = post.process(dequantized).unwrap();
+    assert!(!quantized.items.is_empty());
+    assert_eq!(quantized.items.len(), float.items.len());
+    for (a, e) in quantized.items.iter().zip(float.items.iter()) {
+      assert_eq!(a.kind, e.kind);
+      assert_eq!(a.score, e.score);
+      assert_eq!(
+        [a.bbox.x_min, a.bbox.y_min, a.bbox.x_max, a.bbox.y_max],
+        [e.bbox.x_min, e.bbox.y_min, e.bbox.x_max, e.bbox.y_max]
+      );
+    }
+
+    // 没有候选通过阈值时跳过检测头
+    let empty = vec![Tensor::from_u8(vec![1, 7, 4], vec![0; 28], quantization)];
+    assert!(post.process(empty).unwrap().items.is_empty());
+  }
+}
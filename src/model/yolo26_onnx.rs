@@ -0,0 +1,288 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/yolo26_onnx.rs - 基于 tract 的 Yolo26 ONNX CPU 推理
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # Yolo26 ONNX CPU 推理
+//!
+//! 使用纯 Rust 的 tract 运行时在 CPU 上加载 ONNX 模型。ONNX 模型需要与转换为 RKNN
+//! 之前的模型保持相同的输出头，这样可以复用 [`Yolo26Postprocess`]。
+//!
+//! ## URL Scheme
+//!
+//! ```text
+//! onnx-yolo26:///path/to/yolo26n.onnx?object_thresh=0.5&layout=nchw&scale=0.00392157
+//! ```
+//!
+//! ## 参数说明
+//!
+//! - `object_thresh`: 目标置信度阈值，默认与 `yolo26://` 相同
+//! - `pdim`: shanan-cv 后处理并行维度，默认与 `yolo26://` 相同
+//! - `layout`: 模型输入布局，`nchw` 或 `nhwc`，默认 `nchw`
+//! - `scale`: 输入像素缩放系数，默认 1/255
+//! - `class_thresh` 等: 按类别的阈值与过滤，见 [`ClassFilter`](crate::model::ClassFilter)
+//! - `nms` 等: 可选的 NMS 参数，见 [`Nms`](crate::model::Nms)
+
+use shanan_cv::cubecl::Runtime;
+use shanan_trait::WithLabel;
+use thiserror::Error;
+use tracing::{debug, error, info};
+use tract_onnx::prelude::*;
+use url::Url;
+
+use crate::{
+  FromUrl, FromUrlWithScheme,
+  input::AsNhwcFrame,
+  model::{
+    ClassFilter, ClassFilterError, Model, Nms, NmsError, Tensors, Yolo26Builder, Yolo26Error,
+    Yolo26Postprocess,
+  },
+};
+
+const YOLO26_ONNX_NUM_OUTPUTS: usize = 3;
+const YOLO26_ONNX_SCALE: f32 = 1.0 / 255.0;
+
+pub type Yolo26OnnxNhwc<const W: u32, const H: u32> =
+  Yolo26Onnx<W, H, crate::frame::RgbNhwcFrame<W, H>>;
+
+#[derive(Error, Debug)]
+pub enum Yolo26OnnxError {
+  #[error("模型路径错误: {0}")]
+  ModelPathError(String),
+  #[error("模型无效: {0}")]
+  ModelInvalid(String),
+  #[error("tract 错误: {0}")]
+  TractError(#[from] TractError),
+  #[error("类别过滤配置错误: {0}")]
+  ClassFilterError(#[from] ClassFilterError),
+  #[error("NMS 配置错误: {0}")]
+  NmsError(#[from] NmsError),
+  #[error("Yolo26 后处理错误: {0}")]
+  Yolo26Error(#[from] Yolo26Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnnxInputLayout {
+  Nchw,
+  Nhwc,
+}
+
+pub struct Yolo26Onnx<const W: u32, const H: u32, Frame> {
+  plan: TypedRunnableModel<TypedModel>,
+  layout: OnnxInputLayout,
+  scale: f32,
+  _phantom: std::marker::PhantomData<Frame>,
+}
+
+pub struct Yolo26OnnxBuilder {
+  model_path: String,
+  object_thresh: Option<f32>,
+  pdim: Option<u32>,
+  classes: ClassFilter,
+  nms: Nms,
+  layout: OnnxInputLayout,
+  scale: f32,
+}
+
+impl FromUrlWithScheme for Yolo26OnnxBuilder {
+  const SCHEME: &'static str = "onnx-yolo26";
+}
+
+impl Default for Yolo26OnnxBuilder {
+  fn default() -> Self {
+    Self {
+      model_path: String::new(),
+      object_thresh: None,
+      pdim: None,
+      classes: ClassFilter::default(),
+      nms: Nms::default(),
+      layout: OnnxInputLayout::Nchw,
+      scale: YOLO26_ONNX_SCALE,
+    }
+  }
+}
+
+impl FromUrl for Yolo26OnnxBuilder {
+  type Error = Yolo26OnnxError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    if url.scheme() != Self::SCHEME {
+      return Err(Yolo26OnnxError::ModelPathError(format!(
+        "模型路径必须使用 {} 方案",
+        Self::SCHEME
+      )));
+    }
+
+    let mut builder = Yolo26OnnxBuilder::default()
+      .model_path(url.path().to_string())
+      .classes(ClassFilter::from_url(url)?)
+      .nms(Nms::from_url(url)?);
+    for (k, v) in url.query_pairs() {
+      match k.as_ref() {
+        "object_thresh" => builder.object_thresh = v.parse().ok(),
+        "pdim" => builder.pdim = v.parse().ok(),
+        "layout" => {
+          builder.layout = match v.as_ref() {
+            "nchw" => OnnxInputLayout::Nchw,
+            "nhwc" => OnnxInputLayout::Nhwc,
+            other => {
+              return Err(Yolo26OnnxError::ModelPathError(format!(
+                "未知的输入布局: {}",
+                other
+              )));
+            }
+          }
+        }
+        "scale" => builder.scale = v.parse().unwrap_or(YOLO26_ONNX_SCALE),
+        _ => {}
+      }
+    }
+
+    Ok(builder)
+  }
+}
+
+impl Yolo26OnnxBuilder {
+  pub fn model_path(mut self, model_path: String) -> Self {
+    self.model_path = model_path;
+    self
+  }
+
+  pub fn object_thresh(mut self, object_thresh: f32) -> Self {
+    self.object_thresh = Some(object_thresh);
+    self
+  }
+
+  pub fn pdim(mut self, pdim: u32) -> Self {
+    self.pdim = Some(pdim);
+    self
+  }
+
+  pub fn classes(mut self, classes: ClassFilter) -> Self {
+    self.classes = classes;
+    self
+  }
+
+  pub fn nms(mut self, nms: Nms) -> Self {
+    self.nms = nms;
+    self
+  }
+
+  pub fn layout(mut self, layout: OnnxInputLayout) -> Self {
+    self.layout = layout;
+    self
+  }
+
+  pub fn scale(mut self, scale: f32) -> Self {
+    self.scale = scale;
+    self
+  }
+
+  pub fn build_postprocess<const W: u32, const H: u32, T: WithLabel, R: Runtime>(
+    &self,
+  ) -> Result<Yolo26Postprocess<W, H, T, R>, Yolo26OnnxError> {
+    let mut builder = Yolo26Builder::default()
+      .classes(self.classes.clone())
+      .nms(self.nms.clone());
+    if let Some(object_thresh) = self.object_thresh {
+      builder = builder.object_thresh(object_thresh);
+    }
+    if let Some(pdim) = self.pdim {
+      builder = builder.pdim(pdim);
+    }
+    Ok(builder.build_postprocess()?)
+  }
+
+  pub fn build_model<const W: u32, const H: u32, Frame>(
+    self,
+  ) -> Result<Yolo26Onnx<W, H, Frame>, Yolo26OnnxError> {
+    info!("加载 ONNX 模型文件: {}", self.model_path);
+    let shape: [usize; 4] = match self.layout {
+      OnnxInputLayout::Nchw => [1, 3, H as usize, W as usize],
+      OnnxInputLayout::Nhwc => [1, H as usize, W as usize, 3],
+    };
+
+    let plan = tract_onnx::onnx()
+      .model_for_path(&self.model_path)?
+      .with_input_fact(0, f32::fact(shape).into())?
+      .into_optimized()?
+      .into_runnable()?;
+
+    let num_outputs = plan.model().output_outlets()?.len();
+    if num_outputs != YOLO26_ONNX_NUM_OUTPUTS {
+      error!(
+        "预期模型输出数量为 {}, 实际为 {}",
+        YOLO26_ONNX_NUM_OUTPUTS, num_outputs
+      );
+      return Err(Yolo26OnnxError::ModelInvalid(format!(
+        "预期模型输出数量为 {}, 实际为 {}",
+        YOLO26_ONNX_NUM_OUTPUTS, num_outputs
+      )));
+    }
+    info!("ONNX 模型加载完成");
+
+    Ok(Yolo26Onnx {
+      plan,
+      layout: self.layout,
+      scale: self.scale,
+      _phantom: std::marker::PhantomData,
+    })
+  }
+}
+
+impl<const W: u32, const H: u32, Frame: AsNhwcFrame<W, H>> Model for Yolo26Onnx<W, H, Frame> {
+  type Input = Frame;
+  type Output = Tensors;
+  type Error = Yolo26OnnxError;
+
+  fn infer(&self, input: &Self::Input) -> Result<Self::Output, Self::Error> {
+    let (width, height) = (W as usize, H as usize);
+    let data = input.as_nhwc();
+
+    debug!("设置模型输入");
+    let input = match self.layout {
+      OnnxInputLayout::Nchw => {
+        let mut tensor = vec![0f32; 3 * width * height];
+        for y in 0..height {
+          for x in 0..width {
+            for c in 0..3 {
+              tensor[c * height * width + y * width + x] =
+                data[(y * width + x) * 3 + c] as f32 * self.scale;
+            }
+          }
+        }
+        Tensor::from_shape::<f32>(&[1, 3, height, width], &tensor)?
+      }
+      OnnxInputLayout::Nhwc => {
+        let tensor = data
+          .iter()
+          .map(|v| *v as f32 * self.scale)
+          .collect::<Vec<_>>();
+        Tensor::from_shape::<f32>(&[1, height, width, 3], &tensor)?
+      }
+    };
+
+    debug!("执行模型推理");
+    let outputs = self.plan.run(tvec!(input.into()))?;
+
+    debug!("获取模型输出");
+    let mut result = Vec::with_capacity(outputs.len());
+    for output in outputs.iter() {
+      debug!("模型输出形状: {:?}", output.shape());
+      result.push(crate::model::Tensor::from_f32(
+        output.shape().to_vec(),
+        output.as_slice::<f32>()?.to_vec(),
+      ));
+    }
+
+    Ok(result)
+  }
+}
@@ -0,0 +1,483 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/yolov5.rs - YOLOv5 检测头
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # YOLOv5 检测头
+//!
+//! YOLOv5 使用基于锚框的检测头，每个步长一个输出 `[1, A * (5 + C), h, w]`，
+//! 与 rknn_model_zoo 中的 YOLOv5 示例布局一致。每个锚框依次为
+//! `x, y, w, h, 目标置信度, 类别分数...`。
+//!
+//! ## URL Scheme
+//!
+//! ```text
+//! yolov5:///path/to/yolov5s.rknn?object_thresh=0.25&anchors_file=/path/to/anchors_yolov5.txt
+//! ```
+//!
+//! ## 参数说明
+//!
+//! - `object_thresh`: 目标置信度阈值，默认 0.25
+//! - `class_thresh`、`classes`、`exclude_classes`、`class_file`: 按类别的阈值与过滤，见 [`ClassFilter`]
+//! - `nms`、`iou_thresh`、`max_det`、`soft_nms`: NMS 参数，见 [`Nms`]，默认按类别抑制
+//! - `strides`: 各输出的步长，逗号分隔，默认 `8,16,32`
+//! - `anchors`: 锚框宽高，逗号分隔，按步长顺序排列，默认为 YOLOv5 官方锚框
+//! - `anchors_file`: 从文件读取锚框，数值之间以空白或逗号分隔（rknn_model_zoo 的 `anchors_yolov5.txt`）
+//! - `sigmoid`: 输出是否需要经过 sigmoid，默认 `false`（rknn_model_zoo 导出的模型已包含 sigmoid）
+
+use rknpu::InitFlags;
+use shanan_trait::Postprocess;
+use thiserror::Error;
+use tracing::debug;
+use url::Url;
+
+use crate::{
+  FromUrl, FromUrlWithScheme,
+  model::{
+    BBox, ClassFilter, ClassFilterError, ClassRules, DetectItem, DetectResult, Nms, NmsError,
+    NmsMode, RknnModel, RknnModelError, TensorView, Tensors, WithLabel, label_num,
+  },
+  utils::{logit, sigmoid},
+};
+
+const YOLOV5_OBJECT_THRESH: f32 = 0.25;
+const YOLOV5_STRIDES: [u32; 3] = [8, 16, 32];
+const YOLOV5_ANCHORS: [f32; 18] = [
+  10.0, 13.0, 16.0, 30.0, 33.0, 23.0, // P3/8
+  30.0, 61.0, 62.0, 45.0, 59.0, 119.0, // P4/16
+  116.0, 90.0, 156.0, 198.0, 373.0, 326.0, // P5/32
+];
+
+pub type YoloV5<const W: u32, const H: u32, Frame> = RknnModel<W, H, Frame>;
+pub type YoloV5Nhwc<const W: u32, const H: u32> = YoloV5<W, H, crate::frame::RgbNhwcFrame<W, H>>;
+
+#[derive(Error, Debug)]
+pub enum YoloV5Error {
+  #[error("RKNN 模型错误: {0}")]
+  RknnModelError(#[from] RknnModelError),
+  #[error("类别过滤配置错误: {0}")]
+  ClassFilterError(#[from] ClassFilterError),
+  #[error("NMS 配置错误: {0}")]
+  NmsError(#[from] NmsError),
+  #[error("模型路径错误: {0}")]
+  ModelPathError(String),
+  #[error("锚框配置错误: {0}")]
+  AnchorError(String),
+  #[error("锚框文件读取错误: {0}")]
+  AnchorFileError(#[from] std::io::Error),
+  #[error("模型输出不匹配: {0}")]
+  OutputMismatch(String),
+}
+
+pub struct YoloV5Postprocess<const W: u32, const H: u32, T> {
+  object_thresh: f32,
+  classes: ClassRules,
+  nms: Nms,
+  strides: Vec<u32>,
+  anchors: Vec<f32>,
+  sigmoid: bool,
+  /// 类别数量，构建时确定
+  num_classes: usize,
+  _phantom: std::marker::PhantomData<T>,
+}
+
+pub struct YoloV5Builder {
+  model_path: String,
+  flags: InitFlags,
+  object_thresh: f32,
+  classes: ClassFilter,
+  nms: Nms,
+  strides: Vec<u32>,
+  anchors: Vec<f32>,
+  sigmoid: bool,
+  num_labels: Option<usize>,
+}
+
+impl FromUrlWithScheme for YoloV5Builder {
+  const SCHEME: &'static str = "yolov5";
+}
+
+impl Default for YoloV5Builder {
+  fn default() -> Self {
+    Self {
+      model_path: String::new(),
+      flags: InitFlags::default(),
+      object_thresh: YOLOV5_OBJECT_THRESH,
+      classes: ClassFilter::default(),
+      nms: Nms::default(),
+      strides: YOLOV5_STRIDES.to_vec(),
+      anchors: YOLOV5_ANCHORS.to_vec(),
+      sigmoid: false,
+      num_labels: None,
+    }
+  }
+}
+
+fn parse_list<N: std::str::FromStr>(value: &str) -> Option<Vec<N>> {
+  value
+    .split(|c: char| c == ',' || c.is_whitespace())
+    .filter(|s| !s.is_empty())
+    .map(|s| s.parse::<N>().ok())
+    .collect()
+}
+
+impl FromUrl for YoloV5Builder {
+  type Error = YoloV5Error;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    if url.scheme() != Self::SCHEME {
+      return Err(YoloV5Error::ModelPathError(format!(
+        "模型路径必须使用 {} 方案",
+        Self::SCHEME
+      )));
+    }
+
+    let mut builder = YoloV5Builder::default()
+      .model_path(url.path().to_string())
+      .classes(ClassFilter::from_url(url)?)
+      .nms(Nms::from_url(url)?);
+    for (k, v) in url.query_pairs() {
+      match k.as_ref() {
+        "object_thresh" => builder.object_thresh = v.parse().unwrap_or(YOLOV5_OBJECT_THRESH),
+        "strides" => {
+          builder.strides = parse_list(&v)
+            .ok_or_else(|| YoloV5Error::ModelPathError(format!("无效的步长: {}", v)))?
+        }
+        "anchors" => {
+          builder.anchors =
+            parse_list(&v).ok_or_else(|| YoloV5Error::AnchorError(format!("无效的锚框: {}", v)))?
+        }
+        "anchors_file" => builder = builder.anchors_file(v.as_ref())?,
+        "sigmoid" => builder.sigmoid = v.parse().unwrap_or(false),
+        _ => {}
+      }
+    }
+
+    Ok(builder)
+  }
+}
+
+impl YoloV5Builder {
+  pub fn flags(mut self, flags: InitFlags) -> Self {
+    self.flags = flags;
+    self
+  }
+
+  pub fn model_path(mut self, model_path: String) -> Self {
+    self.model_path = model_path;
+    self
+  }
+
+  pub fn object_thresh(mut self, object_thresh: f32) -> Self {
+    self.object_thresh = object_thresh;
+    self
+  }
+
+  pub fn iou_thresh(mut self, iou_thresh: f32) -> Self {
+    self.nms = self.nms.iou_thresh(iou_thresh);
+    self
+  }
+
+  pub fn classes(mut self, classes: ClassFilter) -> Self {
+    self.classes = classes;
+    self
+  }
+
+  pub fn nms(mut self, nms: Nms) -> Self {
+    self.nms = nms;
+    self
+  }
+
+  pub fn strides(mut self, strides: Vec<u32>) -> Self {
+    self.strides = strides;
+    self
+  }
+
+  pub fn anchors(mut self, anchors: Vec<f32>) -> Self {
+    self.anchors = anchors;
+    self
+  }
+
+  /// 从文件读取锚框
+  pub fn anchors_file(mut self, path: &str) -> Result<Self, YoloV5Error> {
+    let content = std::fs::read_to_string(path)?;
+    self.anchors = parse_list(&content)
+      .ok_or_else(|| YoloV5Error::AnchorError(format!("锚框文件格式错误: {}", path)))?;
+    Ok(self)
+  }
+
+  pub fn sigmoid(mut self, sigmoid: bool) -> Self {
+    self.sigmoid = sigmoid;
+    self
+  }
+
+  /// 类别数量，构建模型时检查每个检测头的通道数，0 表示不检查
+  pub fn labels(mut self, num_labels: usize) -> Self {
+    self.num_labels = (num_labels > 0).then_some(num_labels);
+    self
+  }
+
+  pub fn build_postprocess<const W: u32, const H: u32, T: WithLabel>(
+    &self,
+  ) -> Result<YoloV5Postprocess<W, H, T>, YoloV5Error> {
+    if self.strides.is_empty() || self.strides.contains(&0) {
+      return Err(YoloV5Error::ModelPathError(format!(
+        "无效的步长: {:?}",
+        self.strides
+      )));
+    }
+    if self.anchors.is_empty() || self.anchors.len() % (2 * self.strides.len()) != 0 {
+      return Err(YoloV5Error::AnchorError(format!(
+        "锚框数量 {} 无法按 {} 个步长均分为宽高对",
+        self.anchors.len(),
+        self.strides.len()
+      )));
+    }
+
+    let classes = self.classes.resolve::<T>(self.object_thresh)?;
+    // 解码阈值与 NMS 的分数下限取允许类别中的最低阈值，各类别的阈值由 `classes` 检查
+    let object_thresh = classes.min_thresh();
+    Ok(YoloV5Postprocess {
+      object_thresh,
+      classes,
+      nms: self
+        .nms
+        .clone()
+        .default_mode(NmsMode::ClassAware)
+        .score_thresh(object_thresh),
+      strides: self.strides.clone(),
+      anchors: self.anchors.clone(),
+      sigmoid: self.sigmoid,
+      num_classes: label_num::<T>(),
+      _phantom: std::marker::PhantomData,
+    })
+  }
+
+  pub fn build_model<const W: u32, const H: u32, Frame>(
+    self,
+  ) -> Result<YoloV5<W, H, Frame>, YoloV5Error> {
+    let model = RknnModel::load(
+      &self.model_path,
+      self.flags,
+      Some(self.strides.len() as u32),
+    )?;
+    let num_anchors = self.anchors.len() / (2 * self.strides.len().max(1));
+    if let Some(num_labels) = self.num_labels
+      && num_anchors > 0
+    {
+      // 每个检测头的通道为每个锚框的 4 个边框值、目标分数与类别分数
+      for index in 0..self.strides.len() {
+        model
+          .info()
+          .check_output_labels(index, num_anchors, 5, num_labels)
+          .map_err(RknnModelError::from)?;
+      }
+    }
+    Ok(model)
+  }
+}
+
+impl<const W: u32, const H: u32, T: WithLabel> Postprocess for YoloV5Postprocess<W, H, T> {
+  type Input = Tensors;
+  type Output = DetectResult<T>;
+  type Error = YoloV5Error;
+
+  fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+    debug!("后处理模型输出");
+    if output.len() != self.strides.len() {
+      return Err(YoloV5Error::OutputMismatch(format!(
+        "输出数量 {} 与步长数量 {} 不匹配",
+        output.len(),
+        self.strides.len()
+      )));
+    }
+
+    let num_anchors = self.anchors.len() / (2 * self.strides.len());
+    let mut items = Vec::new();
+    for (i, (tensor, stride)) in output.iter().zip(self.strides.iter()).enumerate() {
+      let anchors = &self.anchors[i * 2 * num_anchors..(i + 1) * 2 * num_anchors];
+      self.decode_head(*stride, anchors, tensor.view(), &mut items)?;
+    }
+
+    self.classes.retain(&mut items);
+    let items = self.nms.suppress(items);
+    debug!("NMS 后检测数量: {}", items.len());
+
+    Ok(DetectResult {
+      items: items.into_boxed_slice(),
+    })
+  }
+}
+
+impl<const W: u32, const H: u32, T: WithLabel> YoloV5Postprocess<W, H, T> {
+  #[inline]
+  fn activate(&self, x: f32) -> f32 {
+    if self.sigmoid { sigmoid(x) } else { x }
+  }
+
+  fn decode_head(
+    &self,
+    stride: u32,
+    anchors: &[f32],
+    pred: TensorView,
+    items: &mut Vec<DetectItem<T>>,
+  ) -> Result<(), YoloV5Error> {
+    let grid_w = (W / stride) as usize;
+    let grid_h = (H / stride) as usize;
+    let grid = grid_w * grid_h;
+    let num_classes = self.num_classes;
+    let props = 5 + num_classes;
+    let num_anchors = anchors.len() / 2;
+
+    if pred.len() != num_anchors * props * grid {
+      return Err(YoloV5Error::OutputMismatch(format!(
+        "步长 {} 的输出大小为 {}, 预期为 {}",
+        stride,
+        pred.len(),
+        num_anchors * props * grid
+      )));
+    }
+
+    // 置信度阈值转换到激活前的域，量化输出直接在量化域中比较
+    let thresh = if self.sigmoid {
+      logit(self.object_thresh)
+    } else {
+      self.object_thresh
+    };
+    let stride = stride as f32;
+    for a in 0..num_anchors {
+      let base = a * props * grid;
+      let at = |k: usize, idx: usize| pred.get(base + k * grid + idx);
+      for gy in 0..grid_h {
+        for gx in 0..grid_w {
+          let idx = gy * grid_w + gx;
+          if !pred.exceeds(base + 4 * grid + idx, thresh) {
+            continue;
+          }
+
+          let objectness = self.activate(at(4, idx));
+          let Some((class_id, class_score)) = pred.argmax_allowed(
+            base + 5 * grid + idx,
+            num_classes,
+            grid,
+            self.classes.allowed(),
+          ) else {
+            continue;
+          };
+          let score = objectness * self.activate(class_score);
+          if score <= self.object_thresh {
+            continue;
+          }
+
+          let x = (self.activate(at(0, idx)) * 2.0 - 0.5 + gx as f32) * stride;
+          let y = (self.activate(at(1, idx)) * 2.0 - 0.5 + gy as f32) * stride;
+          let w = (self.activate(at(2, idx)) * 2.0).powi(2) * anchors[2 * a];
+          let h = (self.activate(at(3, idx)) * 2.0).powi(2) * anchors[2 * a + 1];
+
+          items.push(DetectItem::new(
+            T::from_label_id(class_id as u32),
+            score,
+            BBox {
+              x_min: x - w / 2.0,
+              y_min: y - h / 2.0,
+              x_max: x + w / 2.0,
+              y_max: y + h / 2.0,
+            },
+          ));
+        }
+      }
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use shanan_trait::{Postprocess, WithLabel};
+
+  use super::{YoloV5Builder, YoloV5Postprocess};
+  use crate::model::Tensor;
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct TestLabel(u32);
+
+  impl WithLabel for TestLabel {
+    const LABEL_NUM: u32 = 2;
+
+    fn from_label_id(id: u32) -> Self {
+      Self(id)
+    }
+
+    fn to_label_id(&self) -> u32 {
+      self.0
+    }
+
+    fn to_label_str(&self) -> String {
+      format!("class{}", self.0)
+    }
+  }
+
+  #[test]
+  fn decodes_anchor_and_grid() {
+    // 宽 64、高 32，步长 32 时网格为 2 列 1 行，每个位置 2 个锚框
+    let post: YoloV5Postprocess<64, 32, TestLabel> = YoloV5Builder::default()
+      .strides(vec![32])
+      .anchors(vec![10.0, 20.0, 30.0, 40.0])
+      .build_postprocess()
+      .unwrap();
+    // 每个锚框 7 个通道：x, y, w, h, 目标置信度, 两个类别分数
+    let grid = 2;
+    let mut pred = vec![0.0; 2 * 7 * grid];
+    let mut set = |anchor: usize, values: [f32; 7], idx: usize| {
+      for (k, v) in values.into_iter().enumerate() {
+        pred[anchor * 7 * grid + k * grid + idx] = v;
+      }
+    };
+    // 第 2 个锚框、第 2 列
+    set(1, [0.5, 0.75, 0.5, 0.25, 0.9, 0.1, 0.8], 1);
+    // 目标置信度低于阈值
+    set(0, [0.5, 0.5, 0.5, 0.5, 0.1, 0.9, 0.0], 0);
+
+    let result = post
+      .process(vec![Tensor::from_f32(vec![1, 14, 1, 2], pred)])
+      .unwrap();
+    assert_eq!(result.items.len(), 1);
+    let item = &result.items[0];
+    assert_eq!(item.kind, TestLabel(1));
+    assert!((item.score - 0.72).abs() < 1e-6);
+    // 中心 ((0.5 * 2 - 0.5 + 1) * 32, (0.75 * 2 - 0.5) * 32) = (48, 32)，
+    // 宽高 ((0.5 * 2)^2 * 30, (0.25 * 2)^2 * 40) = (30, 10)
+    assert_eq!(
+      [
+        item.bbox.x_min,
+        item.bbox.y_min,
+        item.bbox.x_max,
+        item.bbox.y_max
+      ],
+      [33.0, 27.0, 63.0, 37.0]
+    );
+  }
+
+  #[test]
+  fn rejects_mismatched_output() {
+    let post: YoloV5Postprocess<64, 32, TestLabel> = YoloV5Builder::default()
+      .strides(vec![32])
+      .anchors(vec![10.0, 20.0, 30.0, 40.0])
+      .build_postprocess()
+      .unwrap();
+    assert!(
+      post
+        .process(vec![Tensor::from_f32(vec![1, 12, 1, 2], vec![0.0; 24])])
+        .is_err()
+    );
+  }
+}
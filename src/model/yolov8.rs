@@ -0,0 +1,723 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/yolov8.rs - YOLOv8/YOLO11 检测头
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # YOLOv8/YOLO11 检测头
+//!
+//! YOLOv8 与 YOLO11 使用相同的 anchor-free 检测头：边框通过 DFL（分布焦点损失）
+//! 回归，类别分数独立输出，需要 NMS 去除重复框。支持两种输出布局：
+//!
+//! - 分支布局（rknn_model_zoo 导出）：每个步长依次输出边框 `[1, 4 * reg_max, h, w]`、
+//!   类别分数 `[1, C, h, w]`，以及可选的分数和 `[1, 1, h, w]`
+//! - 融合布局（ultralytics 导出）：单个输出 `[1, 4 + C, N]`，边框已解码为 `cx, cy, w, h`
+//!
+//! ## URL Scheme
+//!
+//! ```text
+//! yolov8:///path/to/yolov8n.rknn?object_thresh=0.25&iou_thresh=0.45
+//! ```
+//!
+//! ## 参数说明
+//!
+//! - `object_thresh`: 目标置信度阈值，默认 0.25
+//! - `class_thresh`、`classes`、`exclude_classes`、`class_file`: 按类别的阈值与过滤，见 [`ClassFilter`]
+//! - `nms`、`iou_thresh`、`max_det`、`soft_nms`: NMS 参数，见 [`Nms`]，默认按类别抑制
+//! - `reg_max`: DFL 分布的区间数量，默认 16
+//! - `strides`: 各分支的步长，逗号分隔，默认 `8,16,32`
+//! - `sigmoid`: 类别分数是否需要经过 sigmoid，默认 `false`
+
+use rknpu::InitFlags;
+use shanan_trait::Postprocess;
+use thiserror::Error;
+use tracing::debug;
+use url::Url;
+
+use crate::{
+  FromUrl, FromUrlWithScheme,
+  model::{
+    BBox, ClassFilter, ClassFilterError, ClassRules, DetectItem, DetectResult, ModelInfo, Nms,
+    NmsError, NmsMode, RknnModel, RknnModelError, Tensor, TensorView, Tensors, WithLabel,
+    label_num,
+  },
+  utils::{logit, sigmoid},
+};
+
+const YOLOV8_OBJECT_THRESH: f32 = 0.25;
+const YOLOV8_REG_MAX: usize = 16;
+const YOLOV8_STRIDES: [u32; 3] = [8, 16, 32];
+
+pub type YoloV8<const W: u32, const H: u32, Frame> = RknnModel<W, H, Frame>;
+pub type YoloV8Nhwc<const W: u32, const H: u32> = YoloV8<W, H, crate::frame::RgbNhwcFrame<W, H>>;
+
+#[derive(Error, Debug)]
+pub enum YoloV8Error {
+  #[error("RKNN 模型错误: {0}")]
+  RknnModelError(#[from] RknnModelError),
+  #[error("类别过滤配置错误: {0}")]
+  ClassFilterError(#[from] ClassFilterError),
+  #[error("NMS 配置错误: {0}")]
+  NmsError(#[from] NmsError),
+  #[error("模型路径错误: {0}")]
+  ModelPathError(String),
+  #[error("模型输出不匹配: {0}")]
+  OutputMismatch(String),
+}
+
+pub struct YoloV8Postprocess<const W: u32, const H: u32, T> {
+  object_thresh: f32,
+  pub(super) classes: ClassRules,
+  pub(super) nms: Nms,
+  reg_max: usize,
+  pub(super) strides: Vec<u32>,
+  sigmoid: bool,
+  /// 类别数量，构建时确定
+  num_classes: usize,
+  _phantom: std::marker::PhantomData<T>,
+}
+
+pub struct YoloV8Builder {
+  model_path: String,
+  flags: InitFlags,
+  object_thresh: f32,
+  classes: ClassFilter,
+  nms: Nms,
+  reg_max: usize,
+  strides: Vec<u32>,
+  sigmoid: bool,
+  num_labels: Option<usize>,
+}
+
+impl FromUrlWithScheme for YoloV8Builder {
+  const SCHEME: &'static str = "yolov8";
+}
+
+impl Default for YoloV8Builder {
+  fn default() -> Self {
+    Self {
+      model_path: String::new(),
+      flags: InitFlags::default(),
+      object_thresh: YOLOV8_OBJECT_THRESH,
+      classes: ClassFilter::default(),
+      nms: Nms::default(),
+      reg_max: YOLOV8_REG_MAX,
+      strides: YOLOV8_STRIDES.to_vec(),
+      sigmoid: false,
+      num_labels: None,
+    }
+  }
+}
+
+impl FromUrl for YoloV8Builder {
+  type Error = YoloV8Error;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    if url.scheme() != Self::SCHEME {
+      return Err(YoloV8Error::ModelPathError(format!(
+        "模型路径必须使用 {} 方案",
+        Self::SCHEME
+      )));
+    }
+    Self::from_query(url)
+  }
+}
+
+impl YoloV8Builder {
+  /// 从 URL 路径与查询参数构建，不检查方案，供分割等变体复用
+  pub(super) fn from_query(url: &Url) -> Result<Self, YoloV8Error> {
+    let mut builder = YoloV8Builder::default()
+      .model_path(url.path().to_string())
+      .classes(ClassFilter::from_url(url)?)
+      .nms(Nms::from_url(url)?);
+    for (k, v) in url.query_pairs() {
+      match k.as_ref() {
+        "object_thresh" => builder.object_thresh = v.parse().unwrap_or(YOLOV8_OBJECT_THRESH),
+        "reg_max" => builder.reg_max = v.parse().unwrap_or(YOLOV8_REG_MAX),
+        "strides" => {
+          builder.strides = v
+            .split(',')
+            .map(|s| s.trim().parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| YoloV8Error::ModelPathError(format!("无效的步长: {}", v)))?
+        }
+        "sigmoid" => builder.sigmoid = v.parse().unwrap_or(false),
+        _ => {}
+      }
+    }
+
+    Ok(builder)
+  }
+
+  pub fn flags(mut self, flags: InitFlags) -> Self {
+    self.flags = flags;
+    self
+  }
+
+  pub fn model_path(mut self, model_path: String) -> Self {
+    self.model_path = model_path;
+    self
+  }
+
+  pub fn object_thresh(mut self, object_thresh: f32) -> Self {
+    self.object_thresh = object_thresh;
+    self
+  }
+
+  pub fn iou_thresh(mut self, iou_thresh: f32) -> Self {
+    self.nms = self.nms.iou_thresh(iou_thresh);
+    self
+  }
+
+  pub fn classes(mut self, classes: ClassFilter) -> Self {
+    self.classes = classes;
+    self
+  }
+
+  pub fn nms(mut self, nms: Nms) -> Self {
+    self.nms = nms;
+    self
+  }
+
+  pub fn reg_max(mut self, reg_max: usize) -> Self {
+    self.reg_max = reg_max;
+    self
+  }
+
+  pub fn strides(mut self, strides: Vec<u32>) -> Self {
+    self.strides = strides;
+    self
+  }
+
+  pub fn sigmoid(mut self, sigmoid: bool) -> Self {
+    self.sigmoid = sigmoid;
+    self
+  }
+
+  /// 类别数量，构建模型时检查检测头的类别分数通道数，0 表示不检查
+  pub fn labels(mut self, num_labels: usize) -> Self {
+    self.num_labels = (num_labels > 0).then_some(num_labels);
+    self
+  }
+
+  pub fn build_postprocess<const W: u32, const H: u32, T: WithLabel>(
+    &self,
+  ) -> Result<YoloV8Postprocess<W, H, T>, YoloV8Error> {
+    if self.strides.is_empty() || self.strides.contains(&0) {
+      return Err(YoloV8Error::ModelPathError(format!(
+        "无效的步长: {:?}",
+        self.strides
+      )));
+    }
+
+    let classes = self.classes.resolve::<T>(self.object_thresh)?;
+    // 解码阈值与 NMS 的分数下限取允许类别中的最低阈值，各类别的阈值由 `classes` 检查
+    let object_thresh = classes.min_thresh();
+    Ok(YoloV8Postprocess {
+      object_thresh,
+      classes,
+      nms: self
+        .nms
+        .clone()
+        .default_mode(NmsMode::ClassAware)
+        .score_thresh(object_thresh),
+      reg_max: self.reg_max,
+      strides: self.strides.clone(),
+      sigmoid: self.sigmoid,
+      num_classes: label_num::<T>(),
+      _phantom: std::marker::PhantomData,
+    })
+  }
+
+  pub fn build_model<const W: u32, const H: u32, Frame>(
+    self,
+  ) -> Result<YoloV8<W, H, Frame>, YoloV8Error> {
+    self.build_model_with(0, |_| Some(0))
+  }
+
+  /// 加载模型，设置了类别数量时检查检测头的类别分数通道数
+  ///
+  /// 检测头之后的 `trailing` 个输出（掩码原型、关键点等）不检查。融合输出在类别分数之后还有
+  /// `extra` 返回的通道数，返回 `None` 时不检查融合输出。
+  pub(super) fn build_model_with<const W: u32, const H: u32, Frame>(
+    self,
+    trailing: usize,
+    extra: impl FnOnce(&ModelInfo) -> Option<usize>,
+  ) -> Result<YoloV8<W, H, Frame>, YoloV8Error> {
+    // 输出数量取决于导出方式，在后处理中检查
+    let model = RknnModel::load(&self.model_path, self.flags, None)?;
+    if let Some(num_labels) = self.num_labels {
+      let info = model.info();
+      let outputs = label_outputs(
+        info.outputs.len(),
+        trailing,
+        self.strides.len(),
+        self.reg_max,
+        extra(info),
+      );
+      for (index, extra) in outputs {
+        info
+          .check_output_labels(index, 1, extra, num_labels)
+          .map_err(RknnModelError::from)?;
+      }
+    }
+    Ok(model)
+  }
+}
+
+/// 包含类别分数的输出及其非类别通道数
+///
+/// 只有一个检测头时为融合输出，通道为边框、类别分数与 `extra` 个附加通道；否则每个分支为边框与
+/// 类别分数拼接的单个输出，或边框、类别分数以及可选的目标分数等多个输出。
+fn label_outputs(
+  outputs: usize,
+  trailing: usize,
+  branches: usize,
+  reg_max: usize,
+  extra: Option<usize>,
+) -> Vec<(usize, usize)> {
+  let heads = if outputs == 1 {
+    1
+  } else {
+    outputs.saturating_sub(trailing)
+  };
+  if heads == 1 {
+    return extra.map(|extra| (0, 4 + extra)).into_iter().collect();
+  }
+  if branches == 0 || heads % branches != 0 {
+    // 输出数量不匹配，在后处理中报告
+    return Vec::new();
+  }
+  let per_branch = heads / branches;
+  (0..branches)
+    .map(|i| {
+      if per_branch == 1 {
+        (i, 4 * reg_max)
+      } else {
+        (i * per_branch + 1, 0)
+      }
+    })
+    .collect()
+}
+
+impl<const W: u32, const H: u32, T: WithLabel> Postprocess for YoloV8Postprocess<W, H, T> {
+  type Input = Tensors;
+  type Output = DetectResult<T>;
+  type Error = YoloV8Error;
+
+  fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+    debug!("后处理模型输出");
+    let mut items = Vec::new();
+
+    if output.len() == 1 {
+      self.decode_fused(output[0].view(), 0, &mut |item, _| items.push(item))?;
+    } else {
+      for (stride, (boxes, scores)) in self.strides.iter().zip(self.split_heads(&output)?) {
+        self.decode_branch(*stride, boxes, scores, &mut |item, _| items.push(item))?;
+      }
+    }
+
+    self.classes.retain(&mut items);
+    let items = self.nms.suppress(items);
+    debug!("NMS 后检测数量: {}", items.len());
+
+    Ok(DetectResult {
+      items: items.into_boxed_slice(),
+    })
+  }
+}
+
+impl<const W: u32, const H: u32, T: WithLabel> YoloV8Postprocess<W, H, T> {
+  /// 查找允许类别中的最高分类别，分数未超过阈值时返回 `None`
+  ///
+  /// 比较在量化域中进行，只有通过阈值的候选才会被反量化。
+  fn best_class(&self, scores: &TensorView, len: usize, idx: usize) -> Option<(u32, f32)> {
+    let (class_id, _) =
+      scores.argmax_allowed(idx, self.num_classes, len, self.classes.allowed())?;
+    let pos = class_id * len + idx;
+    let thresh = if self.sigmoid {
+      logit(self.object_thresh)
+    } else {
+      self.object_thresh
+    };
+    if !scores.exceeds(pos, thresh) {
+      return None;
+    }
+
+    let score = scores.get(pos);
+    let score = if self.sigmoid { sigmoid(score) } else { score };
+    Some((class_id as u32, score))
+  }
+
+  /// 类别数量
+  pub(super) fn num_classes(&self) -> usize {
+    self.num_classes
+  }
+
+  /// 按分支拆分检测头输出，返回每个分支的边框与类别分数
+  ///
+  /// 每个分支可以是边框与类别分数拼接的单个输出，也可以是边框、类别分数以及可选的目标分数。
+  pub(super) fn split_heads<'a>(
+    &self,
+    heads: &'a [Tensor],
+  ) -> Result<Vec<(TensorView<'a>, TensorView<'a>)>, YoloV8Error> {
+    let branches = self.strides.len();
+    if heads.is_empty() || heads.len() % branches != 0 || heads.len() / branches > 3 {
+      return Err(YoloV8Error::OutputMismatch(format!(
+        "检测输出数量 {} 与步长数量 {} 不匹配",
+        heads.len(),
+        branches
+      )));
+    }
+    let per_branch = heads.len() / branches;
+
+    Ok(
+      self
+        .strides
+        .iter()
+        .enumerate()
+        .map(|(i, stride)| {
+          if per_branch == 1 {
+            // 边框与类别分数拼接在同一个输出中
+            let head = heads[i].view();
+            let grid = ((W / stride) * (H / stride)) as usize;
+            let split = head.len().saturating_sub(self.num_classes * grid);
+            (head.slice(0..split), head.slice(split..head.len()))
+          } else {
+            (
+              heads[i * per_branch].view(),
+              heads[i * per_branch + 1].view(),
+            )
+          }
+        })
+        .collect(),
+    )
+  }
+
+  /// 解码一个分支，每个候选连同其网格下标传给 `emit`
+  pub(super) fn decode_branch(
+    &self,
+    stride: u32,
+    boxes: TensorView,
+    scores: TensorView,
+    emit: &mut impl FnMut(DetectItem<T>, usize),
+  ) -> Result<(), YoloV8Error> {
+    let grid_w = (W / stride) as usize;
+    let grid_h = (H / stride) as usize;
+    let grid = grid_w * grid_h;
+    let num_classes = self.num_classes;
+
+    if boxes.len() != 4 * self.reg_max * grid || scores.len() != num_classes * grid {
+      return Err(YoloV8Error::OutputMismatch(format!(
+        "步长 {} 的输出大小为 {} / {}, 预期为 {} / {}",
+        stride,
+        boxes.len(),
+        scores.len(),
+        4 * self.reg_max * grid,
+        num_classes * grid
+      )));
+    }
+
+    let stride = stride as f32;
+    for gy in 0..grid_h {
+      for gx in 0..grid_w {
+        let idx = gy * grid_w + gx;
+        let Some((class_id, score)) = self.best_class(&scores, grid, idx) else {
+          continue;
+        };
+
+        // 左、上、右、下四个方向的距离
+        let mut dist = [0f32; 4];
+        for (side, d) in dist.iter_mut().enumerate() {
+          *d = self.dfl(&boxes, side, grid, idx);
+        }
+
+        let cx = gx as f32 + 0.5;
+        let cy = gy as f32 + 0.5;
+        emit(
+          DetectItem::new(
+            T::from_label_id(class_id),
+            score,
+            BBox {
+              x_min: (cx - dist[0]) * stride,
+              y_min: (cy - dist[1]) * stride,
+              x_max: (cx + dist[2]) * stride,
+              y_max: (cy + dist[3]) * stride,
+            },
+          ),
+          idx,
+        );
+      }
+    }
+
+    Ok(())
+  }
+
+  /// DFL 解码：对区间分布做 softmax 后求期望
+  fn dfl(&self, boxes: &TensorView, side: usize, grid: usize, idx: usize) -> f32 {
+    let bin = |j: usize| boxes.get((side * self.reg_max + j) * grid + idx);
+    let max = (0..self.reg_max).map(bin).fold(f32::MIN, f32::max);
+    let mut sum = 0.0;
+    let mut expect = 0.0;
+    for j in 0..self.reg_max {
+      let e = (bin(j) - max).exp();
+      sum += e;
+      expect += e * j as f32;
+    }
+    expect / sum
+  }
+
+  /// 解码融合输出，`extra` 为类别分数之后的附加行数（如掩码系数）
+  pub(super) fn decode_fused(
+    &self,
+    pred: TensorView,
+    extra: usize,
+    emit: &mut impl FnMut(DetectItem<T>, usize),
+  ) -> Result<(), YoloV8Error> {
+    let rows = 4 + self.num_classes + extra;
+    if pred.len() % rows != 0 {
+      return Err(YoloV8Error::OutputMismatch(format!(
+        "输出大小 {} 不是 {} 的整数倍",
+        pred.len(),
+        rows
+      )));
+    }
+
+    let len = pred.len() / rows;
+    for i in 0..len {
+      let Some((class_id, score)) = self.best_class(&pred, len, 4 * len + i) else {
+        continue;
+      };
+
+      let cx = pred.get(i);
+      let cy = pred.get(len + i);
+      let w = pred.get(2 * len + i);
+      let h = pred.get(3 * len + i);
+      emit(
+        DetectItem::new(
+          T::from_label_id(class_id),
+          score,
+          BBox {
+            x_min: cx - w / 2.0,
+            y_min: cy - h / 2.0,
+            x_max: cx + w / 2.0,
+            y_max: cy + h / 2.0,
+          },
+        ),
+        i,
+      );
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use shanan_trait::{Postprocess, WithLabel};
+
+  use super::{YoloV8Builder, YoloV8Postprocess, label_outputs};
+  use crate::model::{ClassFilter, DetectResult, Nms, NmsMethod, Quantization, Tensor};
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct TestLabel(u32);
+
+  impl WithLabel for TestLabel {
+    const LABEL_NUM: u32 = 3;
+
+    fn from_label_id(id: u32) -> Self {
+      Self(id)
+    }
+
+    fn to_label_id(&self) -> u32 {
+      self.0
+    }
+
+    fn to_label_str(&self) -> String {
+      format!("class{}", self.0)
+    }
+  }
+
+  /// 线性同余生成的伪随机字节
+  fn random_bytes(len: usize, seed: u32) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(2_654_435_761).wrapping_add(1);
+    (0..len)
+      .map(|_| {
+        state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        (state >> 16) as u8
+      })
+      .collect()
+  }
+
+  fn dequantized(tensors: &[Tensor]) -> Vec<Tensor> {
+    tensors
+      .iter()
+      .map(|t| Tensor::from_f32(t.shape.clone(), t.to_f32().into_owned()))
+      .collect()
+  }
+
+  fn assert_same(actual: &DetectResult<TestLabel>, expected: &DetectResult<TestLabel>) {
+    assert_eq!(actual.items.len(), expected.items.len());
+    for (a, e) in actual.items.iter().zip(expected.items.iter()) {
+      assert_eq!(a.kind, e.kind);
+      assert_eq!(a.score, e.score);
+      assert_eq!(
+        [a.bbox.x_min, a.bbox.y_min, a.bbox.x_max, a.bbox.y_max],
+        [e.bbox.x_min, e.bbox.y_min, e.bbox.x_max, e.bbox.y_max]
+      );
+    }
+  }
+
+  #[test]
+  fn quantized_branch_decode_matches_f32() {
+    let post: YoloV8Postprocess<64, 64, TestLabel> = YoloV8Builder::default()
+      .strides(vec![16, 32])
+      .build_postprocess()
+      .unwrap();
+    let box_q = Quantization::new(0.08, -10);
+    let score_q = Quantization::new(1.0 / 255.0, 0);
+    let mut output = Vec::new();
+    for (i, size) in [4usize, 2].into_iter().enumerate() {
+      let grid = size * size;
+      let boxes = random_bytes(4 * 16 * grid, i as u32);
+      output.push(Tensor::from_i8(
+        vec![1, 64, size, size],
+        boxes.into_iter().map(|v| v as i8).collect(),
+        box_q,
+      ));
+      output.push(Tensor::from_u8(
+        vec![1, 3, size, size],
+        random_bytes(3 * grid, 10 + i as u32),
+        score_q,
+      ));
+    }
+
+    let quantized = post.process(output.clone()).unwrap();
+    let float = post.process(dequantized(&output)).unwrap();
+    assert!(!quantized.is_empty());
+    assert_same(&quantized, &float);
+  }
+
+  #[test]
+  fn quantized_fused_decode_matches_f32() {
+    let post: YoloV8Postprocess<64, 64, TestLabel> =
+      YoloV8Builder::default().build_postprocess().unwrap();
+    let output = vec![Tensor::from_u8(
+      vec![1, 7, 32],
+      random_bytes(7 * 32, 3),
+      Quantization::new(0.25, 0),
+    )];
+
+    let quantized = post.process(output.clone()).unwrap();
+    let float = post.process(dequantized(&output)).unwrap();
+    assert!(!quantized.is_empty());
+    assert_same(&quantized, &float);
+  }
+
+  #[test]
+  fn label_outputs_follow_export_layout() {
+    // 融合输出
+    assert_eq!(label_outputs(1, 0, 3, 16, Some(0)), [(0, 4)]);
+    // 分割模型的融合输出与掩码原型
+    assert_eq!(label_outputs(2, 1, 3, 16, Some(32)), [(0, 36)]);
+    assert!(label_outputs(2, 1, 3, 16, None).is_empty());
+    // 每个分支为边框与类别分数
+    assert_eq!(
+      label_outputs(6, 0, 3, 16, Some(0)),
+      [(1, 0), (3, 0), (5, 0)]
+    );
+    // 每个分支另有目标分数，之后为关键点输出
+    assert_eq!(
+      label_outputs(10, 1, 3, 16, Some(51)),
+      [(1, 0), (4, 0), (7, 0)]
+    );
+    // 每个分支为拼接的单个输出
+    assert_eq!(
+      label_outputs(4, 1, 3, 16, Some(1)),
+      [(0, 64), (1, 64), (2, 64)]
+    );
+    // 输出数量不匹配
+    assert!(label_outputs(5, 0, 3, 16, Some(0)).is_empty());
+  }
+
+  #[test]
+  fn excluded_top_class_falls_back_to_allowed_class() {
+    let post: YoloV8Postprocess<64, 64, TestLabel> = YoloV8Builder::default()
+      .strides(vec![32])
+      .classes(ClassFilter::default().exclude("class0"))
+      .build_postprocess()
+      .unwrap();
+    let mut scores = vec![0.0; 3 * 4];
+    scores[0] = 0.9;
+    scores[4] = 0.6;
+    let output = vec![
+      Tensor::from_f32(vec![1, 64, 2, 2], vec![0.0; 64 * 4]),
+      Tensor::from_f32(vec![1, 3, 2, 2], scores),
+    ];
+
+    let result = post.process(output).unwrap();
+    assert_eq!(result.items.len(), 1);
+    assert_eq!(result.items[0].kind, TestLabel(1));
+    assert_eq!(result.items[0].score, 0.6);
+  }
+
+  #[test]
+  fn non_square_grid_follows_width_and_height() {
+    // 宽 64、高 32，步长 32 时网格为 2 列 1 行
+    let post: YoloV8Postprocess<64, 32, TestLabel> = YoloV8Builder::default()
+      .strides(vec![32])
+      .build_postprocess()
+      .unwrap();
+    let mut scores = vec![0.0; 3 * 2];
+    scores[1] = 0.9;
+    let output = vec![
+      Tensor::from_f32(vec![1, 64, 1, 2], vec![0.0; 64 * 2]),
+      Tensor::from_f32(vec![1, 3, 1, 2], scores),
+    ];
+
+    let result = post.process(output).unwrap();
+    assert_eq!(result.items.len(), 1);
+    let bbox = &result.items[0].bbox;
+    assert_eq!(
+      [
+        (bbox.x_min + bbox.x_max) / 2.0,
+        (bbox.y_min + bbox.y_max) / 2.0
+      ],
+      [48.0, 16.0]
+    );
+  }
+
+  #[test]
+  fn class_threshold_below_object_thresh_survives_soft_nms() {
+    // Soft-NMS 的分数下限取类别阈值中的最低值，而不是 object_thresh
+    let post: YoloV8Postprocess<64, 64, TestLabel> = YoloV8Builder::default()
+      .object_thresh(0.5)
+      .strides(vec![32])
+      .classes(ClassFilter::default().threshold("class1", 0.2))
+      .nms(Nms::default().method(NmsMethod::SoftLinear))
+      .build_postprocess()
+      .unwrap();
+    let mut scores = vec![0.0; 3 * 4];
+    scores[0] = 0.9;
+    scores[4 + 3] = 0.3;
+    let output = vec![
+      Tensor::from_f32(vec![1, 64, 2, 2], vec![0.0; 64 * 4]),
+      Tensor::from_f32(vec![1, 3, 2, 2], scores),
+    ];
+
+    let result = post.process(output).unwrap();
+    let kept: Vec<_> = result
+      .items
+      .iter()
+      .map(|item| (item.kind, item.score))
+      .collect();
+    assert_eq!(kept, [(TestLabel(0), 0.9), (TestLabel(1), 0.3)]);
+  }
+}
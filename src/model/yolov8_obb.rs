@@ -0,0 +1,306 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/yolov8_obb.rs - YOLOv8/YOLO11 旋转检测
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # YOLOv8/YOLO11 旋转检测
+//!
+//! 旋转检测模型在检测头之外为每个候选输出一个旋转角，结果为 [`RotatedBox`]，
+//! NMS 使用旋转交并比。支持两种输出布局：
+//!
+//! - 分支布局（rknn_model_zoo 导出）：每个步长输出检测头（边框与类别分数拼接为一个输出，
+//!   或与 YOLOv8 相同的分开输出），最后一个输出为所有候选的旋转角 `[1, 1, N]`，
+//!   候选按步长、网格行优先排列。旋转角为原始值，按 `(sigmoid(x) - 0.25) * π` 解码
+//! - 融合布局（ultralytics 导出）：`[1, 4 + C + 1, N]`，边框为 `cx, cy, w, h`，旋转角为弧度
+//!
+//! ## URL Scheme
+//!
+//! ```text
+//! yolov8-obb:///path/to/yolov8n-obb.rknn?object_thresh=0.25&iou_thresh=0.45
+//! ```
+//!
+//! ## 参数说明
+//!
+//! - `angle_raw`: 分支布局的旋转角是否需要解码，默认 `true`
+//! - 其余参数与 [`YoloV8Builder`] 相同
+
+use std::f32::consts::PI;
+
+use shanan_trait::Postprocess;
+use thiserror::Error;
+use tracing::debug;
+use url::Url;
+
+use crate::{
+  FromUrl, FromUrlWithScheme,
+  model::{
+    DetectItem, DetectResult, RotatedBox, Tensor, TensorView, Tensors, WithLabel, YoloV8,
+    YoloV8Builder, YoloV8Error, YoloV8Postprocess,
+  },
+  utils::sigmoid,
+};
+
+#[derive(Error, Debug)]
+pub enum YoloV8ObbError {
+  #[error("YOLOv8 错误: {0}")]
+  YoloV8Error(#[from] YoloV8Error),
+  #[error("模型路径错误: {0}")]
+  ModelPathError(String),
+  #[error("模型输出不匹配: {0}")]
+  OutputMismatch(String),
+}
+
+pub struct YoloV8ObbPostprocess<const W: u32, const H: u32, T> {
+  detect: YoloV8Postprocess<W, H, T>,
+  angle_raw: bool,
+}
+
+pub struct YoloV8ObbBuilder {
+  detect: YoloV8Builder,
+  angle_raw: bool,
+}
+
+impl FromUrlWithScheme for YoloV8ObbBuilder {
+  const SCHEME: &'static str = "yolov8-obb";
+}
+
+impl Default for YoloV8ObbBuilder {
+  fn default() -> Self {
+    Self {
+      detect: YoloV8Builder::default(),
+      angle_raw: true,
+    }
+  }
+}
+
+impl FromUrl for YoloV8ObbBuilder {
+  type Error = YoloV8ObbError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    if url.scheme() != Self::SCHEME {
+      return Err(YoloV8ObbError::ModelPathError(format!(
+        "模型路径必须使用 {} 方案",
+        Self::SCHEME
+      )));
+    }
+
+    let mut builder = YoloV8ObbBuilder::default().detect(YoloV8Builder::from_query(url)?);
+    for (k, v) in url.query_pairs() {
+      if k == "angle_raw" {
+        builder.angle_raw = v.parse().unwrap_or(true);
+      }
+    }
+
+    Ok(builder)
+  }
+}
+
+impl YoloV8ObbBuilder {
+  /// 检测部分的参数
+  pub fn detect(mut self, detect: YoloV8Builder) -> Self {
+    self.detect = detect;
+    self
+  }
+
+  /// 类别数量，见 [`YoloV8Builder::labels`]
+  pub fn labels(mut self, num_labels: usize) -> Self {
+    self.detect = self.detect.labels(num_labels);
+    self
+  }
+
+  pub fn angle_raw(mut self, angle_raw: bool) -> Self {
+    self.angle_raw = angle_raw;
+    self
+  }
+
+  pub fn build_postprocess<const W: u32, const H: u32, T: WithLabel>(
+    &self,
+  ) -> Result<YoloV8ObbPostprocess<W, H, T>, YoloV8ObbError> {
+    Ok(YoloV8ObbPostprocess {
+      detect: self.detect.build_postprocess()?,
+      angle_raw: self.angle_raw,
+    })
+  }
+
+  pub fn build_model<const W: u32, const H: u32, Frame>(
+    self,
+  ) -> Result<YoloV8<W, H, Frame>, YoloV8ObbError> {
+    Ok(self.detect.build_model_with(1, |_| Some(1))?)
+  }
+}
+
+impl<const W: u32, const H: u32, T: WithLabel> Postprocess for YoloV8ObbPostprocess<W, H, T> {
+  type Input = Tensors;
+  type Output = DetectResult<T>;
+  type Error = YoloV8ObbError;
+
+  fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+    debug!("后处理旋转检测模型输出");
+    let mut items: Vec<DetectItem<T>> = Vec::new();
+
+    if output.len() == 1 {
+      let pred = output[0].view();
+      let len = pred.len() / (4 + self.detect.num_classes() + 1);
+      let angle_base = (4 + self.detect.num_classes()) * len;
+      self.detect.decode_fused(pred, 1, &mut |mut item, i| {
+        let bbox = item.bbox;
+        item.obb = Some(RotatedBox {
+          cx: (bbox.x_min + bbox.x_max) / 2.0,
+          cy: (bbox.y_min + bbox.y_max) / 2.0,
+          width: bbox.width(),
+          height: bbox.height(),
+          angle: pred.get(angle_base + i),
+        });
+        items.push(item);
+      })?;
+    } else {
+      let Some((angles, heads)) = output.split_last() else {
+        return Err(YoloV8ObbError::OutputMismatch("模型没有输出".to_string()));
+      };
+      self.decode_branches(heads, angles.view(), &mut items)?;
+    }
+
+    // 外接矩形与旋转边框保持一致
+    for item in items.iter_mut() {
+      if let Some(obb) = &item.obb {
+        item.bbox = obb.bounds();
+      }
+    }
+
+    self.detect.classes.retain(&mut items);
+    let items = self.detect.nms.suppress(items);
+    debug!("NMS 后检测数量: {}", items.len());
+
+    Ok(DetectResult {
+      items: items.into_boxed_slice(),
+    })
+  }
+}
+
+impl<const W: u32, const H: u32, T: WithLabel> YoloV8ObbPostprocess<W, H, T> {
+  fn decode_branches(
+    &self,
+    heads: &[Tensor],
+    angles: TensorView,
+    items: &mut Vec<DetectItem<T>>,
+  ) -> Result<(), YoloV8ObbError> {
+    let strides = &self.detect.strides;
+    let branches = self.detect.split_heads(heads)?;
+
+    let grids: Vec<usize> = strides
+      .iter()
+      .map(|s| ((W / s) * (H / s)) as usize)
+      .collect();
+    let total: usize = grids.iter().sum();
+    if angles.len() != total {
+      return Err(YoloV8ObbError::OutputMismatch(format!(
+        "旋转角输出大小为 {}, 预期为 {}",
+        angles.len(),
+        total
+      )));
+    }
+
+    let mut offset = 0;
+    for ((stride, grid), (boxes, scores)) in strides.iter().zip(&grids).zip(branches) {
+      let grid_w = (W / stride) as usize;
+      let s = *stride as f32;
+      self
+        .detect
+        .decode_branch(*stride, boxes, scores, &mut |mut item, idx| {
+          let raw = angles.get(offset + idx);
+          let angle = if self.angle_raw {
+            (sigmoid(raw) - 0.25) * PI
+          } else {
+            raw
+          };
+
+          // 检测头按轴对齐解码，由边框还原到网格中心的左、上、右、下距离后按旋转角重新解码
+          let gx = (idx % grid_w) as f32 + 0.5;
+          let gy = (idx / grid_w) as f32 + 0.5;
+          let bbox = item.bbox;
+          let (l, t) = (gx - bbox.x_min / s, gy - bbox.y_min / s);
+          let (r, b) = (bbox.x_max / s - gx, bbox.y_max / s - gy);
+          let (xf, yf) = ((r - l) / 2.0, (b - t) / 2.0);
+          let (sin, cos) = angle.sin_cos();
+          item.obb = Some(RotatedBox {
+            cx: (xf * cos - yf * sin + gx) * s,
+            cy: (xf * sin + yf * cos + gy) * s,
+            width: (l + r) * s,
+            height: (t + b) * s,
+            angle,
+          });
+          items.push(item);
+        })?;
+      offset += grid;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::f32::consts::FRAC_PI_4;
+
+  use shanan_trait::{Postprocess, WithLabel};
+
+  use super::{YoloV8ObbBuilder, YoloV8ObbPostprocess};
+  use crate::model::Tensor;
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct TestLabel(u32);
+
+  impl WithLabel for TestLabel {
+    const LABEL_NUM: u32 = 1;
+
+    fn from_label_id(id: u32) -> Self {
+      Self(id)
+    }
+
+    fn to_label_id(&self) -> u32 {
+      self.0
+    }
+
+    fn to_label_str(&self) -> String {
+      format!("class{}", self.0)
+    }
+  }
+
+  #[test]
+  fn suppresses_by_rotated_iou() {
+    let post: YoloV8ObbPostprocess<64, 64, TestLabel> =
+      YoloV8ObbBuilder::default().build_postprocess().unwrap();
+    // 融合输出，三个 40x8 的旋转框：A 与 B 十字交叉，外接矩形相同但旋转交并比约为 0.11；
+    // C 为 A 平移 1 像素，旋转交并比约为 0.81
+    let pred = vec![
+      32.0, 32.0, 33.0, // cx
+      32.0, 32.0, 32.0, // cy
+      40.0, 40.0, 40.0, // w
+      8.0, 8.0, 8.0, // h
+      0.9, 0.8, 0.7, // 分数
+      FRAC_PI_4, -FRAC_PI_4, FRAC_PI_4, // 旋转角
+    ];
+
+    let result = post
+      .process(vec![Tensor::from_f32(vec![1, 6, 3], pred)])
+      .unwrap();
+    let kept: Vec<_> = result.items.iter().map(|item| item.score).collect();
+    assert_eq!(kept, [0.9, 0.8]);
+
+    // 外接矩形由旋转框计算，半宽为 (20 + 4) * cos(π/4)
+    let item = &result.items[0];
+    let obb = item.obb.unwrap();
+    assert_eq!(obb.angle, FRAC_PI_4);
+    let half = 24.0 * FRAC_PI_4.cos();
+    assert!((item.bbox.x_min - (32.0 - half)).abs() < 1e-4);
+    assert!((item.bbox.y_max - (32.0 + half)).abs() < 1e-4);
+  }
+}
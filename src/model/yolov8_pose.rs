@@ -0,0 +1,354 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/yolov8_pose.rs - YOLOv8/YOLO11 姿态估计
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # YOLOv8/YOLO11 姿态估计
+//!
+//! 姿态模型在检测头之外为每个候选输出 K 个关键点 `(x, y, 置信度)`。检测部分与
+//! [`YoloV8Postprocess`] 相同，关键点随检测一起经过类别过滤与 NMS。支持两种输出布局：
+//!
+//! - 分支布局（rknn_model_zoo 导出）：每个步长输出检测头（边框与类别分数拼接为一个输出，
+//!   或与 YOLOv8 相同的分开输出），最后一个输出为所有候选的关键点 `[1, K, 3, N]`，
+//!   候选按步长、网格行优先排列。关键点为未解码的原始值，按
+//!   `x = (x * 2 + gx) * stride` 解码，置信度经过 sigmoid
+//! - 融合布局（ultralytics 导出）：`[1, 4 + C + 3K, N]`，关键点已解码为像素坐标
+//!
+//! ## URL Scheme
+//!
+//! ```text
+//! yolov8-pose:///path/to/yolov8n-pose.rknn?object_thresh=0.25
+//! ```
+//!
+//! ## 参数说明
+//!
+//! - `keypoints`: 关键点数量，默认 17（COCO 人体关键点）
+//! - `skeleton`: 骨架连接，形如 `0-1,1-2`，默认 COCO 人体骨架
+//! - `kpt_raw`: 分支布局的关键点是否需要解码，默认 `true`
+//! - 其余参数与 [`YoloV8Builder`] 相同
+
+use std::sync::Arc;
+
+use shanan_trait::Postprocess;
+use thiserror::Error;
+use tracing::debug;
+use url::Url;
+
+use crate::{
+  FromUrl, FromUrlWithScheme,
+  model::{
+    COCO_KEYPOINTS, COCO_SKELETON, DetectItem, DetectResult, Keypoint, Pose, Tensor, TensorView,
+    Tensors, WithLabel, YoloV8, YoloV8Builder, YoloV8Error, YoloV8Postprocess, parse_skeleton,
+  },
+  utils::sigmoid,
+};
+
+#[derive(Error, Debug)]
+pub enum YoloV8PoseError {
+  #[error("YOLOv8 错误: {0}")]
+  YoloV8Error(#[from] YoloV8Error),
+  #[error("模型路径错误: {0}")]
+  ModelPathError(String),
+  #[error("无效的骨架定义: {0}")]
+  SkeletonError(String),
+  #[error("模型输出不匹配: {0}")]
+  OutputMismatch(String),
+}
+
+pub struct YoloV8PosePostprocess<const W: u32, const H: u32, T> {
+  detect: YoloV8Postprocess<W, H, T>,
+  num_keypoints: usize,
+  skeleton: Arc<[(usize, usize)]>,
+  kpt_raw: bool,
+}
+
+pub struct YoloV8PoseBuilder {
+  detect: YoloV8Builder,
+  num_keypoints: usize,
+  skeleton: Vec<(usize, usize)>,
+  kpt_raw: bool,
+}
+
+impl FromUrlWithScheme for YoloV8PoseBuilder {
+  const SCHEME: &'static str = "yolov8-pose";
+}
+
+impl Default for YoloV8PoseBuilder {
+  fn default() -> Self {
+    Self {
+      detect: YoloV8Builder::default(),
+      num_keypoints: COCO_KEYPOINTS,
+      skeleton: COCO_SKELETON.to_vec(),
+      kpt_raw: true,
+    }
+  }
+}
+
+impl FromUrl for YoloV8PoseBuilder {
+  type Error = YoloV8PoseError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    if url.scheme() != Self::SCHEME {
+      return Err(YoloV8PoseError::ModelPathError(format!(
+        "模型路径必须使用 {} 方案",
+        Self::SCHEME
+      )));
+    }
+
+    let mut builder = YoloV8PoseBuilder::default().detect(YoloV8Builder::from_query(url)?);
+    for (k, v) in url.query_pairs() {
+      match k.as_ref() {
+        "keypoints" => builder.num_keypoints = v.parse().unwrap_or(COCO_KEYPOINTS),
+        "skeleton" => {
+          builder.skeleton =
+            parse_skeleton(&v).ok_or_else(|| YoloV8PoseError::SkeletonError(v.to_string()))?
+        }
+        "kpt_raw" => builder.kpt_raw = v.parse().unwrap_or(true),
+        _ => {}
+      }
+    }
+
+    Ok(builder)
+  }
+}
+
+impl YoloV8PoseBuilder {
+  /// 检测部分的参数
+  pub fn detect(mut self, detect: YoloV8Builder) -> Self {
+    self.detect = detect;
+    self
+  }
+
+  /// 类别数量，见 [`YoloV8Builder::labels`]
+  pub fn labels(mut self, num_labels: usize) -> Self {
+    self.detect = self.detect.labels(num_labels);
+    self
+  }
+
+  pub fn num_keypoints(mut self, num_keypoints: usize) -> Self {
+    self.num_keypoints = num_keypoints;
+    self
+  }
+
+  pub fn skeleton(mut self, skeleton: Vec<(usize, usize)>) -> Self {
+    self.skeleton = skeleton;
+    self
+  }
+
+  pub fn kpt_raw(mut self, kpt_raw: bool) -> Self {
+    self.kpt_raw = kpt_raw;
+    self
+  }
+
+  pub fn build_postprocess<const W: u32, const H: u32, T: WithLabel>(
+    &self,
+  ) -> Result<YoloV8PosePostprocess<W, H, T>, YoloV8PoseError> {
+    if let Some((a, b)) = self
+      .skeleton
+      .iter()
+      .find(|(a, b)| *a >= self.num_keypoints || *b >= self.num_keypoints)
+    {
+      return Err(YoloV8PoseError::SkeletonError(format!(
+        "连接 {}-{} 超出关键点数量 {}",
+        a, b, self.num_keypoints
+      )));
+    }
+
+    Ok(YoloV8PosePostprocess {
+      detect: self.detect.build_postprocess()?,
+      num_keypoints: self.num_keypoints,
+      skeleton: self.skeleton.clone().into(),
+      kpt_raw: self.kpt_raw,
+    })
+  }
+
+  pub fn build_model<const W: u32, const H: u32, Frame>(
+    self,
+  ) -> Result<YoloV8<W, H, Frame>, YoloV8PoseError> {
+    let extra = 3 * self.num_keypoints;
+    Ok(self.detect.build_model_with(1, |_| Some(extra))?)
+  }
+}
+
+impl<const W: u32, const H: u32, T: WithLabel> Postprocess for YoloV8PosePostprocess<W, H, T> {
+  type Input = Tensors;
+  type Output = DetectResult<T>;
+  type Error = YoloV8PoseError;
+
+  fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+    debug!("后处理姿态模型输出");
+    let k = self.num_keypoints;
+    let mut items: Vec<(DetectItem<T>, Vec<Keypoint>)> = Vec::new();
+
+    if output.len() == 1 {
+      let pred = output[0].view();
+      let len = pred.len() / (4 + self.detect.num_classes() + 3 * k);
+      let kpt_base = (4 + self.detect.num_classes()) * len;
+      self.detect.decode_fused(pred, 3 * k, &mut |item, i| {
+        let at = |j: usize| pred.get(kpt_base + j * len + i);
+        let keypoints = (0..k).map(|p| Keypoint {
+          x: at(3 * p),
+          y: at(3 * p + 1),
+          score: at(3 * p + 2),
+        });
+        items.push((item, keypoints.collect()));
+      })?;
+    } else {
+      let Some((kpts, heads)) = output.split_last() else {
+        return Err(YoloV8PoseError::OutputMismatch("模型没有输出".to_string()));
+      };
+      self.decode_branches(heads, kpts.view(), &mut items)?;
+    }
+
+    let classes = &self.detect.classes;
+    items.retain(|(item, _)| classes.accept(item.kind.to_label_id(), item.score));
+    let results: Vec<DetectItem<T>> = self
+      .detect
+      .nms
+      .suppress_with(items)
+      .into_iter()
+      .map(|(mut item, keypoints)| {
+        item.keypoints = Some(Pose {
+          keypoints,
+          skeleton: self.skeleton.clone(),
+        });
+        item
+      })
+      .collect();
+    debug!("NMS 后实例数量: {}", results.len());
+
+    Ok(DetectResult {
+      items: results.into_boxed_slice(),
+    })
+  }
+}
+
+impl<const W: u32, const H: u32, T: WithLabel> YoloV8PosePostprocess<W, H, T> {
+  fn decode_branches(
+    &self,
+    heads: &[Tensor],
+    kpts: TensorView,
+    items: &mut Vec<(DetectItem<T>, Vec<Keypoint>)>,
+  ) -> Result<(), YoloV8PoseError> {
+    let strides = &self.detect.strides;
+    let branches = self.detect.split_heads(heads)?;
+
+    let grids: Vec<usize> = strides
+      .iter()
+      .map(|s| ((W / s) * (H / s)) as usize)
+      .collect();
+    let total: usize = grids.iter().sum();
+    let k = self.num_keypoints;
+    if kpts.len() != 3 * k * total {
+      return Err(YoloV8PoseError::OutputMismatch(format!(
+        "关键点输出大小为 {}, 预期为 {}",
+        kpts.len(),
+        3 * k * total
+      )));
+    }
+
+    let mut offset = 0;
+    for ((stride, grid), (boxes, scores)) in strides.iter().zip(&grids).zip(branches) {
+      let grid_w = (W / stride) as usize;
+      let s = *stride as f32;
+      self
+        .detect
+        .decode_branch(*stride, boxes, scores, &mut |item, idx| {
+          let n = offset + idx;
+          let at = |p: usize, j: usize| kpts.get((3 * p + j) * total + n);
+          let (gx, gy) = ((idx % grid_w) as f32, (idx / grid_w) as f32);
+          let keypoints = (0..k).map(|p| {
+            if self.kpt_raw {
+              Keypoint {
+                x: (at(p, 0) * 2.0 + gx) * s,
+                y: (at(p, 1) * 2.0 + gy) * s,
+                score: sigmoid(at(p, 2)),
+              }
+            } else {
+              Keypoint {
+                x: at(p, 0),
+                y: at(p, 1),
+                score: at(p, 2),
+              }
+            }
+          });
+          items.push((item, keypoints.collect()));
+        })?;
+      offset += grid;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use shanan_trait::{Postprocess, WithLabel};
+
+  use super::{YoloV8PoseBuilder, YoloV8PosePostprocess};
+  use crate::model::{Tensor, YoloV8Builder};
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct TestLabel(u32);
+
+  impl WithLabel for TestLabel {
+    const LABEL_NUM: u32 = 1;
+
+    fn from_label_id(id: u32) -> Self {
+      Self(id)
+    }
+
+    fn to_label_id(&self) -> u32 {
+      self.0
+    }
+
+    fn to_label_str(&self) -> String {
+      format!("class{}", self.0)
+    }
+  }
+
+  #[test]
+  fn scales_raw_keypoints_by_grid_and_stride() {
+    // 宽 64、高 32，步长 16 的网格为 4x2，步长 32 的网格为 2x1，共 10 个候选
+    let post: YoloV8PosePostprocess<64, 32, TestLabel> = YoloV8PoseBuilder::default()
+      .detect(YoloV8Builder::default().strides(vec![16, 32]))
+      .num_keypoints(2)
+      .skeleton(vec![(0, 1)])
+      .build_postprocess()
+      .unwrap();
+    // 步长 32 的第 2 列，即所有候选中的第 10 个
+    let mut scores = vec![0.0; 2];
+    scores[1] = 0.9;
+    let total = 10;
+    let mut kpts = vec![0.0; 2 * 3 * total];
+    for (j, v) in [0.25, 0.5, 0.0, -0.5, 0.25, 2.0].into_iter().enumerate() {
+      kpts[j * total + 9] = v;
+    }
+    let output = vec![
+      Tensor::from_f32(vec![1, 64, 2, 4], vec![0.0; 64 * 8]),
+      Tensor::from_f32(vec![1, 1, 2, 4], vec![0.0; 8]),
+      Tensor::from_f32(vec![1, 64, 1, 2], vec![0.0; 64 * 2]),
+      Tensor::from_f32(vec![1, 1, 1, 2], scores),
+      Tensor::from_f32(vec![1, 2, 3, total], kpts),
+    ];
+
+    let result = post.process(output).unwrap();
+    assert_eq!(result.items.len(), 1);
+    let pose = result.items[0].keypoints.as_ref().unwrap();
+    // x = (x * 2 + gx) * stride，y = (y * 2 + gy) * stride，置信度经过 sigmoid
+    let keypoints: Vec<_> = pose.keypoints.iter().map(|p| (p.x, p.y)).collect();
+    assert_eq!(keypoints, [(48.0, 32.0), (0.0, 16.0)]);
+    assert_eq!(pose.keypoints[0].score, 0.5);
+    assert!((pose.keypoints[1].score - 0.880_797).abs() < 1e-6);
+    assert_eq!(pose.limbs(0.6).count(), 0);
+    assert_eq!(pose.limbs(0.4).count(), 1);
+  }
+}
@@ -0,0 +1,370 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/model/yolov8_seg.rs - YOLOv8/YOLO11 实例分割
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # YOLOv8/YOLO11 实例分割
+//!
+//! 分割模型在检测头之外为每个候选输出掩码系数，并输出一组掩码原型
+//! `[1, nm, H / 4, W / 4]`。检测部分与 [`YoloV8Postprocess`] 相同；NMS 之后，
+//! 每个实例的掩码为系数与原型的线性组合经 sigmoid 后裁剪到检测框，再双线性放大到输入分辨率。
+//! 支持两种输出布局，掩码原型均为最后一个输出：
+//!
+//! - 分支布局（rknn_model_zoo 导出）：每个步长依次输出边框、类别分数、可选的分数和，
+//!   以及掩码系数 `[1, nm, h, w]`
+//! - 融合布局（ultralytics 导出）：`[1, 4 + C + nm, N]`
+//!
+//! ## URL Scheme
+//!
+//! ```text
+//! yolov8-seg:///path/to/yolov8n-seg.rknn?object_thresh=0.25&mask_thresh=0.5
+//! ```
+//!
+//! ## 参数说明
+//!
+//! - `mask_thresh`: 掩码二值化阈值，默认 0.5
+//! - 其余参数与 [`YoloV8Builder`] 相同
+
+use shanan_trait::Postprocess;
+use thiserror::Error;
+use tracing::debug;
+use url::Url;
+
+use crate::{
+  FromUrl, FromUrlWithScheme,
+  model::{
+    BBox, DetectItem, DetectResult, Mask, TensorView, Tensors, WithLabel, YoloV8, YoloV8Builder,
+    YoloV8Error, YoloV8Postprocess,
+  },
+  utils::logit,
+};
+
+const YOLOV8_SEG_MASK_THRESH: f32 = 0.5;
+/// 掩码原型相对输入图像的下采样倍数
+const YOLOV8_SEG_PROTO_STRIDE: u32 = 4;
+
+#[derive(Error, Debug)]
+pub enum YoloV8SegError {
+  #[error("YOLOv8 错误: {0}")]
+  YoloV8Error(#[from] YoloV8Error),
+  #[error("模型路径错误: {0}")]
+  ModelPathError(String),
+  #[error("模型输出不匹配: {0}")]
+  OutputMismatch(String),
+}
+
+pub struct YoloV8SegPostprocess<const W: u32, const H: u32, T> {
+  detect: YoloV8Postprocess<W, H, T>,
+  mask_thresh: f32,
+}
+
+pub struct YoloV8SegBuilder {
+  detect: YoloV8Builder,
+  mask_thresh: f32,
+}
+
+impl FromUrlWithScheme for YoloV8SegBuilder {
+  const SCHEME: &'static str = "yolov8-seg";
+}
+
+impl Default for YoloV8SegBuilder {
+  fn default() -> Self {
+    Self {
+      detect: YoloV8Builder::default(),
+      mask_thresh: YOLOV8_SEG_MASK_THRESH,
+    }
+  }
+}
+
+impl FromUrl for YoloV8SegBuilder {
+  type Error = YoloV8SegError;
+
+  fn from_url(url: &Url) -> Result<Self, Self::Error> {
+    if url.scheme() != Self::SCHEME {
+      return Err(YoloV8SegError::ModelPathError(format!(
+        "模型路径必须使用 {} 方案",
+        Self::SCHEME
+      )));
+    }
+
+    let mut builder = YoloV8SegBuilder::default().detect(YoloV8Builder::from_query(url)?);
+    for (k, v) in url.query_pairs() {
+      if k == "mask_thresh" {
+        builder.mask_thresh = v.parse().unwrap_or(YOLOV8_SEG_MASK_THRESH);
+      }
+    }
+
+    Ok(builder)
+  }
+}
+
+impl YoloV8SegBuilder {
+  /// 检测部分的参数
+  pub fn detect(mut self, detect: YoloV8Builder) -> Self {
+    self.detect = detect;
+    self
+  }
+
+  /// 类别数量，见 [`YoloV8Builder::labels`]
+  pub fn labels(mut self, num_labels: usize) -> Self {
+    self.detect = self.detect.labels(num_labels);
+    self
+  }
+
+  pub fn mask_thresh(mut self, mask_thresh: f32) -> Self {
+    self.mask_thresh = mask_thresh;
+    self
+  }
+
+  pub fn build_postprocess<const W: u32, const H: u32, T: WithLabel>(
+    &self,
+  ) -> Result<YoloV8SegPostprocess<W, H, T>, YoloV8SegError> {
+    if W % YOLOV8_SEG_PROTO_STRIDE != 0 || H % YOLOV8_SEG_PROTO_STRIDE != 0 {
+      return Err(YoloV8SegError::ModelPathError(format!(
+        "输入尺寸 {}x{} 必须是 {} 的整数倍",
+        W, H, YOLOV8_SEG_PROTO_STRIDE
+      )));
+    }
+
+    Ok(YoloV8SegPostprocess {
+      detect: self.detect.build_postprocess()?,
+      mask_thresh: self.mask_thresh,
+    })
+  }
+
+  pub fn build_model<const W: u32, const H: u32, Frame>(
+    self,
+  ) -> Result<YoloV8<W, H, Frame>, YoloV8SegError> {
+    Ok(self.detect.build_model_with(1, |info| {
+      info.outputs.last().and_then(|proto| proto.channels())
+    })?)
+  }
+}
+
+impl<const W: u32, const H: u32, T: WithLabel> Postprocess for YoloV8SegPostprocess<W, H, T> {
+  type Input = Tensors;
+  type Output = DetectResult<T>;
+  type Error = YoloV8SegError;
+
+  fn process(&self, output: Self::Input) -> Result<Self::Output, Self::Error> {
+    debug!("后处理分割模型输出");
+    let Some((proto, heads)) = output.split_last() else {
+      return Err(YoloV8SegError::OutputMismatch("模型没有输出".to_string()));
+    };
+
+    let proto = proto.view();
+    let (proto_w, proto_h) = (
+      (W / YOLOV8_SEG_PROTO_STRIDE) as usize,
+      (H / YOLOV8_SEG_PROTO_STRIDE) as usize,
+    );
+    if proto.is_empty() || proto.len() % (proto_w * proto_h) != 0 {
+      return Err(YoloV8SegError::OutputMismatch(format!(
+        "掩码原型大小 {} 不是 {}x{} 的整数倍",
+        proto.len(),
+        proto_w,
+        proto_h
+      )));
+    }
+    let nm = proto.len() / (proto_w * proto_h);
+
+    // 候选及其掩码系数
+    let mut items: Vec<(DetectItem<T>, Vec<f32>)> = Vec::new();
+
+    let branches = self.detect.strides.len();
+    match heads.len() {
+      1 => {
+        let pred = heads[0].view();
+        let len = pred.len() / (4 + self.detect.num_classes() + nm);
+        let coeff_base = (4 + self.detect.num_classes()) * len;
+        self.detect.decode_fused(pred, nm, &mut |item, i| {
+          let coeffs = (0..nm).map(|k| pred.get(coeff_base + k * len + i));
+          items.push((item, coeffs.collect()));
+        })?;
+      }
+      n if n == 3 * branches || n == 4 * branches => {
+        let per_branch = n / branches;
+        for (i, stride) in self.detect.strides.iter().enumerate() {
+          let boxes = heads[i * per_branch].view();
+          let scores = heads[i * per_branch + 1].view();
+          let seg = heads[(i + 1) * per_branch - 1].view();
+          let grid = ((W / stride) * (H / stride)) as usize;
+          if seg.len() != nm * grid {
+            return Err(YoloV8SegError::OutputMismatch(format!(
+              "步长 {} 的掩码系数大小为 {}, 预期为 {}",
+              stride,
+              seg.len(),
+              nm * grid
+            )));
+          }
+          self
+            .detect
+            .decode_branch(*stride, boxes, scores, &mut |item, idx| {
+              let coeffs = (0..nm).map(|k| seg.get(k * grid + idx));
+              items.push((item, coeffs.collect()));
+            })?;
+        }
+      }
+      n => {
+        return Err(YoloV8SegError::OutputMismatch(format!(
+          "检测输出数量 {} 与步长数量 {} 不匹配",
+          n, branches
+        )));
+      }
+    }
+
+    // 只为 NMS 保留的实例计算掩码
+    let classes = &self.detect.classes;
+    items.retain(|(item, _)| classes.accept(item.kind.to_label_id(), item.score));
+    let results: Vec<DetectItem<T>> = self
+      .detect
+      .nms
+      .suppress_with(items)
+      .into_iter()
+      .map(|(mut item, coeffs)| {
+        item.mask = self.build_mask(&coeffs, &proto, proto_w, proto_h, &item.bbox);
+        item
+      })
+      .collect();
+    debug!("NMS 后实例数量: {}", results.len());
+
+    Ok(DetectResult {
+      items: results.into_boxed_slice(),
+    })
+  }
+}
+
+impl<const W: u32, const H: u32, T: WithLabel> YoloV8SegPostprocess<W, H, T> {
+  /// 计算检测框范围内的掩码
+  ///
+  /// 先在原型分辨率上计算框内（含一格边缘）的掩码 logit，再双线性插值到输入分辨率，
+  /// 与阈值的 logit 比较，避免逐像素计算 sigmoid。
+  fn build_mask(
+    &self,
+    coeffs: &[f32],
+    proto: &TensorView,
+    proto_w: usize,
+    proto_h: usize,
+    bbox: &BBox,
+  ) -> Option<Mask> {
+    let x0 = bbox.x_min.floor().clamp(0.0, W as f32) as u32;
+    let y0 = bbox.y_min.floor().clamp(0.0, H as f32) as u32;
+    let x1 = bbox.x_max.ceil().clamp(0.0, W as f32) as u32;
+    let y1 = bbox.y_max.ceil().clamp(0.0, H as f32) as u32;
+    if x0 >= x1 || y0 >= y1 {
+      return None;
+    }
+
+    let stride = YOLOV8_SEG_PROTO_STRIDE as f32;
+    // 像素中心在原型网格中的坐标
+    let to_proto =
+      |p: u32, max: usize| ((p as f32 + 0.5) / stride - 0.5).clamp(0.0, (max - 1) as f32);
+    let u0 = to_proto(x0, proto_w).floor() as usize;
+    let u1 = to_proto(x1 - 1, proto_w).ceil() as usize;
+    let v0 = to_proto(y0, proto_h).floor() as usize;
+    let v1 = to_proto(y1 - 1, proto_h).ceil() as usize;
+
+    let crop_w = u1 - u0 + 1;
+    let plane = proto_w * proto_h;
+    let mut logits = vec![0f32; crop_w * (v1 - v0 + 1)];
+    for v in v0..=v1 {
+      for u in u0..=u1 {
+        logits[(v - v0) * crop_w + (u - u0)] = coeffs
+          .iter()
+          .enumerate()
+          .map(|(k, c)| c * proto.get(k * plane + v * proto_w + u))
+          .sum();
+      }
+    }
+
+    let thresh = logit(self.mask_thresh);
+    let (width, height) = (x1 - x0, y1 - y0);
+    let mut data = Vec::with_capacity((width * height) as usize);
+    for y in y0..y1 {
+      let v = to_proto(y, proto_h);
+      let (va, fy) = (v.floor() as usize, v.fract());
+      let vb = (va + 1).min(v1);
+      for x in x0..x1 {
+        let u = to_proto(x, proto_w);
+        let (ua, fx) = (u.floor() as usize, u.fract());
+        let ub = (ua + 1).min(u1);
+        let at = |u: usize, v: usize| logits[(v - v0) * crop_w + (u - u0)];
+        let top = at(ua, va) * (1.0 - fx) + at(ub, va) * fx;
+        let bottom = at(ua, vb) * (1.0 - fx) + at(ub, vb) * fx;
+        data.push(top * (1.0 - fy) + bottom * fy > thresh);
+      }
+    }
+
+    Some(Mask::new(x0, y0, width, height, data))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use shanan_trait::{Postprocess, WithLabel};
+
+  use super::{YoloV8SegBuilder, YoloV8SegPostprocess};
+  use crate::model::Tensor;
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  struct TestLabel(u32);
+
+  impl WithLabel for TestLabel {
+    const LABEL_NUM: u32 = 1;
+
+    fn from_label_id(id: u32) -> Self {
+      Self(id)
+    }
+
+    fn to_label_id(&self) -> u32 {
+      self.0
+    }
+
+    fn to_label_str(&self) -> String {
+      format!("class{}", self.0)
+    }
+  }
+
+  #[test]
+  fn crops_and_upsamples_mask() {
+    let post: YoloV8SegPostprocess<32, 32, TestLabel> =
+      YoloV8SegBuilder::default().build_postprocess().unwrap();
+    // 融合输出，每个候选为 cx, cy, w, h, 类别分数, 掩码系数；第二个候选低于阈值
+    let pred = vec![
+      16.0, 16.0, // cx
+      16.0, 16.0, // cy
+      16.0, 16.0, // w
+      16.0, 16.0, // h
+      0.9, 0.1, // 分数
+      1.0, 1.0, // 掩码系数
+    ];
+    // 8x8 的单通道原型，左半边为正、右半边为负
+    let proto = (0..64)
+      .map(|i| if i % 8 < 4 { 10.0 } else { -10.0 })
+      .collect();
+    let output = vec![
+      Tensor::from_f32(vec![1, 6, 2], pred),
+      Tensor::from_f32(vec![1, 1, 8, 8], proto),
+    ];
+
+    let result = post.process(output).unwrap();
+    assert_eq!(result.items.len(), 1);
+    let mask = result.items[0].mask.as_ref().unwrap();
+    // 掩码裁剪到检测框 [8, 24) 并放大到输入分辨率
+    assert_eq!((mask.x, mask.y, mask.width, mask.height), (8, 8, 16, 16));
+    // 原型第 3、4 列之间插值为零的位置是输入的 x = 15.5
+    assert!(mask.contains(15, 8));
+    assert!(!mask.contains(16, 8));
+    // 检测框之外不属于实例
+    assert!(!mask.contains(7, 8));
+    assert!(!mask.contains(15, 24));
+    assert_eq!(mask.area(), 8 * 16);
+  }
+}
@@ -15,18 +15,26 @@ use crate::FromUrl;
 #[cfg(feature = "save_image_file")]
 use crate::FromUrlWithScheme;
 use crate::frame::{RgbNchwFrame, RgbNhwcFrame};
-use crate::model::DetectResult;
-use shanan_trait::{Render, WithLabel};
+use crate::output::draw::DrawableResult;
+use shanan_trait::Render;
 use thiserror::Error;
 use url::Url;
 
 pub mod draw;
 
+mod batch_render;
+pub use self::batch_render::BatchRender;
+
 #[cfg(feature = "save_image_file")]
 mod save_image_file;
 #[cfg(feature = "save_image_file")]
 pub use self::save_image_file::{SaveImageFileError, SaveImageFileOutput};
 
+#[cfg(feature = "gstreamer_output")]
+mod pts_clock;
+#[cfg(feature = "gstreamer_output")]
+use self::pts_clock::PtsClock;
+
 #[cfg(feature = "gstreamer_output")]
 mod gstreamer_video_output;
 #[cfg(feature = "gstreamer_output")]
@@ -101,16 +109,25 @@ impl<'a, const W: u32, const H: u32> FromUrl for OutputWrapper<'a, W, H> {
   }
 }
 
-impl<'a, const W: u32, const H: u32, T: WithLabel> Render<RgbNchwFrame<W, H>, DetectResult<T>>
+impl<'a, const W: u32, const H: u32> OutputWrapper<'a, W, H> {
+  /// GStreamer 管道总线监控器，非 GStreamer 输出返回 `None`
+  #[cfg(feature = "gstreamer_output")]
+  pub fn bus_monitor(&self) -> Option<crate::utils::GStreamerBusMonitor> {
+    match self {
+      OutputWrapper::GStreamerVideoOutput(output) => Some(output.bus_monitor().clone()),
+      OutputWrapper::GStreamerRtspOutput(output) => Some(output.bus_monitor().clone()),
+      #[allow(unreachable_patterns)]
+      _ => None,
+    }
+  }
+}
+
+impl<'a, const W: u32, const H: u32, R: DrawableResult> Render<RgbNchwFrame<W, H>, R>
   for OutputWrapper<'a, W, H>
 {
   type Error = OutputError;
 
-  fn render_result(
-    &self,
-    frame: &RgbNchwFrame<W, H>,
-    result: &DetectResult<T>,
-  ) -> Result<(), Self::Error> {
+  fn render_result(&self, frame: &RgbNchwFrame<W, H>, result: &R) -> Result<(), Self::Error> {
     match self {
       #[cfg(feature = "save_image_file")]
       OutputWrapper::SaveImageFileOutput(output) => output
@@ -132,16 +149,12 @@ impl<'a, const W: u32, const H: u32, T: WithLabel> Render<RgbNchwFrame<W, H>, De
   }
 }
 
-impl<'a, const W: u32, const H: u32, T: WithLabel> Render<RgbNhwcFrame<W, H>, DetectResult<T>>
+impl<'a, const W: u32, const H: u32, R: DrawableResult> Render<RgbNhwcFrame<W, H>, R>
   for OutputWrapper<'a, W, H>
 {
   type Error = OutputError;
 
-  fn render_result(
-    &self,
-    frame: &RgbNhwcFrame<W, H>,
-    result: &DetectResult<T>,
-  ) -> Result<(), Self::Error> {
+  fn render_result(&self, frame: &RgbNhwcFrame<W, H>, result: &R) -> Result<(), Self::Error> {
     match self {
       #[cfg(feature = "save_image_file")]
       OutputWrapper::SaveImageFileOutput(output) => output
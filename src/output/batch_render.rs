@@ -0,0 +1,51 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/output/batch_render.rs - 批量渲染
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+use shanan_trait::Render;
+
+use crate::frame::FrameBatch;
+
+/// 逐帧渲染一批结果
+///
+/// 只有一个输出时所有帧都渲染到该输出；多个输出时第 i 帧渲染到第 i 个输出，
+/// 与 [`MultiInput`](crate::input::MultiInput) 的输入一一对应。
+pub struct BatchRender<R> {
+  outputs: Vec<R>,
+}
+
+impl<R> BatchRender<R> {
+  pub fn new(output: R) -> Self {
+    Self {
+      outputs: vec![output],
+    }
+  }
+
+  /// 每个输入一个输出
+  pub fn per_source(outputs: Vec<R>) -> Self {
+    Self { outputs }
+  }
+}
+
+impl<F, D, R: Render<F, D>> Render<FrameBatch<F>, Vec<D>> for BatchRender<R> {
+  type Error = R::Error;
+
+  fn render_result(&self, batch: &FrameBatch<F>, results: &Vec<D>) -> Result<(), Self::Error> {
+    if self.outputs.is_empty() {
+      return Ok(());
+    }
+    for (index, (frame, result)) in batch.frames().iter().zip(results).enumerate() {
+      self.outputs[index % self.outputs.len()].render_result(frame, result)?;
+    }
+    Ok(())
+  }
+}
@@ -18,16 +18,15 @@ use std::sync::{Arc, Mutex};
 use thiserror::Error;
 
 use crate::output::draw::ToRgbImage;
+
 use crate::{
   FromUrl, FromUrlWithScheme,
-  frame::{RgbNchwFrame, RgbNhwcFrame},
-  model::DetectResult,
+  frame::{FrameQuality, RgbNchwFrame, RgbNhwcFrame},
   output::{
     Render,
-    draw::{Draw, DrawDetectionOnFrame, Record},
+    draw::{Draw, DrawableResult, Record},
   },
 };
-use shanan_trait::WithLabel;
 
 #[derive(Error, Debug)]
 pub enum DirectoryRecordOutputError {
@@ -45,25 +44,30 @@ pub enum DrawWrapper<'a> {
 }
 
 impl DrawWrapper<'_> {
-  pub fn save_result<F, T>(
+  pub fn save_result<F, R>(
     &self,
     path: &PathBuf,
     frame: &F,
-    result: &DetectResult<T>,
+    result: &R,
+    quality: Option<&FrameQuality>,
   ) -> Result<(), DirectoryRecordOutputError>
   where
     F: ToRgbImage,
-    T: WithLabel,
+    R: DrawableResult,
   {
+    if let Some(quality) = quality {
+      Record::record_quality(quality, path)?;
+    }
+
     match self {
       DrawWrapper::Draw(draw) => {
-        let image: RgbImage = draw.draw_detection(frame, result);
+        let image: RgbImage = draw.draw_result(frame, result);
         image.save(path)?;
       }
       DrawWrapper::Record(record) => {
         let image = frame.to_rgb_image();
         image.save(path)?;
-        record.record(result, path)?;
+        result.record(record, path)?;
       }
     };
 
@@ -156,37 +160,33 @@ impl<'a, const W: u32, const H: u32> DirectoryRecordOutput<'a, W, H> {
   }
 }
 
-impl<'a, const W: u32, const H: u32, T: WithLabel> Render<RgbNhwcFrame<W, H>, DetectResult<T>>
+impl<'a, const W: u32, const H: u32, R: DrawableResult> Render<RgbNhwcFrame<W, H>, R>
   for DirectoryRecordOutput<'a, W, H>
 {
   type Error = DirectoryRecordOutputError;
 
-  fn render_result(
-    &self,
-    frame: &RgbNhwcFrame<W, H>,
-    result: &DetectResult<T>,
-  ) -> Result<(), Self::Error> {
+  fn render_result(&self, frame: &RgbNhwcFrame<W, H>, result: &R) -> Result<(), Self::Error> {
     let path = self.frame_path();
     if self.always || !result.is_empty() {
-      self.draw.save_result(&path, frame, result)?;
+      self
+        .draw
+        .save_result(&path, frame, result, frame.quality())?;
     }
     Ok(())
   }
 }
 
-impl<'a, const W: u32, const H: u32, T: WithLabel> Render<RgbNchwFrame<W, H>, DetectResult<T>>
+impl<'a, const W: u32, const H: u32, R: DrawableResult> Render<RgbNchwFrame<W, H>, R>
   for DirectoryRecordOutput<'a, W, H>
 {
   type Error = DirectoryRecordOutputError;
 
-  fn render_result(
-    &self,
-    frame: &RgbNchwFrame<W, H>,
-    result: &DetectResult<T>,
-  ) -> Result<(), Self::Error> {
+  fn render_result(&self, frame: &RgbNchwFrame<W, H>, result: &R) -> Result<(), Self::Error> {
     let path = self.frame_path();
     if self.always || !result.is_empty() {
-      self.draw.save_result(&path, frame, result)?;
+      self
+        .draw
+        .save_result(&path, frame, result, frame.quality())?;
     }
     Ok(())
   }
@@ -13,12 +13,14 @@
 
 use ab_glyph::{FontRef, PxScale};
 use image::{ImageBuffer, Rgb, RgbImage};
-use imageproc::drawing::{draw_filled_rect_mut, draw_text_mut};
+use imageproc::drawing::{
+  draw_filled_circle_mut, draw_filled_rect_mut, draw_line_segment_mut, draw_text_mut,
+};
 
 use crate::{
-  frame::{RgbNchwFrame, RgbNhwcFrame},
+  frame::{FrameQuality, RgbNchwFrame, RgbNhwcFrame},
   input::{AsNchwFrame, AsNhwcFrame},
-  model::{BBox, DetectItem, DetectResult},
+  model::{Attribute, BBox, ClassifyResult, DetectItem, DetectResult, Mask, Pose, RotatedBox},
 };
 use shanan_trait::WithLabel;
 
@@ -28,6 +30,10 @@ const LABEL_TEXT_HEIGHT: i32 = 24;
 const LABEL_CHAR_WIDTH: f32 = 11.0; // 每字符平均宽度（粗略估计）
 const LABEL_TEXT_VERTICAL_PADDING: i32 = 2;
 const LABEL_COLOR: [u8; 3] = [0, 0, 255]; // 蓝色
+const MASK_ALPHA: f32 = 0.4;
+const KEYPOINT_COLOR: [u8; 3] = [255, 128, 0]; // 橙色
+const KEYPOINT_RADIUS: i32 = 3;
+const KEYPOINT_THRESH: f32 = 0.5;
 
 pub struct Draw<'a> {
   font_size: f32,
@@ -36,6 +42,9 @@ pub struct Draw<'a> {
   label_text_vertical_padding: i32,
   font: FontRef<'a>,
   label_color: [u8; 3],
+  mask_alpha: f32,
+  keypoint_color: [u8; 3],
+  keypoint_thresh: f32,
 }
 
 impl<'a> Default for Draw<'a> {
@@ -49,12 +58,51 @@ impl<'a> Default for Draw<'a> {
       label_char_width: LABEL_CHAR_WIDTH,
       label_text_vertical_padding: LABEL_TEXT_VERTICAL_PADDING,
       label_color: LABEL_COLOR,
+      mask_alpha: MASK_ALPHA,
+      keypoint_color: KEYPOINT_COLOR,
+      keypoint_thresh: KEYPOINT_THRESH,
       font,
     }
   }
 }
 
 impl<'a> Draw<'a> {
+  /// 将实例掩码按 `mask_alpha` 与图像混合
+  fn blend_mask(&self, image: &mut RgbImage, mask: &Mask, color: [u8; 3]) {
+    let x_end = (mask.x + mask.width).min(image.width());
+    let y_end = (mask.y + mask.height).min(image.height());
+    for y in mask.y..y_end {
+      for x in mask.x..x_end {
+        if !mask.contains(x, y) {
+          continue;
+        }
+        let pixel = image.get_pixel_mut(x, y);
+        for (p, c) in pixel.0.iter_mut().zip(color) {
+          *p = (*p as f32 * (1.0 - self.mask_alpha) + c as f32 * self.mask_alpha).round() as u8;
+        }
+      }
+    }
+  }
+
+  /// 绘制骨架连线与置信度超过阈值的关键点
+  fn draw_pose(&self, image: &mut RgbImage, pose: &Pose) {
+    for (a, b) in pose.limbs(self.keypoint_thresh) {
+      draw_line_segment_mut(image, (a.x, a.y), (b.x, b.y), Rgb(self.label_color));
+    }
+    for point in pose
+      .keypoints
+      .iter()
+      .filter(|p| p.score > self.keypoint_thresh)
+    {
+      draw_filled_circle_mut(
+        image,
+        (point.x as i32, point.y as i32),
+        KEYPOINT_RADIUS,
+        Rgb(self.keypoint_color),
+      );
+    }
+  }
+
   // 在图像上绘制一个矩形边框，bbox 为归一化坐标 [x_min, y_min, x_max, y_max]
   fn draw_bbox_with_label<T: WithLabel>(
     &self,
@@ -115,8 +163,76 @@ impl<'a> Draw<'a> {
       }
     }
 
-    // 创建标签文本
+    self.draw_label(image, (x_min, y_min), kind, score, color, font);
+  }
+
+  /// 绘制旋转边框，标签位于外接矩形上方
+  fn draw_rotated_box_with_label<T: WithLabel>(
+    &self,
+    image: &mut RgbImage,
+    obb: &RotatedBox,
+    kind: &T,
+    score: f32,
+    color: [u8; 3],
+    font: &FontRef,
+  ) {
+    let corners = obb.corners();
+    for i in 0..corners.len() {
+      draw_line_segment_mut(
+        image,
+        corners[i],
+        corners[(i + 1) % corners.len()],
+        Rgb(color),
+      );
+    }
+
+    let bounds = obb.bounds();
+    let (w, h) = (image.width() as i32, image.height() as i32);
+    let x_min = (bounds.x_min as i32).clamp(0, w - 1);
+    let y_min = (bounds.y_min as i32).clamp(0, h - 1);
+    self.draw_label(image, (x_min, y_min), kind, score, color, font);
+  }
+
+  // 在 (x_min, y_min) 上方绘制类别与分数标签
+  fn draw_label<T: WithLabel>(
+    &self,
+    image: &mut RgbImage,
+    (x_min, y_min): (i32, i32),
+    kind: &T,
+    score: f32,
+    color: [u8; 3],
+    font: &FontRef,
+  ) {
     let label = format!("{} {:.2}", kind.to_label_str(), score);
+    self.draw_text_label(image, (x_min, y_min), &label, color, font);
+  }
+
+  /// 在边框内侧顶部逐行绘制级联二级模型的标签
+  fn draw_attributes(&self, image: &mut RgbImage, bbox: &BBox, attributes: &[Attribute]) {
+    let (w, h) = (image.width() as i32, image.height() as i32);
+    let x_min = (bbox.x_min as i32).clamp(0, w - 1);
+    let y_min = (bbox.y_min as i32).clamp(0, h - 1);
+    for (row, attribute) in attributes.iter().enumerate() {
+      // 标签绘制在给定位置上方，因此以下一行的顶部作为位置
+      let y = y_min + (row as i32 + 1) * self.label_text_height;
+      if y > h {
+        break;
+      }
+      let label = format!("{} {:.2}", attribute.label, attribute.score);
+      self.draw_text_label(image, (x_min, y), &label, self.label_color, &self.font);
+    }
+  }
+
+  // 在 (x_min, y_min) 上方绘制文本标签
+  fn draw_text_label(
+    &self,
+    image: &mut RgbImage,
+    (x_min, y_min): (i32, i32),
+    label: &str,
+    color: [u8; 3],
+    font: &FontRef,
+  ) {
+    let w = image.width() as f32;
 
     // 文本参数
     let scale = PxScale::from(self.font_size);
@@ -149,7 +265,7 @@ impl<'a> Draw<'a> {
         label_y + self.label_text_vertical_padding,
         scale,
         font,
-        &label,
+        label,
       );
     }
   }
@@ -159,6 +275,41 @@ pub trait DrawDetecctionOnImage<T: WithLabel> {
   fn draw_detections_on_image(&self, image: &mut RgbImage, result: &DetectResult<T>);
 }
 
+/// 可由输出绘制与记录的推理结果
+pub trait DrawableResult {
+  fn is_empty(&self) -> bool;
+  fn draw_on_image(&self, draw: &Draw, image: &mut RgbImage);
+  fn record(&self, record: &Record, path: &std::path::Path) -> Result<(), std::io::Error>;
+}
+
+impl<T: WithLabel> DrawableResult for DetectResult<T> {
+  fn is_empty(&self) -> bool {
+    DetectResult::is_empty(self)
+  }
+
+  fn draw_on_image(&self, draw: &Draw, image: &mut RgbImage) {
+    draw.draw_detections_on_image(image, self);
+  }
+
+  fn record(&self, record: &Record, path: &std::path::Path) -> Result<(), std::io::Error> {
+    record.record(self, path)
+  }
+}
+
+impl<T: WithLabel> DrawableResult for ClassifyResult<T> {
+  fn is_empty(&self) -> bool {
+    ClassifyResult::is_empty(self)
+  }
+
+  fn draw_on_image(&self, draw: &Draw, image: &mut RgbImage) {
+    draw.draw_classification_on_image(image, self);
+  }
+
+  fn record(&self, record: &Record, path: &std::path::Path) -> Result<(), std::io::Error> {
+    record.record_classification(self, path)
+  }
+}
+
 pub trait ToRgbImage {
   fn to_rgb_image(&self) -> RgbImage;
 }
@@ -181,6 +332,42 @@ impl<FromFrame: ToRgbImage, ToFrame: FromRgbImage, T: WithLabel, D: DrawDeteccti
   }
 }
 
+impl Draw<'_> {
+  /// 绘制任意推理结果，返回绘制后的帧
+  pub fn draw_result<FromFrame: ToRgbImage, ToFrame: FromRgbImage, R: DrawableResult>(
+    &self,
+    frame: &FromFrame,
+    result: &R,
+  ) -> ToFrame {
+    let mut image = frame.to_rgb_image();
+    result.draw_on_image(self, &mut image);
+    ToFrame::from_rgb_image(&image)
+  }
+
+  /// 在图像左上角逐行绘制分类结果，分数最高的类别在最上方
+  fn draw_classification_on_image<T: WithLabel>(
+    &self,
+    image: &mut RgbImage,
+    result: &ClassifyResult<T>,
+  ) {
+    for (row, item) in result.items.iter().enumerate() {
+      // 标签绘制在给定位置上方，因此以下一行的顶部作为位置
+      let y = (row as i32 + 1) * self.label_text_height;
+      if y > image.height() as i32 {
+        break;
+      }
+      self.draw_label(
+        image,
+        (0, y),
+        &item.kind,
+        item.score,
+        self.label_color,
+        &self.font,
+      );
+    }
+  }
+}
+
 impl<const W: u32, const H: u32> ToRgbImage for RgbNchwFrame<W, H> {
   fn to_rgb_image(&self) -> RgbImage {
     let width = self.width() as u32;
@@ -260,16 +447,38 @@ impl<const W: u32, const H: u32> FromRgbImage for RgbNchwFrame<W, H> {
 
 impl<T: WithLabel> DrawDetecctionOnImage<T> for Draw<'_> {
   fn draw_detections_on_image(&self, image: &mut RgbImage, result: &DetectResult<T>) {
+    // 先混合掩码，避免覆盖边框和标签
+    for mask in result.items.iter().filter_map(|item| item.mask.as_ref()) {
+      self.blend_mask(image, mask, self.label_color);
+    }
+
     // 绘制检测框和标签
-    for DetectItem { kind, score, bbox } in result.items.iter() {
-      self.draw_bbox_with_label(
-        image,
-        bbox,
-        kind,
-        *score,
-        self.label_color, // 蓝色边框
-        &self.font,
-      );
+    for DetectItem {
+      kind,
+      score,
+      bbox,
+      keypoints,
+      obb,
+      attributes,
+      ..
+    } in result.items.iter()
+    {
+      if let Some(pose) = keypoints {
+        self.draw_pose(image, pose);
+      }
+      if let Some(obb) = obb {
+        self.draw_rotated_box_with_label(image, obb, kind, *score, self.label_color, &self.font);
+      } else {
+        self.draw_bbox_with_label(
+          image,
+          bbox,
+          kind,
+          *score,
+          self.label_color, // 蓝色边框
+          &self.font,
+        );
+      }
+      self.draw_attributes(image, bbox, attributes);
     }
   }
 }
@@ -292,11 +501,7 @@ impl Record {
   ) -> Result<(), std::io::Error> {
     let mut records = Vec::new();
     for item in result.items.iter() {
-      let name = if self.label_with_name {
-        item.kind.to_label_str()
-      } else {
-        format!("{}", item.kind.to_label_id())
-      };
+      let name = self.label_name(&item.kind);
       let record = format!(
         "{}, {:.4}, {:.4}, {:.4}, {:.4}, {:.4}",
         name, item.score, item.bbox.x_min, item.bbox.y_min, item.bbox.x_max, item.bbox.y_max
@@ -304,6 +509,213 @@ impl Record {
       records.push(record);
     }
     std::fs::write(path.with_extension("txt"), records.join("\n"))?;
+    self.record_polygons(result, path)?;
+    self.record_keypoints(result, path)?;
+    self.record_rotated_boxes(result, path)?;
+    self.record_attributes(result, path)
+  }
+
+  /// 将级联二级模型的标签写入 `<帧文件名>.attr.txt`
+  ///
+  /// 每行一个实例：`序号, 类别, 标签1, 分数1, 标签2, 分数2, ...`，序号对应检测记录中的行号
+  /// （从 0 开始）。没有二级标签时不写入文件。
+  fn record_attributes<T: WithLabel>(
+    &self,
+    result: &DetectResult<T>,
+    path: &std::path::Path,
+  ) -> Result<(), std::io::Error> {
+    let mut records = Vec::new();
+    for (index, item) in result.items.iter().enumerate() {
+      if item.attributes.is_empty() {
+        continue;
+      }
+      let attributes: Vec<String> = item
+        .attributes
+        .iter()
+        .map(|a| {
+          if self.label_with_name {
+            format!("{}, {:.4}", a.label, a.score)
+          } else {
+            format!("{}, {:.4}", a.id, a.score)
+          }
+        })
+        .collect();
+      records.push(format!(
+        "{}, {}, {}",
+        index,
+        self.label_name(&item.kind),
+        attributes.join(", ")
+      ));
+    }
+    if records.is_empty() {
+      return Ok(());
+    }
+    std::fs::write(path.with_extension("attr.txt"), records.join("\n"))?;
+    Ok(())
+  }
+
+  /// 将旋转边框的四个角点写入 `<帧文件名>.obb.txt`
+  ///
+  /// 每行一个实例：`序号, 类别, x1, y1, x2, y2, x3, y3, x4, y4`，角点按顺时针排列，
+  /// 序号对应检测记录中的行号（从 0 开始）。没有旋转边框时不写入文件。
+  fn record_rotated_boxes<T: WithLabel>(
+    &self,
+    result: &DetectResult<T>,
+    path: &std::path::Path,
+  ) -> Result<(), std::io::Error> {
+    let mut records = Vec::new();
+    for (index, item) in result.items.iter().enumerate() {
+      let Some(obb) = &item.obb else {
+        continue;
+      };
+      let corners: Vec<String> = obb
+        .corners()
+        .iter()
+        .map(|(x, y)| format!("{:.4}, {:.4}", x, y))
+        .collect();
+      records.push(format!(
+        "{}, {}, {}",
+        index,
+        self.label_name(&item.kind),
+        corners.join(", ")
+      ));
+    }
+    if records.is_empty() {
+      return Ok(());
+    }
+    std::fs::write(path.with_extension("obb.txt"), records.join("\n"))?;
+    Ok(())
+  }
+
+  /// 将姿态关键点写入 `<帧文件名>.pose.txt`
+  ///
+  /// 每行一个实例：`序号, 类别, x1, y1, 置信度1, x2, y2, 置信度2, ...`，序号对应检测记录中的行号
+  /// （从 0 开始）。没有关键点时不写入文件。
+  fn record_keypoints<T: WithLabel>(
+    &self,
+    result: &DetectResult<T>,
+    path: &std::path::Path,
+  ) -> Result<(), std::io::Error> {
+    let mut records = Vec::new();
+    for (index, item) in result.items.iter().enumerate() {
+      let Some(pose) = &item.keypoints else {
+        continue;
+      };
+      let points: Vec<String> = pose
+        .keypoints
+        .iter()
+        .map(|p| format!("{:.4}, {:.4}, {:.4}", p.x, p.y, p.score))
+        .collect();
+      records.push(format!(
+        "{}, {}, {}",
+        index,
+        self.label_name(&item.kind),
+        points.join(", ")
+      ));
+    }
+    if records.is_empty() {
+      return Ok(());
+    }
+    std::fs::write(path.with_extension("pose.txt"), records.join("\n"))?;
+    Ok(())
+  }
+
+  /// 将分类结果写入 `<帧文件名>.txt` 与 `<帧文件名>.json`
+  ///
+  /// 文本每行一个类别：`类别, 分数`，按分数从高到低排列。JSON 为
+  /// `[{"id": .., "label": .., "score": ..}, ...]`。
+  pub fn record_classification<T: WithLabel>(
+    &self,
+    result: &ClassifyResult<T>,
+    path: &std::path::Path,
+  ) -> Result<(), std::io::Error> {
+    let records: Vec<String> = result
+      .items
+      .iter()
+      .map(|item| format!("{}, {:.4}", self.label_name(&item.kind), item.score))
+      .collect();
+    std::fs::write(path.with_extension("txt"), records.join("\n"))?;
+
+    let json: Vec<serde_json::Value> = result
+      .items
+      .iter()
+      .map(|item| {
+        serde_json::json!({
+          "id": item.kind.to_label_id(),
+          "label": item.kind.to_label_str(),
+          "score": item.score,
+        })
+      })
+      .collect();
+    std::fs::write(
+      path.with_extension("json"),
+      serde_json::Value::Array(json).to_string(),
+    )?;
+    Ok(())
+  }
+
+  fn label_name<T: WithLabel>(&self, kind: &T) -> String {
+    if self.label_with_name {
+      kind.to_label_str()
+    } else {
+      format!("{}", kind.to_label_id())
+    }
+  }
+
+  /// 将实例掩码轮廓写入 `<帧文件名>.seg.txt`
+  ///
+  /// 每行一个多边形：`序号, 类别, 掩码像素面积, x1, y1, x2, y2, ...`，序号对应检测记录中的行号
+  /// （从 0 开始）。外轮廓顺时针排列，空洞逆时针排列。没有掩码时不写入文件。
+  fn record_polygons<T: WithLabel>(
+    &self,
+    result: &DetectResult<T>,
+    path: &std::path::Path,
+  ) -> Result<(), std::io::Error> {
+    let mut records = Vec::new();
+    for (index, item) in result.items.iter().enumerate() {
+      let Some(mask) = &item.mask else {
+        continue;
+      };
+      let name = self.label_name(&item.kind);
+      let area = mask.area();
+      for polygon in mask.polygons() {
+        let points: Vec<String> = polygon
+          .iter()
+          .map(|(x, y)| format!("{:.0}, {:.0}", x, y))
+          .collect();
+        records.push(format!(
+          "{}, {}, {}, {}",
+          index,
+          name,
+          area,
+          points.join(", ")
+        ));
+      }
+    }
+    if records.is_empty() {
+      return Ok(());
+    }
+    std::fs::write(path.with_extension("seg.txt"), records.join("\n"))?;
+    Ok(())
+  }
+
+  /// 将帧质量指标写入 `<帧文件名>.quality.txt`
+  pub fn record_quality(
+    quality: &FrameQuality,
+    path: &std::path::Path,
+  ) -> Result<(), std::io::Error> {
+    let mut record = format!(
+      "sharpness, {:.4}\nluminance, {:.4}\nclipped_dark, {:.4}\nclipped_bright, {:.4}\npassed, {}",
+      quality.sharpness,
+      quality.luminance,
+      quality.clipped_dark,
+      quality.clipped_bright,
+      quality.passed
+    );
+    if let Some(frame_diff) = quality.frame_diff {
+      record.push_str(&format!("\nframe_diff, {:.4}", frame_diff));
+    }
+    std::fs::write(path.with_extension("quality.txt"), record)?;
     Ok(())
   }
 }
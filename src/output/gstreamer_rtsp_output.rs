@@ -89,24 +89,23 @@
 //! # }
 //! ```
 
-use std::sync::{Arc, Mutex};
+use std::sync::Mutex;
 
 use crate::{
   FromUrl, FromUrlWithScheme,
-  frame::{RgbNchwFrame, RgbNhwcFrame},
+  frame::{FrameTimestamp, RgbNchwFrame, RgbNhwcFrame},
   input::AsNhwcFrame,
-  model::DetectResult,
   output::{
-    Render,
-    draw::{Draw, DrawDetectionOnFrame},
+    PtsClock, Render,
+    draw::{Draw, DrawableResult},
   },
+  utils::{GStreamerBusEvent, GStreamerBusMonitor},
 };
-use shanan_trait::WithLabel;
 
 use gstreamer::{self as gst, prelude::*};
 use gstreamer_app as gst_app;
 use thiserror::Error;
-use tracing::{error, info};
+use tracing::{debug, error, info};
 use url::Url;
 
 /// GStreamer RTSP 输出错误类型
@@ -136,6 +135,9 @@ pub enum GStreamerRtspOutputError {
   /// 缓冲区创建错误
   #[error("Buffer creation error")]
   BufferCreationError,
+  /// 管道总线上报的错误
+  #[error("GStreamer bus error: {0}")]
+  BusError(GStreamerBusEvent),
 }
 
 /// GStreamer RTSP 推流输出
@@ -157,9 +159,9 @@ pub enum GStreamerRtspOutputError {
 pub struct GStreamerRtspOutput<'a, const W: u32, const H: u32> {
   pipeline: gst::Pipeline,
   appsrc: gst_app::AppSrc,
-  ifps: i32,
+  bus: GStreamerBusMonitor,
   _ofps: i32,
-  frame_count: Arc<Mutex<u64>>,
+  clock: Mutex<PtsClock>,
   draw: Draw<'a>,
 }
 
@@ -249,8 +251,15 @@ impl<'a, const W: u32, const H: u32> FromUrl for GStreamerRtspOutput<'a, W, H> {
     appsrc.set_format(gst::Format::Time);
     appsrc.set_property("is-live", true);
 
+    let bus = GStreamerBusMonitor::new(&pipeline)
+      .ok_or_else(|| GStreamerRtspOutputError::PipelineError("Pipeline has no bus".to_string()))?;
+
     // Start the pipeline
     pipeline.set_state(gst::State::Playing)?;
+    if let Some(event) = bus.poll() {
+      let _ = pipeline.set_state(gst::State::Null);
+      return Err(GStreamerRtspOutputError::BusError(event));
+    }
 
     info!(
       "RTSP output initialized: {}x{} @ ({} -> {} fps) on port {}",
@@ -260,9 +269,9 @@ impl<'a, const W: u32, const H: u32> FromUrl for GStreamerRtspOutput<'a, W, H> {
     Ok(GStreamerRtspOutput {
       pipeline,
       appsrc,
-      ifps,
+      bus,
       _ofps: ofps,
-      frame_count: Arc::new(Mutex::new(0)),
+      clock: Mutex::new(PtsClock::new(ifps.max(1) as u64)),
       draw: Draw::default(),
     })
   }
@@ -274,16 +283,32 @@ impl<'a, const W: u32, const H: u32> Drop for GStreamerRtspOutput<'a, W, H> {
       tracing::warn!("Failed to stop GStreamer RTSP output pipeline: {}", e);
     }
 
-    let frame_count = self.frame_count.lock().unwrap();
-    info!(
-      "RTSP output closed. Total frames streamed: {}",
-      *frame_count
-    );
+    let frame_count = self.clock.lock().unwrap().frames();
+    info!("RTSP output closed. Total frames streamed: {}", frame_count);
   }
 }
 
 impl<'a, const W: u32, const H: u32> GStreamerRtspOutput<'a, W, H> {
-  fn push_frame(&self, data: &[u8]) -> Result<(), GStreamerRtspOutputError> {
+  /// 管道总线监控器
+  pub fn bus_monitor(&self) -> &GStreamerBusMonitor {
+    &self.bus
+  }
+
+  /// 检查管道总线，若有错误则返回
+  pub fn check_bus(&self) -> Result<(), GStreamerRtspOutputError> {
+    match self.bus.poll() {
+      Some(event) => Err(GStreamerRtspOutputError::BusError(event)),
+      None => Ok(()),
+    }
+  }
+
+  fn push_frame(
+    &self,
+    data: &[u8],
+    timestamp: &FrameTimestamp,
+  ) -> Result<(), GStreamerRtspOutputError> {
+    self.check_bus()?;
+
     let size = data.len();
     let mut buffer =
       gst::Buffer::with_size(size).map_err(|_| GStreamerRtspOutputError::BufferCreationError)?;
@@ -297,53 +322,54 @@ impl<'a, const W: u32, const H: u32> GStreamerRtspOutput<'a, W, H> {
     }
 
     // Set timestamp
-    let mut frame_count = self.frame_count.lock().unwrap();
-    let timestamp = (*frame_count * 1_000_000_000) / (self.ifps as u64);
-    *frame_count += 1;
+    let (pts, duration) = self.buffer_timestamp(timestamp);
 
     {
       let buffer_ref = buffer.get_mut().unwrap();
-      buffer_ref.set_pts(gst::ClockTime::from_nseconds(timestamp));
-      buffer_ref.set_duration(gst::ClockTime::from_nseconds(
-        1_000_000_000 / self.ifps as u64,
-      ));
+      buffer_ref.set_pts(pts);
+      buffer_ref.set_duration(duration);
     }
 
     self.appsrc.push_buffer(buffer).map_err(|e| {
       GStreamerRtspOutputError::PipelineError(format!("Failed to push buffer: {:?}", e))
     })?;
 
+    if let Some(latency) = timestamp.latency() {
+      debug!("采集到推流延迟: {:.2?}", latency);
+    }
+
     Ok(())
   }
+
+  /// 计算输出缓冲区的 PTS 与持续时间，参见 [`PtsClock`]
+  fn buffer_timestamp(&self, timestamp: &FrameTimestamp) -> (gst::ClockTime, gst::ClockTime) {
+    let (pts, duration) = self.clock.lock().unwrap().next(timestamp);
+    (
+      gst::ClockTime::from_nseconds(pts.as_nanos() as u64),
+      gst::ClockTime::from_nseconds(duration.as_nanos() as u64),
+    )
+  }
 }
 
-impl<'a, const W: u32, const H: u32, T: WithLabel> Render<RgbNchwFrame<W, H>, DetectResult<T>>
+impl<'a, const W: u32, const H: u32, R: DrawableResult> Render<RgbNchwFrame<W, H>, R>
   for GStreamerRtspOutput<'a, W, H>
 {
   type Error = GStreamerRtspOutputError;
 
-  fn render_result(
-    &self,
-    frame: &RgbNchwFrame<W, H>,
-    result: &DetectResult<T>,
-  ) -> Result<(), Self::Error> {
-    let rgb_data: RgbNhwcFrame<W, H> = self.draw.draw_detection(frame, result);
-    self.push_frame(rgb_data.as_nhwc())
+  fn render_result(&self, frame: &RgbNchwFrame<W, H>, result: &R) -> Result<(), Self::Error> {
+    let rgb_data: RgbNhwcFrame<W, H> = self.draw.draw_result(frame, result);
+    self.push_frame(rgb_data.as_nhwc(), frame.timestamp())
   }
 }
 
-impl<'a, const W: u32, const H: u32, T: WithLabel> Render<RgbNhwcFrame<W, H>, DetectResult<T>>
+impl<'a, const W: u32, const H: u32, R: DrawableResult> Render<RgbNhwcFrame<W, H>, R>
   for GStreamerRtspOutput<'a, W, H>
 {
   type Error = GStreamerRtspOutputError;
 
-  fn render_result(
-    &self,
-    frame: &RgbNhwcFrame<W, H>,
-    result: &DetectResult<T>,
-  ) -> Result<(), Self::Error> {
-    let rgb_data: RgbNhwcFrame<W, H> = self.draw.draw_detection(frame, result);
-    self.push_frame(rgb_data.as_nhwc())?;
+  fn render_result(&self, frame: &RgbNhwcFrame<W, H>, result: &R) -> Result<(), Self::Error> {
+    let rgb_data: RgbNhwcFrame<W, H> = self.draw.draw_result(frame, result);
+    self.push_frame(rgb_data.as_nhwc(), frame.timestamp())?;
     Ok(())
   }
 }
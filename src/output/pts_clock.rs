@@ -0,0 +1,139 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/output/pts_clock.rs - 输出流时间戳
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+use std::time::Duration;
+
+use crate::frame::FrameTimestamp;
+
+/// PTS 的生成方式，由第一帧决定
+#[derive(Debug, Clone, Copy)]
+enum PtsMode {
+  /// 以第一帧的运行时间为零点
+  RunningTime(Duration),
+  /// 按帧率和帧计数生成
+  FrameCount,
+}
+
+/// 输出缓冲区的 PTS 生成器
+///
+/// 第一帧带有运行时间时，之后的帧都按运行时间计算，缺少运行时间或运行时间回退的帧接在
+/// 上一帧之后；否则全部按帧计数生成，忽略之后出现的运行时间。生成的 PTS 单调递增。
+#[derive(Debug)]
+pub(crate) struct PtsClock {
+  fps: u64,
+  frames: u64,
+  mode: Option<PtsMode>,
+  /// 上一帧的 PTS 与持续时间
+  last: Option<(Duration, Duration)>,
+}
+
+impl PtsClock {
+  pub(crate) fn new(fps: u64) -> Self {
+    Self {
+      fps: fps.max(1),
+      frames: 0,
+      mode: None,
+      last: None,
+    }
+  }
+
+  /// 已生成时间戳的帧数
+  pub(crate) fn frames(&self) -> u64 {
+    self.frames
+  }
+
+  /// 计算下一帧的 PTS 与持续时间
+  pub(crate) fn next(&mut self, timestamp: &FrameTimestamp) -> (Duration, Duration) {
+    let mode = *self.mode.get_or_insert(match timestamp.running_time {
+      Some(running_time) => PtsMode::RunningTime(running_time),
+      None => PtsMode::FrameCount,
+    });
+    let duration = timestamp
+      .duration
+      .unwrap_or(Duration::from_nanos(1_000_000_000 / self.fps));
+
+    let pts = match mode {
+      PtsMode::RunningTime(base) => {
+        let follow = self.last.map_or(Duration::ZERO, |(pts, d)| pts + d);
+        timestamp
+          .running_time
+          .map(|running_time| running_time.saturating_sub(base))
+          .filter(|pts| self.last.is_none_or(|(last, _)| *pts > last))
+          .unwrap_or(follow)
+      }
+      PtsMode::FrameCount => Duration::from_nanos(self.frames * 1_000_000_000 / self.fps),
+    };
+    self.frames += 1;
+    self.last = Some((pts, duration));
+
+    (pts, duration)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::PtsClock;
+  use crate::frame::FrameTimestamp;
+
+  fn at(ms: Option<u64>) -> FrameTimestamp {
+    FrameTimestamp {
+      running_time: ms.map(Duration::from_millis),
+      duration: Some(Duration::from_millis(40)),
+      ..Default::default()
+    }
+  }
+
+  fn pts(clock: &mut PtsClock, frames: &[Option<u64>]) -> Vec<u64> {
+    frames
+      .iter()
+      .map(|ms| clock.next(&at(*ms)).0.as_millis() as u64)
+      .collect()
+  }
+
+  #[test]
+  fn running_time_from_first_frame() {
+    let mut clock = PtsClock::new(25);
+    assert_eq!(
+      pts(&mut clock, &[Some(1000), Some(1040), Some(1120)]),
+      [0, 40, 120]
+    );
+  }
+
+  #[test]
+  fn missing_running_time_follows_previous_frame() {
+    let mut clock = PtsClock::new(25);
+    // 中间的帧没有运行时间，接在上一帧之后，而不是按帧计数回到 40ms
+    assert_eq!(
+      pts(&mut clock, &[Some(1000), Some(1200), None, Some(1300)]),
+      [0, 200, 240, 300]
+    );
+  }
+
+  #[test]
+  fn stays_monotonic() {
+    let mut clock = PtsClock::new(25);
+    assert_eq!(
+      pts(&mut clock, &[Some(1000), Some(1100), Some(1050)]),
+      [0, 100, 140]
+    );
+  }
+
+  #[test]
+  fn frame_count_ignores_later_running_time() {
+    let mut clock = PtsClock::new(10);
+    assert_eq!(pts(&mut clock, &[None, Some(5000), None]), [0, 100, 200]);
+    assert_eq!(clock.frames(), 3);
+  }
+}
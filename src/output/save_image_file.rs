@@ -20,13 +20,11 @@ use url::Url;
 use crate::{
   FromUrl, FromUrlWithScheme,
   frame::{RgbNchwFrame, RgbNhwcFrame},
-  model::DetectResult,
   output::{
     Render,
-    draw::{Draw, DrawDetectionOnFrame},
+    draw::{Draw, DrawableResult},
   },
 };
-use shanan_trait::WithLabel;
 
 pub struct SaveImageFileOutput<'a, const W: u32, const H: u32> {
   path: String,
@@ -84,32 +82,24 @@ impl<'a, const W: u32, const H: u32> SaveImageFileOutput<'a, W, H> {
   }
 }
 
-impl<'a, const W: u32, const H: u32, T: WithLabel> Render<RgbNchwFrame<W, H>, DetectResult<T>>
+impl<'a, const W: u32, const H: u32, R: DrawableResult> Render<RgbNchwFrame<W, H>, R>
   for SaveImageFileOutput<'a, W, H>
 {
   type Error = SaveImageFileError;
 
-  fn render_result(
-    &self,
-    frame: &RgbNchwFrame<W, H>,
-    result: &DetectResult<T>,
-  ) -> Result<(), Self::Error> {
-    let image = self.draw.draw_detection(frame, result);
+  fn render_result(&self, frame: &RgbNchwFrame<W, H>, result: &R) -> Result<(), Self::Error> {
+    let image = self.draw.draw_result(frame, result);
     self.save_image(image)
   }
 }
 
-impl<'a, const W: u32, const H: u32, T: WithLabel> Render<RgbNhwcFrame<W, H>, DetectResult<T>>
+impl<'a, const W: u32, const H: u32, R: DrawableResult> Render<RgbNhwcFrame<W, H>, R>
   for SaveImageFileOutput<'a, W, H>
 {
   type Error = SaveImageFileError;
 
-  fn render_result(
-    &self,
-    frame: &RgbNhwcFrame<W, H>,
-    result: &DetectResult<T>,
-  ) -> Result<(), Self::Error> {
-    let image = self.draw.draw_detection(frame, result);
+  fn render_result(&self, frame: &RgbNhwcFrame<W, H>, result: &R) -> Result<(), Self::Error> {
+    let image = self.draw.draw_result(frame, result);
     self.save_image(image)
   }
 }
@@ -12,10 +12,20 @@
 // Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
 
 use shanan_trait::{Model, Postprocess, Render, Task};
-use std::{thread, time::Duration};
+use std::{
+  sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+  },
+  thread,
+  time::Duration,
+};
 use tracing::{info, warn};
 
-use crate::utils::{DetectionBenchmarker, DetectionTimeRecord};
+use crate::{
+  model::infer_ordered,
+  utils::{DetectionBenchmarker, DetectionTimeRecord},
+};
 
 pub struct OneShotTask;
 
@@ -168,6 +178,90 @@ where
   }
 }
 
+/// 连续处理，推理在多个工作线程上并发执行，渲染仍按帧顺序进行
+///
+/// 模型需为 [`ModelPool`](crate::model::ModelPool) 或包装了模型池的模型，否则推理仍然串行。
+#[derive(Debug)]
+pub struct PooledTask {
+  frame_number: Option<usize>,
+  workers: usize,
+}
+
+impl Default for PooledTask {
+  fn default() -> Self {
+    Self {
+      frame_number: None,
+      workers: 1,
+    }
+  }
+}
+
+impl PooledTask {
+  pub fn with_frame_number(mut self, frame_number: Option<usize>) -> Self {
+    self.frame_number = frame_number;
+    self
+  }
+
+  /// 工作线程数量，通常为模型池的实例数量
+  pub fn with_workers(mut self, workers: usize) -> Self {
+    self.workers = workers;
+    self
+  }
+}
+
+impl<I, M, P, R, F, O, D, ME, PE, RE> Task<I, M, P, R> for PooledTask
+where
+  I: Iterator<Item = F>,
+  M: Model<Input = F, Output = O, Error = ME> + Sync,
+  P: Postprocess<Input = O, Output = D, Error = PE>,
+  R: Render<F, D, Error = RE>,
+  F: Send,
+  O: Send,
+  ME: std::error::Error + Sync + Send + 'static,
+  PE: std::error::Error + Sync + Send + 'static,
+  RE: std::error::Error + Sync + Send + 'static,
+{
+  type Output = ();
+  type Error = anyhow::Error;
+
+  fn run_task(self, input: I, model: M, post: P, render: R) -> Result<Self::Output, Self::Error> {
+    info!("开始任务，工作线程数量: {}", self.workers);
+    let stop = Arc::new(AtomicBool::new(false));
+    let handler_stop = stop.clone();
+
+    ctrlc::set_handler(move || {
+      info!("收到中断信号，准备退出...");
+      handler_stop.store(true, Ordering::SeqCst);
+      thread::spawn(|| {
+        thread::sleep(Duration::from_secs(30));
+        warn!("强制退出程序");
+        std::process::exit(1);
+      });
+    })
+    .expect("Error setting Ctrl-C handler");
+
+    // 达到指定帧数或收到中断信号后不再提交新帧，已提交的帧处理完成后退出
+    let frames = input
+      .take(self.frame_number.unwrap_or(usize::MAX))
+      .take_while(|_| !stop.load(Ordering::SeqCst));
+
+    let mut frame_index = 0;
+    let mut now = std::time::Instant::now();
+    infer_ordered(&model, self.workers, frames, |frame, output| {
+      frame_index = (frame_index + 1) % usize::MAX;
+      info!("处理第 {} 帧图像", frame_index);
+      let result = post.process(output)?;
+      render.render_result(&frame, &result)?;
+      info!("完成，帧间隔: {:.2?}", now.elapsed());
+      now = std::time::Instant::now();
+      Ok::<(), anyhow::Error>(())
+    })?;
+
+    info!("任务完成，退出");
+    Ok(())
+  }
+}
+
 pub struct BenchmarkTask {
   benchmarker: DetectionBenchmarker,
   times: u32,
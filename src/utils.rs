@@ -11,5 +11,23 @@
 //
 // Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
 
+/// Sigmoid 函数
+#[inline]
+pub fn sigmoid(x: f32) -> f32 {
+  1.0 / (1.0 + (-x).exp())
+}
+
+/// sigmoid 的反函数，用于将概率阈值转换到 logit 域
+#[inline]
+pub fn logit(p: f32) -> f32 {
+  let p = p.clamp(f32::EPSILON, 1.0 - f32::EPSILON);
+  (p / (1.0 - p)).ln()
+}
+
 mod benchmark;
 pub use benchmark::{DetectionBenchmarker, DetectionTimeRecord};
+
+#[cfg(any(feature = "gstreamer_input", feature = "gstreamer_output"))]
+mod gstreamer_bus;
+#[cfg(any(feature = "gstreamer_input", feature = "gstreamer_output"))]
+pub use gstreamer_bus::{GStreamerBusEvent, GStreamerBusMonitor};
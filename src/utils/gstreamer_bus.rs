@@ -0,0 +1,360 @@
+// 该文件是 Shanan （山南西风） 项目的一部分。
+// src/utils/gstreamer_bus.rs - GStreamer 总线监控
+//
+// 本文件根据 Apache 许可证第 2.0 版（以下简称“许可证”）授权使用；
+// 除非遵守该许可证条款，否则您不得使用本文件。
+// 您可通过以下网址获取许可证副本：
+// http://www.apache.org/licenses/LICENSE-2.0
+// 除非适用法律要求或书面同意，根据本许可协议分发的软件均按“原样”提供，
+// 不附带任何形式的明示或暗示的保证或条件。
+// 有关许可权限与限制的具体条款，请参阅本许可协议。
+//
+// Copyright (C) 2026 Johann Li <me@qinka.pro>, Wareless Group
+
+//! # GStreamer 总线监控
+//!
+//! 读取管道总线上的消息，并将错误、警告、EOS、状态变化与 QoS 消息转换为
+//! [`GStreamerBusEvent`]。监控器可以被克隆，克隆体共享同一份事件记录，
+//! 因此任务可以在输入或输出被移动之后继续查询管道状态。
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use gstreamer::{self as gst, prelude::*};
+use tracing::{debug, error, info, warn};
+
+/// 保留的历史事件数量
+const BUS_EVENT_HISTORY: usize = 64;
+
+/// GStreamer 总线事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum GStreamerBusEvent {
+  /// 元素错误，管道无法继续运行
+  Error {
+    element: String,
+    message: String,
+    debug: Option<String>,
+  },
+  /// 元素警告
+  Warning {
+    element: String,
+    message: String,
+    debug: Option<String>,
+  },
+  /// 流结束
+  Eos,
+  /// 元素状态变化
+  StateChanged {
+    element: String,
+    old: gst::State,
+    current: gst::State,
+    pending: gst::State,
+  },
+  /// 服务质量（丢帧等）报告
+  Qos {
+    element: String,
+    live: bool,
+    running_time: Option<Duration>,
+    jitter: i64,
+    proportion: f64,
+    quality: i32,
+  },
+}
+
+impl GStreamerBusEvent {
+  pub fn is_error(&self) -> bool {
+    matches!(self, GStreamerBusEvent::Error { .. })
+  }
+
+  fn from_message(message: &gst::Message) -> Option<Self> {
+    let element = message
+      .src()
+      .map(|src| src.name().to_string())
+      .unwrap_or_else(|| "<unknown>".to_string());
+
+    match message.view() {
+      gst::MessageView::Error(err) => Some(GStreamerBusEvent::Error {
+        element,
+        message: err.error().to_string(),
+        debug: err.debug().map(|d| d.to_string()),
+      }),
+      gst::MessageView::Warning(warning) => Some(GStreamerBusEvent::Warning {
+        element,
+        message: warning.error().to_string(),
+        debug: warning.debug().map(|d| d.to_string()),
+      }),
+      gst::MessageView::Eos(_) => Some(GStreamerBusEvent::Eos),
+      gst::MessageView::StateChanged(state) => Some(GStreamerBusEvent::StateChanged {
+        element,
+        old: state.old(),
+        current: state.current(),
+        pending: state.pending(),
+      }),
+      gst::MessageView::Qos(qos) => {
+        let (live, running_time, _, _, _) = qos.get();
+        let (jitter, proportion, quality) = qos.values();
+        Some(GStreamerBusEvent::Qos {
+          element,
+          live,
+          running_time: running_time.map(|t| Duration::from_nanos(t.nseconds())),
+          jitter,
+          proportion,
+          quality,
+        })
+      }
+      _ => None,
+    }
+  }
+}
+
+impl fmt::Display for GStreamerBusEvent {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      GStreamerBusEvent::Error {
+        element,
+        message,
+        debug,
+      } => {
+        write!(f, "element '{}' error: {}", element, message)?;
+        if let Some(debug) = debug {
+          write!(f, " ({})", debug)?;
+        }
+        Ok(())
+      }
+      GStreamerBusEvent::Warning {
+        element,
+        message,
+        debug,
+      } => {
+        write!(f, "element '{}' warning: {}", element, message)?;
+        if let Some(debug) = debug {
+          write!(f, " ({})", debug)?;
+        }
+        Ok(())
+      }
+      GStreamerBusEvent::Eos => write!(f, "end of stream"),
+      GStreamerBusEvent::StateChanged {
+        element,
+        old,
+        current,
+        pending,
+      } => write!(
+        f,
+        "element '{}' state changed: {:?} -> {:?} (pending {:?})",
+        element, old, current, pending
+      ),
+      GStreamerBusEvent::Qos {
+        element,
+        jitter,
+        proportion,
+        quality,
+        ..
+      } => write!(
+        f,
+        "element '{}' qos: jitter {} ns, proportion {:.3}, quality {}",
+        element, jitter, proportion, quality
+      ),
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+struct BusState {
+  history: VecDeque<GStreamerBusEvent>,
+  last_error: Option<GStreamerBusEvent>,
+  eos: bool,
+}
+
+/// GStreamer 总线监控器
+///
+/// 通过 [`GStreamerBusMonitor::poll`] 以非阻塞方式读取总线消息。
+#[derive(Debug, Clone)]
+pub struct GStreamerBusMonitor {
+  bus: gst::Bus,
+  state: Arc<Mutex<BusState>>,
+}
+
+impl GStreamerBusMonitor {
+  pub fn new(pipeline: &gst::Pipeline) -> Option<Self> {
+    let bus = pipeline.bus()?;
+    Some(Self {
+      bus,
+      state: Arc::new(Mutex::new(BusState::default())),
+    })
+  }
+
+  /// 读取总线上所有待处理的消息
+  ///
+  /// 若本次读取到错误消息，返回第一条错误事件。
+  pub fn poll(&self) -> Option<GStreamerBusEvent> {
+    let mut first_error = None;
+    while let Some(message) = self.bus.pop() {
+      let Some(event) = GStreamerBusEvent::from_message(&message) else {
+        continue;
+      };
+
+      match &event {
+        GStreamerBusEvent::Error { .. } => error!("GStreamer 总线: {}", event),
+        GStreamerBusEvent::Warning { .. } => warn!("GStreamer 总线: {}", event),
+        GStreamerBusEvent::Eos => info!("GStreamer 总线: {}", event),
+        _ => debug!("GStreamer 总线: {}", event),
+      }
+
+      let mut state = self.state.lock().unwrap();
+      if event.is_error() {
+        state.last_error = Some(event.clone());
+        if first_error.is_none() {
+          first_error = Some(event.clone());
+        }
+      }
+      if event == GStreamerBusEvent::Eos {
+        state.eos = true;
+      }
+      if state.history.len() >= BUS_EVENT_HISTORY {
+        state.history.pop_front();
+      }
+      state.history.push_back(event);
+    }
+    first_error
+  }
+
+  /// 最近的事件记录（由旧到新）
+  pub fn events(&self) -> Vec<GStreamerBusEvent> {
+    self.state.lock().unwrap().history.iter().cloned().collect()
+  }
+
+  /// 最近一次错误事件
+  pub fn last_error(&self) -> Option<GStreamerBusEvent> {
+    self.state.lock().unwrap().last_error.clone()
+  }
+
+  /// 是否已收到 EOS
+  pub fn is_eos(&self) -> bool {
+    self.state.lock().unwrap().eos
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use gstreamer::{self as gst, prelude::*};
+
+  use super::{GStreamerBusEvent, GStreamerBusMonitor};
+
+  fn pipeline() -> gst::Pipeline {
+    gst::init().unwrap();
+    gst::Pipeline::builder().name("test").build()
+  }
+
+  fn error(pipeline: &gst::Pipeline, message: &str) -> gst::Message {
+    gst::message::Error::builder(gst::CoreError::Failed, message)
+      .src(pipeline)
+      .debug("debug info")
+      .build()
+  }
+
+  #[test]
+  fn maps_error_and_warning() {
+    let pipeline = pipeline();
+    assert_eq!(
+      GStreamerBusEvent::from_message(&error(&pipeline, "no plugin")),
+      Some(GStreamerBusEvent::Error {
+        element: "test".to_string(),
+        message: "no plugin".to_string(),
+        debug: Some("debug info".to_string()),
+      })
+    );
+
+    let warning = gst::message::Warning::builder(gst::CoreError::Failed, "late")
+      .src(&pipeline)
+      .build();
+    assert_eq!(
+      GStreamerBusEvent::from_message(&warning),
+      Some(GStreamerBusEvent::Warning {
+        element: "test".to_string(),
+        message: "late".to_string(),
+        debug: None,
+      })
+    );
+  }
+
+  #[test]
+  fn maps_state_qos_and_eos() {
+    let pipeline = pipeline();
+    let state = gst::message::StateChanged::builder(
+      gst::State::Ready,
+      gst::State::Paused,
+      gst::State::Playing,
+    )
+    .src(&pipeline)
+    .build();
+    assert_eq!(
+      GStreamerBusEvent::from_message(&state),
+      Some(GStreamerBusEvent::StateChanged {
+        element: "test".to_string(),
+        old: gst::State::Ready,
+        current: gst::State::Paused,
+        pending: gst::State::Playing,
+      })
+    );
+
+    let qos = gst::message::Qos::builder(
+      true,
+      gst::ClockTime::from_mseconds(40),
+      gst::ClockTime::NONE,
+      gst::ClockTime::NONE,
+      gst::ClockTime::NONE,
+    )
+    .values(-1000, 0.5, 3)
+    .src(&pipeline)
+    .build();
+    assert_eq!(
+      GStreamerBusEvent::from_message(&qos),
+      Some(GStreamerBusEvent::Qos {
+        element: "test".to_string(),
+        live: true,
+        running_time: Some(Duration::from_millis(40)),
+        jitter: -1000,
+        proportion: 0.5,
+        quality: 3,
+      })
+    );
+
+    assert_eq!(
+      GStreamerBusEvent::from_message(&gst::message::Eos::new()),
+      Some(GStreamerBusEvent::Eos)
+    );
+  }
+
+  #[test]
+  fn ignores_other_messages() {
+    let pipeline = pipeline();
+    let latency = gst::message::Latency::builder().src(&pipeline).build();
+    assert_eq!(GStreamerBusEvent::from_message(&latency), None);
+  }
+
+  #[test]
+  fn poll_records_first_and_last_error() {
+    let pipeline = pipeline();
+    let monitor = GStreamerBusMonitor::new(&pipeline).unwrap();
+    let bus = pipeline.bus().unwrap();
+    bus.post(error(&pipeline, "first")).unwrap();
+    bus.post(error(&pipeline, "second")).unwrap();
+    bus.post(gst::message::Eos::new()).unwrap();
+
+    let message = |event: Option<GStreamerBusEvent>| match event {
+      Some(GStreamerBusEvent::Error { message, .. }) => Some(message),
+      _ => None,
+    };
+    assert_eq!(message(monitor.poll()).as_deref(), Some("first"));
+    // 克隆体共享事件记录
+    let clone = monitor.clone();
+    assert_eq!(message(clone.last_error()).as_deref(), Some("second"));
+    assert!(clone.is_eos());
+    assert_eq!(clone.events().len(), 3);
+    assert_eq!(monitor.poll(), None);
+  }
+}